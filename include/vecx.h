@@ -0,0 +1,106 @@
+#ifndef VECX_H
+#define VECX_H
+
+/* 由 cbindgen 生成，不要手工修改；修改 src/ffi.rs 后重新生成 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+
+
+// 不透明的字节缓冲区句柄
+typedef struct VecxU8 VecxU8;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 创建一个空缓冲区，不分配数据内存
+VecxU8 *vecx_u8_new(void);
+
+// 创建一个预留 `cap` 字节的空缓冲区
+VecxU8 *vecx_u8_with_capacity(size_t cap);
+
+// 在末尾追加一个字节
+//
+// # Safety
+//
+// `v` 必须是本模块创建、还没有释放的句柄
+void vecx_u8_push(VecxU8 *v, uint8_t byte);
+
+// 在末尾追加 `len` 个字节
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`；`data` 必须指向至少 `len` 个可读字节（`len == 0` 时可以为 NULL），
+// 并且不能指向 `v` 自己的数据（追加时可能重新分配）
+void vecx_u8_extend(VecxU8 *v,
+                    const uint8_t *data,
+                    size_t len);
+
+// 数据起始地址，可以读写前 `vecx_u8_len` 个字节
+//
+// 下一次 `push`/`extend`/`reserve` 之后可能失效；空缓冲区返回一个非 NULL 但不可访问的地址
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`
+uint8_t *vecx_u8_data(VecxU8 *v);
+
+// 已写入的字节数
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`
+size_t vecx_u8_len(const VecxU8 *v);
+
+// 当前容量
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`
+size_t vecx_u8_capacity(const VecxU8 *v);
+
+// 预留至少 `additional` 字节，之后追加这么多字节不会再重新分配
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`
+void vecx_u8_reserve(VecxU8 *v, size_t additional);
+
+// 释放句柄，把数据内存的所有权交给调用者，返回数据指针并写出长度和容量
+//
+// 返回的内存不能用 C 的 `free` 释放，只能原样交给 `vecx_u8_from_raw` 收回
+//
+// # Safety
+//
+// `v` 同 `vecx_u8_push`，调用之后不能再使用；`len`、`cap` 必须是可写的有效指针
+uint8_t *vecx_u8_into_raw(VecxU8 *v,
+                          size_t *len,
+                          size_t *cap);
+
+// 用 `vecx_u8_into_raw` 得到的三元组重新创建句柄
+//
+// # Safety
+//
+// `data`、`len`、`cap` 必须原样来自同一次 `vecx_u8_into_raw`，并且 `len` 只能改小
+// （不能超过容量，多出来的字节必须已经写过）；这块内存之后归新句柄所有
+VecxU8 *vecx_u8_from_raw(uint8_t *data,
+                         size_t len,
+                         size_t cap);
+
+// 释放句柄和它的数据；`v` 为 NULL 时什么都不做
+//
+// # Safety
+//
+// `v` 为 NULL，或者是本模块创建、还没有释放的句柄
+void vecx_u8_free(VecxU8 *v);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VECX_H */
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::iter::FusedIterator;
+
 type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T> {
@@ -7,17 +10,281 @@ struct Node<T> {
 
 pub struct LinkedList<T> {
     head: Link<T>,
+    len: usize,
+}
+
+// 第二个字段是剩余元素个数
+pub struct Iter<'a, T: 'a>(Option<&'a Node<T>>, usize);
+
+pub struct IterMut<'a, T: 'a>(Option<&'a mut Node<T>>, usize);
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+/// 栈式的单链表：`push`、`pop`、`peek` 都在表头进行，迭代顺序是从表头（最后 push 的元素）开始
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList { head: None, len: 0 }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let next = self.head.take();
+        self.head = Some(Box::new(Node { elem, next }));
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.head.take().map(|node| {
+            let node = *node;
+            self.head = node.next;
+            self.len -= 1;
+            node.elem
+        })
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node| &mut node.elem)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.head.as_deref(), self.len)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.head.as_deref_mut(), self.len)
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|elem| elem == x)
+    }
+
+    /// 原地反转，只调整 next 指针，不移动元素
+    pub fn reverse(&mut self) {
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = prev;
+            prev = Some(node);
+        }
+        self.head = prev;
+    }
+
+    /// 把 `other` 的所有节点接到末尾，`other` 变为空，需要 O(self.len) 找到末尾
+    pub fn append(&mut self, other: &mut Self) {
+        let len = other.len;
+        *tail(&mut self.head) = other.head.take();
+        self.len += len;
+        other.len = 0;
+    }
+
+    /// 从下标 `at` 处断开，返回 `[at, len)` 这一段，自身保留 `[0, at)`
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "index out of bounds");
+
+        let mut cur = &mut self.head;
+        for _ in 0..at {
+            cur = &mut cur.as_mut().unwrap().next;
+        }
+        let rest = LinkedList { head: cur.take(), len: self.len - at };
+        self.len = at;
+        rest
+    }
+
+    /// 只保留 `f` 返回 true 的元素，顺序不变
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let len = &mut self.len;
+        let mut cur = &mut self.head;
+        while cur.is_some() {
+            if f(&cur.as_ref().unwrap().elem) {
+                cur = &mut cur.as_mut().unwrap().next;
+            } else {
+                let mut node = cur.take().unwrap();
+                *cur = node.next.take();
+                *len -= 1;
+            }
+        }
+    }
+}
+
+// 末尾那个空的 Link，往这里写入就是接到链表尾部
+fn tail<T>(mut cur: &mut Link<T>) -> &mut Link<T> {
+    while cur.is_some() {
+        cur = &mut cur.as_mut().unwrap().next;
+    }
+    cur
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        LinkedList::new()
+    }
+}
+
+// 默认的 drop 会沿着 next 递归，几十万个节点就能把栈撑爆
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            // 先把后继摘下来，node 离开作用域时已经没有子节点了
+            cur = node.next.take();
+        }
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        let mut list = LinkedList { head: None, len: self.len };
+        // 始终指向新链表末尾的空 Link，逐个往后接
+        let mut tail = &mut list.head;
+        let mut cur = self.head.as_deref();
+        while let Some(node) = cur {
+            let new = tail.insert(Box::new(Node { elem: node.elem.clone(), next: None }));
+            tail = &mut new.next;
+            cur = node.next.as_deref();
+        }
+        list
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len { return false; }
+
+        let (mut a, mut b) = (self.head.as_deref(), other.head.as_deref());
+        loop {
+            match (a, b) {
+                (None, None) => return true,
+                (Some(x), Some(y)) if x.elem == y.elem => {
+                    a = x.next.as_deref();
+                    b = y.next.as_deref();
+                }
+                _ => return false,
+            }
+        }
+    }
 }
 
-pub struct IterMut<'a, T: 'a>(Option<&'a mut Node<T>>);
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 按迭代顺序收集：第一个元素成为表头，和 `iter()` 的顺序一致
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+/// 依次接到末尾，和 `append` 一样保持顺序
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let len = &mut self.len;
+        let mut tail = tail(&mut self.head);
+        for elem in iter {
+            let node = tail.insert(Box::new(Node { elem, next: None }));
+            tail = &mut node.next;
+            *len += 1;
+        }
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.map(|node| {
+            self.0 = node.next.as_deref();
+            self.1 -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.1, Some(self.1))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+impl<'a, T> FusedIterator for Iter<'a, T> {}
 
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.0.take().map(|node| {
-            self.0 = node.next.as_mut().map(|node| &mut **node);
+            self.0 = node.next.as_deref_mut();
+            self.1 -= 1;
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.1, Some(self.1))
+    }
 }
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
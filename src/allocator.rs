@@ -0,0 +1,95 @@
+//! RawVec / Vecx 使用的分配器接口
+//!
+//! 标准库的 `Allocator` trait 还没有稳定，这里定义一个最小的版本：
+//! `Global` 直接转发给全局分配器，其他分配器（比如 `bump::Bump`）通过 `&A` 传给 `Vecx`
+
+use std::alloc::{self, Layout};
+use std::error::Error;
+use std::fmt;
+use std::ptr::{self, NonNull};
+
+/// 分配失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl Error for AllocError {}
+
+/// # Safety
+///
+/// 实现者必须保证：`allocate`/`grow` 返回的内存满足 `layout` 的大小和对齐要求，
+/// 在被 `deallocate`（或被 `grow` 换掉）之前一直有效，并且不会同时分给别人
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    ///
+    /// `ptr` 必须是这个分配器用同样的 `layout` 分配出来、还没有释放的内存
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// 把一块内存扩大到 `new_layout`，原有内容保持不变；默认实现是分配新内存再拷贝
+    ///
+    /// # Safety
+    ///
+    /// `ptr` 必须是用 `old_layout` 分配出来的，且 `new_layout.size() >= old_layout.size()`
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+/// 全局分配器，`Vecx<T>` 默认使用它
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+// 零大小的请求不真正分配，返回一个满足对齐的悬垂指针
+fn dangling(layout: Layout) -> NonNull<u8> {
+    unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) }
+}
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            alloc::dealloc(ptr.as_ptr(), layout);
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        // 对齐不变时可以直接 realloc
+        if old_layout.size() == 0 || old_layout.align() != new_layout.align() {
+            let new_ptr = self.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+            return Ok(new_ptr);
+        }
+        NonNull::new(alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size())).ok_or(AllocError)
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).grow(ptr, old_layout, new_layout)
+    }
+}
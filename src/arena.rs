@@ -0,0 +1,217 @@
+use std::cell::RefCell;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use crate::vecx::into_iter::IntoIterx;
+use crate::vecx::raw_vec::RawVec;
+use crate::vecx::Vecx;
+
+// 第一块大约占用的字节数，之后每块的容量翻倍
+const FIRST_CHUNK_BYTES: usize = 4096;
+
+/// 类型化的 arena：对象分配进一块块固定容量的 `RawVec`，块满了就开新块，
+/// 已经分配的对象永远不会被移动，所以 `alloc` 可以只通过 `&self` 返回 `&mut T`
+///
+/// 单个对象不能释放，所有对象在 arena 被 drop（或 `clear`）时一起 drop
+pub struct Arena<T> {
+    // 最后一块是正在填充的块
+    chunks: RefCell<Vecx<Chunk<T>>>,
+}
+
+/// 一块存储：前 `len` 个位置已初始化，容量用完之前不会重新分配
+struct Chunk<T> {
+    buf: RawVec<T>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    chunks: slice::IterMut<'a, Chunk<T>>,
+    cur: slice::IterMut<'a, T>,
+    len: usize,
+}
+
+impl<T> Chunk<T> {
+    fn with_capacity(cap: usize) -> Self {
+        Chunk { buf: RawVec::with_capacity(cap), len: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.cap - self.len
+    }
+
+    // 下一个空位
+    fn end(&self) -> *mut T {
+        unsafe { self.buf.ptr.as_ptr().add(self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len) }
+    }
+
+    fn clear(&mut self) {
+        // 先把 len 清零：某个元素的 Drop panic 时，drop_in_place 会继续 drop 剩下的元素，
+        // 之后这块不会再去碰这些元素
+        let len = mem::replace(&mut self.len, 0);
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.buf.ptr.as_ptr(), len));
+        }
+    }
+}
+
+impl<T> Drop for Chunk<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena { chunks: RefCell::new(Vecx::new()) }
+    }
+
+    /// 预先分配一块能放下 `cap` 个对象的存储
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut chunks = Vecx::new();
+        chunks.push(Chunk::with_capacity(cap));
+        Arena { chunks: RefCell::new(chunks) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(|c| c.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 和 typed-arena 一样，每次返回的都是新分配、互不重叠的对象
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        let chunk = reserve(&mut chunks, 1);
+        unsafe {
+            let ptr = chunk.end();
+            ptr.write(value);
+            chunk.len += 1;
+            // 指向块内的存储，块不会重新分配，RefCell 的借用结束之后依然有效
+            &mut *ptr
+        }
+    }
+
+    /// 把迭代器的所有元素连续地分配在同一块里
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend<I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+    {
+        // 迭代器里可能又调用了 `alloc`，所以先收集起来，再去借用 chunks
+        let mut items = Vecx::new();
+        for item in iter {
+            items.push(item);
+        }
+        let n = items.len();
+        if n == 0 {
+            return &mut [];
+        }
+
+        let mut chunks = self.chunks.borrow_mut();
+        let chunk = reserve(&mut chunks, n);
+        unsafe {
+            let dst = chunk.end();
+            for (i, item) in items.drain().enumerate() {
+                dst.add(i).write(item);
+            }
+            chunk.len += n;
+            slice::from_raw_parts_mut(dst, n)
+        }
+    }
+
+    /// 按分配顺序遍历所有对象
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let len = self.len();
+        let mut chunks = self.chunks.get_mut().iter_mut();
+        let cur = match chunks.next() {
+            Some(chunk) => chunk.as_mut_slice().iter_mut(),
+            None => [].iter_mut(),
+        };
+        IterMut { chunks, cur, len }
+    }
+
+    /// drop 所有对象，保留最后（也是最大）的一块存储供之后复用
+    pub fn clear(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let last = chunks.pop();
+        *chunks = Vecx::new();
+        if let Some(mut last) = last {
+            last.clear();
+            chunks.push(last);
+        }
+    }
+
+    /// 把所有对象按分配顺序搬进一个 Vecx
+    pub fn into_vecx(mut self) -> Vecx<T> {
+        let mut out = Vecx::with_capacity(self.len());
+        for chunk in self.chunks.get_mut().iter_mut() {
+            // 先清零 len，元素的所有权转移给 out，块只负责释放存储
+            let len = mem::replace(&mut chunk.len, 0);
+            for i in 0..len {
+                out.push(unsafe { ptr::read(chunk.buf.ptr.as_ptr().add(i)) });
+            }
+        }
+        out
+    }
+}
+
+// 保证最后一块至少还有 `additional` 个空位，放不下就开一块新的，已有的块不动
+fn reserve<T>(chunks: &mut Vecx<Chunk<T>>, additional: usize) -> &mut Chunk<T> {
+    let fits = chunks.last().is_some_and(|c| c.remaining() >= additional);
+    if !fits {
+        let first = (FIRST_CHUNK_BYTES / mem::size_of::<T>().max(1)).max(1);
+        let cap = chunks.last().map_or(first, |c| c.buf.cap.saturating_mul(2));
+        chunks.push(Chunk::with_capacity(cap.max(additional)));
+    }
+    chunks.last_mut().unwrap()
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T> IntoIterator for Arena<T> {
+    type Item = T;
+    type IntoIter = IntoIterx<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vecx().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Arena<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(elem) = self.cur.next() {
+                self.len -= 1;
+                return Some(elem);
+            }
+            self.cur = self.chunks.next()?.as_mut_slice().iter_mut();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
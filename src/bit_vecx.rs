@@ -0,0 +1,637 @@
+//! 按位压缩存放的 bool 向量，以及在它上面建立的 rank/select 索引
+
+use std::fmt;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Index, Range};
+
+use crate::vecx::Vecx;
+
+const WORD: usize = 64;
+
+/// 每个字存 64 位，第 i 位在 `words[i / 64]` 的第 `i % 64` 位
+///
+/// 不变式：最后一个字里超出 `len` 的位总是 0，这样 `count_ones`、比较等操作可以直接按字处理
+pub struct BitVecx {
+    words: Vecx<u64>,
+    len: usize,
+}
+
+/// 一段连续位的只读视图
+#[derive(Clone, Copy)]
+pub struct BitSlice<'a> {
+    words: &'a [u64],
+    start: usize,
+    len: usize,
+}
+
+/// 一段连续位的可写视图
+pub struct BitSliceMut<'a> {
+    words: &'a mut [u64],
+    start: usize,
+    len: usize,
+}
+
+pub struct Iter<'a> {
+    bits: BitSlice<'a>,
+    front: usize,
+    back: usize,
+}
+
+/// 逐字用 `trailing_zeros` 找出所有为 1（或为 0）的位置
+pub struct IterOnes<'a> {
+    words: &'a [u64],
+    // 当前字还没有返回的位；iter_zeros 时存的是取反后的字
+    cur: u64,
+    index: usize,
+    invert: bool,
+    len: usize,
+}
+
+// 低 n 位全为 1 的掩码，n 可以是 64
+fn low_mask(n: usize) -> u64 {
+    if n >= WORD {
+        !0
+    } else {
+        (1u64 << n) - 1
+    }
+}
+
+fn get_bit(words: &[u64], i: usize) -> bool {
+    words[i / WORD] >> (i % WORD) & 1 != 0
+}
+
+fn set_bit(words: &mut [u64], i: usize, value: bool) {
+    let mask = 1u64 << (i % WORD);
+    if value {
+        words[i / WORD] |= mask;
+    } else {
+        words[i / WORD] &= !mask;
+    }
+}
+
+// 字中第 k 个（从 0 开始）为 1 的位的位置，k 必须小于 count_ones。
+// 有 BMI2 时用 pdep 把 1 << k 放到 word 的第 k 个 1 上，一条指令完成
+#[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+fn select_in_word(word: u64, k: u32) -> usize {
+    use std::arch::x86_64::_pdep_u64;
+    unsafe { _pdep_u64(1 << k, word).trailing_zeros() as usize }
+}
+
+// 没有 pdep 时清掉最低的 k 个 1，剩下最低的 1 就是要找的位置
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+fn select_in_word(mut word: u64, k: u32) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+impl BitVecx {
+    pub fn new() -> Self {
+        BitVecx { words: Vecx::new(), len: 0 }
+    }
+
+    pub fn with_capacity(bits: usize) -> Self {
+        BitVecx { words: Vecx::with_capacity(bits.div_ceil(WORD)), len: 0 }
+    }
+
+    /// `len` 个值都为 `value` 的位
+    pub fn repeat(value: bool, len: usize) -> Self {
+        let mut bits = BitVecx::with_capacity(len);
+        let fill = if value { !0 } else { 0 };
+        for _ in 0..len.div_ceil(WORD) {
+            bits.words.push(fill);
+        }
+        bits.len = len;
+        bits.clear_tail();
+        bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 底层的字，最后一个字中超出 `len` 的位为 0
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    // 维护不变式：清掉最后一个字里超出 len 的位
+    fn clear_tail(&mut self) {
+        let tail = self.len % WORD;
+        if tail != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= low_mask(tail);
+        }
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(WORD) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        set_bit(&mut self.words, self.len - 1, value);
+    }
+
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = get_bit(&self.words, self.len - 1);
+        set_bit(&mut self.words, self.len - 1, false);
+        self.len -= 1;
+        if self.len.is_multiple_of(WORD) {
+            self.words.pop();
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(get_bit(&self.words, index))
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "index out of bounds");
+        set_bit(&mut self.words, index, value);
+    }
+
+    /// 在 `index` 处插入一位，后面的位整体后移，按字移动
+    pub fn insert(&mut self, index: usize, value: bool) {
+        assert!(index <= self.len, "index out of bounds");
+        self.push(false);
+
+        let first = index / WORD;
+        let low = low_mask(index % WORD);
+        let w = self.words[first];
+        let mut carry = w >> 63;
+        self.words[first] = (w & low) | ((w & !low) << 1) | ((value as u64) << (index % WORD));
+        for word in self.words[first + 1..].iter_mut() {
+            let next = *word >> 63;
+            *word = (*word << 1) | carry;
+            carry = next;
+        }
+    }
+
+    /// 删除 `index` 处的位并返回它，后面的位整体前移
+    pub fn remove(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "index out of bounds");
+        let value = get_bit(&self.words, index);
+
+        let first = index / WORD;
+        let low = low_mask(index % WORD);
+        let n = self.words.len();
+        for i in first..n {
+            let w = self.words[i];
+            let next = if i + 1 < n { self.words[i + 1] & 1 } else { 0 };
+            let shifted = (w >> 1) | (next << 63);
+            self.words[i] = if i == first { (w & low) | (shifted & !low) } else { shifted };
+        }
+        // 最后一位已经移走，现在是 0
+        self.pop();
+        value
+    }
+
+    pub fn clear(&mut self) {
+        while self.words.pop().is_some() {}
+        self.len = 0;
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.as_bitslice().iter()
+    }
+
+    /// 所有为 1 的位的下标，从小到大
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes::new(&self.words, false, self.len)
+    }
+
+    /// 所有为 0 的位的下标，从小到大
+    pub fn iter_zeros(&self) -> IterOnes<'_> {
+        IterOnes::new(&self.words, true, self.len)
+    }
+
+    pub fn and(&mut self, other: &BitVecx) {
+        self.zip_words(other, |a, b| a & b);
+    }
+
+    pub fn or(&mut self, other: &BitVecx) {
+        self.zip_words(other, |a, b| a | b);
+    }
+
+    pub fn xor(&mut self, other: &BitVecx) {
+        self.zip_words(other, |a, b| a ^ b);
+    }
+
+    pub fn not(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = !*w);
+        self.clear_tail();
+    }
+
+    fn zip_words(&mut self, other: &BitVecx, f: impl Fn(u64, u64) -> u64) {
+        assert_eq!(self.len, other.len, "bit vector length mismatch");
+        for (a, &b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a = f(*a, b);
+        }
+    }
+
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        BitSlice { words: &self.words, start: 0, len: self.len }
+    }
+
+    pub fn as_mut_bitslice(&mut self) -> BitSliceMut<'_> {
+        BitSliceMut { words: &mut self.words, start: 0, len: self.len }
+    }
+
+    /// `range` 内的位的只读视图
+    pub fn slice(&self, range: Range<usize>) -> BitSlice<'_> {
+        self.as_bitslice().slice(range)
+    }
+
+    pub fn slice_mut(&mut self, range: Range<usize>) -> BitSliceMut<'_> {
+        assert!(range.start <= range.end && range.end <= self.len, "range out of bounds");
+        BitSliceMut { words: &mut self.words, start: range.start, len: range.end - range.start }
+    }
+}
+
+impl<'a> BitSlice<'a> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(get_bit(self.words, self.start + index))
+        } else {
+            None
+        }
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> BitSlice<'a> {
+        assert!(range.start <= range.end && range.end <= self.len, "range out of bounds");
+        BitSlice { words: self.words, start: self.start + range.start, len: range.end - range.start }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+        // 首尾两个字只统计落在视图内的部分
+        let end = self.start + self.len;
+        let (first, last) = (self.start / WORD, (end - 1) / WORD);
+        let mut count = 0;
+        for i in first..=last {
+            let mut w = self.words[i];
+            if i == first {
+                w &= !low_mask(self.start % WORD);
+            }
+            if i == last {
+                w &= low_mask(end - last * WORD);
+            }
+            count += w.count_ones() as usize;
+        }
+        count
+    }
+
+    pub fn iter(&self) -> Iter<'a> {
+        Iter { bits: *self, front: 0, back: self.len }
+    }
+
+    pub fn to_bitvecx(&self) -> BitVecx {
+        self.iter().collect()
+    }
+}
+
+impl<'a> BitSliceMut<'a> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.as_bitslice().get(index)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "index out of bounds");
+        set_bit(self.words, self.start + index, value);
+    }
+
+    pub fn fill(&mut self, value: bool) {
+        for i in 0..self.len {
+            set_bit(self.words, self.start + i, value);
+        }
+    }
+
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        BitSlice { words: self.words, start: self.start, len: self.len }
+    }
+}
+
+impl<'a> IterOnes<'a> {
+    fn new(words: &'a [u64], invert: bool, len: usize) -> Self {
+        let mut iter = IterOnes { words, cur: 0, index: 0, invert, len };
+        iter.cur = iter.load(0);
+        iter
+    }
+
+    // 读出第 i 个字（取反时去掉超出 len 的位）
+    fn load(&self, i: usize) -> u64 {
+        let Some(&w) = self.words.get(i) else { return 0 };
+        if !self.invert {
+            return w;
+        }
+        let valid = self.len - i * WORD;
+        !w & low_mask(valid)
+    }
+}
+
+impl Default for BitVecx {
+    fn default() -> Self {
+        BitVecx::new()
+    }
+}
+
+impl Clone for BitVecx {
+    fn clone(&self) -> Self {
+        let mut words = Vecx::with_capacity(self.words.len());
+        for &w in self.words.iter() {
+            words.push(w);
+        }
+        BitVecx { words, len: self.len }
+    }
+}
+
+impl PartialEq for BitVecx {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.words[..] == other.words[..]
+    }
+}
+
+impl Eq for BitVecx {}
+
+impl fmt::Debug for BitVecx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_bitslice().fmt(f)
+    }
+}
+
+impl fmt::Debug for BitSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+static TRUE: bool = true;
+static FALSE: bool = false;
+
+impl Index<usize> for BitVecx {
+    type Output = bool;
+    fn index(&self, index: usize) -> &bool {
+        match self.get(index).expect("index out of bounds") {
+            true => &TRUE,
+            false => &FALSE,
+        }
+    }
+}
+
+impl BitAndAssign<&BitVecx> for BitVecx {
+    fn bitand_assign(&mut self, rhs: &BitVecx) {
+        self.and(rhs);
+    }
+}
+
+impl BitOrAssign<&BitVecx> for BitVecx {
+    fn bitor_assign(&mut self, rhs: &BitVecx) {
+        self.or(rhs);
+    }
+}
+
+impl BitXorAssign<&BitVecx> for BitVecx {
+    fn bitxor_assign(&mut self, rhs: &BitVecx) {
+        self.xor(rhs);
+    }
+}
+
+impl FromIterator<bool> for BitVecx {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitVecx::new();
+        bits.extend(iter);
+        bits
+    }
+}
+
+impl Extend<bool> for BitVecx {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVecx {
+    type Item = bool;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.bits.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.bits.get(self.back)
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+impl<'a> Iterator for IterOnes<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cur == 0 {
+            self.index += 1;
+            if self.index >= self.words.len() {
+                return None;
+            }
+            self.cur = self.load(self.index);
+        }
+        let bit = self.cur.trailing_zeros() as usize;
+        // 清掉最低的 1
+        self.cur &= self.cur - 1;
+        Some(self.index * WORD + bit)
+    }
+}
+
+/// 在 `BitVecx` 上建立的 rank/select 索引
+///
+/// 每 512 位一个超级块，记录之前所有 1 的个数（u64）；每个字再记录从所在超级块开头算起的
+/// 1 的个数（u16）。`rank` 是两次查表加一次 popcount，O(1)；`select` 先在超级块上二分，
+/// 再在最多 8 个字里顺序查找。额外空间约为原始位数的 37.5%（每 512 位 64 + 8×16 位）
+pub struct RankSelect {
+    bits: BitVecx,
+    supers: Vecx<u64>,
+    blocks: Vecx<u16>,
+    ones: usize,
+}
+
+const WORDS_PER_SUPER: usize = 8;
+const SUPER: usize = WORD * WORDS_PER_SUPER;
+
+impl RankSelect {
+    pub fn new(bits: BitVecx) -> Self {
+        let mut supers = Vecx::with_capacity(bits.words.len().div_ceil(WORDS_PER_SUPER));
+        let mut blocks = Vecx::with_capacity(bits.words.len());
+        let mut total = 0u64;
+        let mut in_super = 0u16;
+        for (i, &w) in bits.words.iter().enumerate() {
+            if i % WORDS_PER_SUPER == 0 {
+                total += in_super as u64;
+                supers.push(total);
+                in_super = 0;
+            }
+            blocks.push(in_super);
+            in_super += w.count_ones() as u16;
+        }
+        let ones = (total + in_super as u64) as usize;
+        RankSelect { bits, supers, blocks, ones }
+    }
+
+    pub fn bits(&self) -> &BitVecx {
+        &self.bits
+    }
+
+    pub fn into_bits(self) -> BitVecx {
+        self.bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.len == 0
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// `[0, index)` 中 1 的个数，`index` 可以等于 `len`
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.bits.len, "index out of bounds");
+        if index == self.bits.len {
+            return self.ones;
+        }
+        let w = index / WORD;
+        let partial = self.bits.words[w] & low_mask(index % WORD);
+        self.supers[w / WORDS_PER_SUPER] as usize + self.blocks[w] as usize + partial.count_ones() as usize
+    }
+
+    /// `[0, index)` 中 0 的个数
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    // 二分查找最后一个「之前的个数 <= k」的超级块，`before(s)` 随 s 单调不减
+    fn find_super(&self, k: usize, before: impl Fn(usize) -> usize) -> usize {
+        let (mut lo, mut hi) = (0, self.supers.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if before(mid) <= k {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo - 1
+    }
+
+    /// 第 `k` 个（从 0 开始）1 的位置
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+        let s = self.find_super(k, |s| self.supers[s] as usize);
+        let mut rest = k - self.supers[s] as usize;
+        let end = ((s + 1) * WORDS_PER_SUPER).min(self.bits.words.len());
+        for w in s * WORDS_PER_SUPER..end {
+            let word = self.bits.words[w];
+            let n = word.count_ones() as usize;
+            if rest < n {
+                return Some(w * WORD + select_in_word(word, rest as u32));
+            }
+            rest -= n;
+        }
+        unreachable!()
+    }
+
+    /// 第 `k` 个（从 0 开始）0 的位置
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.bits.len - self.ones {
+            return None;
+        }
+        // 超级块 s 之前 0 的个数是 s·512 - supers[s]
+        let zeros_before = |s: usize| s * SUPER - self.supers[s] as usize;
+        let s = self.find_super(k, zeros_before);
+        let mut rest = k - zeros_before(s);
+        let end = ((s + 1) * WORDS_PER_SUPER).min(self.bits.words.len());
+        for w in s * WORDS_PER_SUPER..end {
+            // 最后一个字超出 len 的位不算 0
+            let valid = low_mask(self.bits.len - w * WORD);
+            let word = !self.bits.words[w] & valid;
+            let n = word.count_ones() as usize;
+            if rest < n {
+                return Some(w * WORD + select_in_word(word, rest as u32));
+            }
+            rest -= n;
+        }
+        unreachable!()
+    }
+}
@@ -0,0 +1,222 @@
+//! 布隆过滤器，位数组用 `Vecx<u64>` 紧凑存放
+//!
+//! 哈希使用 crate 内的 FNV-1a，经 splitmix64 打散后再派生第二个哈希做 double hashing，
+//! 不依赖 `RandomState` 的随机种子，所以序列化之后在同一平台上可以继续使用
+
+use std::error::Error;
+use std::f64::consts::LN_2;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::vecx::Vecx;
+
+const MAGIC: &[u8; 4] = b"BLM1";
+// magic + 位数 u64 + 哈希函数个数 u32
+const HEADER_LEN: usize = 4 + 8 + 4;
+
+pub struct BloomFilter<T: ?Sized> {
+    bits: Vecx<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+    _marker: PhantomData<fn(&T)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BloomError {
+    /// 位数或哈希函数个数不同的过滤器不能合并
+    Incompatible,
+    /// 反序列化的数据不合法
+    Malformed(&'static str),
+}
+
+/// 64 位 FNV-1a
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+impl<T: ?Sized> BloomFilter<T> {
+    /// 按预计的元素个数和目标误判率选择位数和哈希函数个数
+    ///
+    /// m = -n·ln(p) / (ln 2)²，k = (m / n)·ln 2
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        assert!(fp_rate > 0.0 && fp_rate < 1.0, "false positive rate must be in (0, 1)");
+        let n = expected_items.max(1) as f64;
+        let m = (-n * fp_rate.ln() / (LN_2 * LN_2)).ceil().max(1.0);
+        let k = (m / n * LN_2).round().max(1.0);
+        BloomFilter::with_params(m as usize, k as u32)
+    }
+
+    pub fn with_params(num_bits: usize, num_hashes: u32) -> Self {
+        assert!(num_bits != 0, "bloom filter needs at least one bit");
+        assert!(num_hashes != 0, "bloom filter needs at least one hash function");
+        let words = num_bits.div_ceil(64);
+        let mut bits = Vecx::with_capacity(words);
+        for _ in 0..words {
+            bits.push(0);
+        }
+        BloomFilter { bits, num_bits, num_hashes, _marker: PhantomData }
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// 被置位的位数
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// 根据置位比例估计插入过的不同元素个数：n ≈ -(m / k)·ln(1 - X / m)
+    pub fn estimated_len(&self) -> f64 {
+        let m = self.num_bits as f64;
+        let x = self.count_ones() as f64;
+        if x >= m {
+            return f64::INFINITY;
+        }
+        -(m / self.num_hashes as f64) * (1.0 - x / m).ln()
+    }
+
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.num_bits == other.num_bits && self.num_hashes == other.num_hashes
+    }
+
+    /// 并集：结果包含两边插入过的所有元素
+    pub fn union(&mut self, other: &Self) -> Result<(), BloomError> {
+        if !self.is_compatible(other) {
+            return Err(BloomError::Incompatible);
+        }
+        self.bits.iter_mut().zip(other.bits.iter()).for_each(|(a, b)| *a |= b);
+        Ok(())
+    }
+
+    /// 交集：两边都插入过的元素一定还在，误判率不低于两边各自的误判率
+    pub fn intersection(&mut self, other: &Self) -> Result<(), BloomError> {
+        if !self.is_compatible(other) {
+            return Err(BloomError::Incompatible);
+        }
+        self.bits.iter_mut().zip(other.bits.iter()).for_each(|(a, b)| *a &= b);
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vecx<u8> {
+        let mut out = Vecx::with_capacity(HEADER_LEN + self.bits.len() * 8);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&(self.num_bits as u64).to_le_bytes());
+        out.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in self.bits.iter() {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomError> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err(BloomError::Malformed("bad header"));
+        }
+        let num_bits = u64::from_le_bytes(bytes[4..12].try_into().unwrap());
+        let num_hashes = u32::from_le_bytes(bytes[12..16].try_into().unwrap());
+        let num_bits = usize::try_from(num_bits).map_err(|_| BloomError::Malformed("too many bits"))?;
+        if num_bits == 0 || num_hashes == 0 {
+            return Err(BloomError::Malformed("empty filter"));
+        }
+        let words = &bytes[HEADER_LEN..];
+        if words.len() != num_bits.div_ceil(64) * 8 {
+            return Err(BloomError::Malformed("bit array length mismatch"));
+        }
+
+        let mut bits = Vecx::with_capacity(words.len() / 8);
+        for word in words.chunks_exact(8) {
+            bits.push(u64::from_le_bytes(word.try_into().unwrap()));
+        }
+        // 最后一个字里超出 num_bits 的位必须是 0
+        let tail = num_bits % 64;
+        if tail != 0 && bits[bits.len() - 1] >> tail != 0 {
+            return Err(BloomError::Malformed("bits set past the end"));
+        }
+        Ok(BloomFilter { bits, num_bits, num_hashes, _marker: PhantomData })
+    }
+
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(&self.to_bytes()[..])
+    }
+
+    pub fn from_base64(s: &str) -> Result<Self, BloomError> {
+        let bytes = STANDARD.decode(s.trim()).map_err(|_| BloomError::Malformed("invalid base64"))?;
+        BloomFilter::from_bytes(&bytes)
+    }
+}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    // double hashing：第 i 个位置是 h1 + i·h2（mod m），h2 为奇数。
+    // FNV 对相邻的整数输出很接近，先用 splitmix64 打散，否则取模后位置会扎堆
+    fn positions(&self, item: &T) -> impl Iterator<Item = usize> {
+        let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+        item.hash(&mut hasher);
+        let h1 = splitmix64(hasher.finish());
+        let h2 = splitmix64(h1) | 1;
+        let m = self.num_bits as u64;
+        (0..self.num_hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        for pos in self.positions(item) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+    }
+
+    /// 返回 false 时一定没有插入过；返回 true 时可能是误判
+    pub fn contains(&self, item: &T) -> bool {
+        self.positions(item).all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("ones", &self.count_ones())
+            .finish()
+    }
+}
+
+impl fmt::Display for BloomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomError::Incompatible => f.write_str("bloom filters have different parameters"),
+            BloomError::Malformed(reason) => write!(f, "malformed bloom filter: {}", reason),
+        }
+    }
+}
+
+impl Error for BloomError {}
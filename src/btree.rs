@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::iter::FusedIterator;
 
 type Link<T> = Option<Box<Node<T>>>;
 
@@ -10,93 +11,458 @@ struct Node<T> {
 
 pub struct Tree<T> {
     root: Link<T>,
+    // 节点总数，各个迭代器的 size_hint 都由它得出
+    len: usize,
 }
 
-struct NodeIterMut<'a, T: 'a> {
-    elem: Option<&'a mut T>,
-    left: Option<&'a mut Node<T>>,
-    right: Option<&'a mut Node<T>>,
+/// 深度优先遍历的顺序，决定一个节点展开后 左子树、元素、右子树 三部分的先后
+#[derive(Clone, Copy)]
+enum Order {
+    In,
+    Pre,
+    Post,
 }
 
-enum State<'a, T: 'a> {
-    Elem(&'a mut T),
-    Node(&'a mut Node<T>),
+enum State<E, N> {
+    Elem(E),
+    Node(N),
 }
 
-pub struct IterMut<'a, T: 'a>(VecDeque<NodeIterMut<'a, T>>);
+/// 可以拆成 (元素, 左子树, 右子树) 的节点句柄：`&Node`、`&mut Node` 和 `Box<Node>`，
+/// 三种遍历形式共用同一套迭代逻辑
+trait Split: Sized {
+    type Elem;
+    fn split(self) -> (Self::Elem, Option<Self>, Option<Self>);
+}
 
-impl<T> Tree<T> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        let mut deque = VecDeque::new();
-        self.root.as_mut().map(|root| deque.push_front(root.iter_mut()));
-        IterMut(deque)
+impl<'a, T> Split for &'a Node<T> {
+    type Elem = &'a T;
+    fn split(self) -> (Self::Elem, Option<Self>, Option<Self>) {
+        (&self.elem, self.left.as_deref(), self.right.as_deref())
     }
 }
 
-impl<T> Node<T> {
-    pub fn iter_mut(&mut self) -> NodeIterMut<T> {
-        NodeIterMut {
-            elem: Some(&mut self.elem),
-            left: self.left.as_mut().map(|node| &mut **node),
-            right: self.right.as_mut().map(|node| &mut **node),
-        }
+impl<'a, T> Split for &'a mut Node<T> {
+    type Elem = &'a mut T;
+    fn split(self) -> (Self::Elem, Option<Self>, Option<Self>) {
+        let Node { elem, left, right } = self;
+        (elem, left.as_deref_mut(), right.as_deref_mut())
     }
 }
 
+impl<T> Split for Box<Node<T>> {
+    type Elem = T;
+    fn split(self) -> (Self::Elem, Option<Self>, Option<Self>) {
+        let Node { elem, left, right } = *self;
+        (elem, left, right)
+    }
+}
+
+/// 一个节点展开后还没有被取走的三部分，前后两端分别从 `front`、`back` 取
+struct Frame<E, N> {
+    parts: [Option<State<E, N>>; 3],
+    front: usize,
+    back: usize,
+}
+
+impl<E, N: Split<Elem = E>> Frame<E, N> {
+    fn new(node: N, order: Order) -> Self {
+        let (elem, left, right) = node.split();
+        let elem = Some(State::Elem(elem));
+        let left = left.map(State::Node);
+        let right = right.map(State::Node);
+        let parts = match order {
+            Order::In => [left, elem, right],
+            Order::Pre => [elem, left, right],
+            Order::Post => [left, right, elem],
+        };
+        Frame { parts, front: 0, back: 3 }
+    }
+}
 
-impl<'a, T> Iterator for NodeIterMut<'a, T> {
-    type Item = State<'a, T>;
+impl<E, N> Iterator for Frame<E, N> {
+    type Item = State<E, N>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.left.take() {
-            Some(node) => Some(State::Node(node)),
-            None => match self.elem.take() {
-                Some(elem) => Some(State::Elem(elem)),
-                None => match self.right.take() {
-                    Some(node) => Some(State::Node(node)),
-                    None => None,
+        while self.front < self.back {
+            let part = self.parts[self.front].take();
+            self.front += 1;
+            if part.is_some() { return part; }
+        }
+        None
+    }
+}
+
+impl<E, N> DoubleEndedIterator for Frame<E, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            let part = self.parts[self.back].take();
+            if part.is_some() { return part; }
+        }
+        None
+    }
+}
+
+/// 深度优先遍历：双端队列里的 Frame 首尾相接就是剩余的遍历序列，
+/// 正向展开的节点压到队首，反向展开的节点压到队尾
+struct Walk<N: Split> {
+    deque: VecDeque<Frame<N::Elem, N>>,
+    order: Order,
+    len: usize,
+}
+
+impl<N: Split> Walk<N> {
+    fn new(root: Option<N>, order: Order, len: usize) -> Self {
+        let mut deque = VecDeque::new();
+        if let Some(root) = root { deque.push_front(Frame::new(root, order)); }
+        Walk { deque, order, len }
+    }
+
+    fn next(&mut self) -> Option<N::Elem> {
+        loop {
+            match self.deque.front_mut().and_then(|frame| frame.next()) {
+                Some(State::Elem(elem)) => {
+                    self.len -= 1;
+                    return Some(elem);
+                }
+                Some(State::Node(node)) => self.deque.push_front(Frame::new(node, self.order)),
+                None => { self.deque.pop_front()?; }
+            }
+        }
+    }
+
+    fn next_back(&mut self) -> Option<N::Elem> {
+        loop {
+            match self.deque.back_mut().and_then(|frame| frame.next_back()) {
+                Some(State::Elem(elem)) => {
+                    self.len -= 1;
+                    return Some(elem);
                 }
+                Some(State::Node(node)) => self.deque.push_back(Frame::new(node, self.order)),
+                None => { self.deque.pop_back()?; }
             }
         }
     }
 }
 
-impl<'a, T> DoubleEndedIterator for NodeIterMut<'a, T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        match self.right.take() {
-            Some(node) => Some(State::Node(node)),
-            None => match self.elem.take() {
-                Some(elem) => Some(State::Elem(elem)),
-                None => match self.left.take() {
-                    Some(node) => Some(State::Node(node)),
-                    None => None,
+/// 广度优先遍历：普通的 FIFO 队列，只能单向
+struct Level<N> {
+    queue: VecDeque<N>,
+    len: usize,
+}
+
+impl<N: Split> Level<N> {
+    fn new(root: Option<N>, len: usize) -> Self {
+        Level { queue: root.into_iter().collect(), len }
+    }
+
+    fn next(&mut self) -> Option<N::Elem> {
+        let (elem, left, right) = self.queue.pop_front()?.split();
+        self.queue.extend(left);
+        self.queue.extend(right);
+        self.len -= 1;
+        Some(elem)
+    }
+}
+
+/// 共享引用的深度优先遍历，由 `iter`、`preorder`、`postorder` 创建
+pub struct Iter<'a, T: 'a>(Walk<&'a Node<T>>);
+
+/// 可变引用的深度优先遍历，由 `iter_mut`、`preorder_mut`、`postorder_mut` 创建
+pub struct IterMut<'a, T: 'a>(Walk<&'a mut Node<T>>);
+
+/// 获取所有权的深度优先遍历，由 `into_iter`、`into_preorder`、`into_postorder` 创建
+pub struct IntoIter<T>(Walk<Box<Node<T>>>);
+
+/// 共享引用的层序遍历
+pub struct LevelOrder<'a, T: 'a>(Level<&'a Node<T>>);
+
+/// 可变引用的层序遍历
+pub struct LevelOrderMut<'a, T: 'a>(Level<&'a mut Node<T>>);
+
+/// 获取所有权的层序遍历
+pub struct IntoLevelOrder<T>(Level<Box<Node<T>>>);
+
+impl<T> Tree<T> {
+    pub fn new() -> Self {
+        Tree { root: None, len: 0 }
+    }
+
+    pub fn leaf(elem: T) -> Self {
+        Tree::join(Tree::new(), elem, Tree::new())
+    }
+
+    /// 以 `elem` 为根，`left`、`right` 为左右子树组成一棵新树
+    pub fn join(mut left: Tree<T>, elem: T, mut right: Tree<T>) -> Self {
+        let len = left.len + right.len + 1;
+        let root = Some(Box::new(Node { elem, left: left.root.take(), right: right.root.take() }));
+        Tree { root, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 中序遍历
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(Walk::new(self.root.as_deref(), Order::In, self.len))
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(Walk::new(self.root.as_deref_mut(), Order::In, self.len))
+    }
+
+    /// 先序遍历：根、左子树、右子树
+    pub fn preorder(&self) -> Iter<'_, T> {
+        Iter(Walk::new(self.root.as_deref(), Order::Pre, self.len))
+    }
+
+    pub fn preorder_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(Walk::new(self.root.as_deref_mut(), Order::Pre, self.len))
+    }
+
+    pub fn into_preorder(mut self) -> IntoIter<T> {
+        IntoIter(Walk::new(self.root.take(), Order::Pre, self.len))
+    }
+
+    /// 后序遍历：左子树、右子树、根
+    pub fn postorder(&self) -> Iter<'_, T> {
+        Iter(Walk::new(self.root.as_deref(), Order::Post, self.len))
+    }
+
+    pub fn postorder_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(Walk::new(self.root.as_deref_mut(), Order::Post, self.len))
+    }
+
+    pub fn into_postorder(mut self) -> IntoIter<T> {
+        IntoIter(Walk::new(self.root.take(), Order::Post, self.len))
+    }
+
+    /// 层序遍历（BFS），同一层从左到右
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder(Level::new(self.root.as_deref(), self.len))
+    }
+
+    pub fn level_order_mut(&mut self) -> LevelOrderMut<'_, T> {
+        LevelOrderMut(Level::new(self.root.as_deref_mut(), self.len))
+    }
+
+    pub fn into_level_order(mut self) -> IntoLevelOrder<T> {
+        IntoLevelOrder(Level::new(self.root.take(), self.len))
+    }
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Tree::new()
+    }
+}
+
+// 默认的 drop 会沿着 Box<Node<T>> 递归，退化成链表的树会直接爆栈
+impl<T> Drop for Tree<T> {
+    fn drop(&mut self) {
+        // 不断右旋：把左孩子提成当前根，原来的根挂到它的右边，
+        // 等根节点没有左孩子时再把它拆下来释放，这时它已经没有子树了，不会递归，也不需要额外的栈
+        let mut cur = self.root.take();
+        while let Some(mut node) = cur {
+            cur = match node.left.take() {
+                Some(mut left) => {
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    Some(left)
                 }
+                None => node.right.take(),
+            };
+        }
+    }
+}
+
+impl<T: Clone> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        // 用显式的栈做后序遍历：子树先克隆好放进 done，轮到父节点时再取出来拼接，
+        // done 里存的是 Tree，克隆中途 panic 时也会走上面的迭代 drop
+        let mut todo = vec![];
+        let mut done: Vec<Tree<T>> = vec![];
+        todo.extend(self.root.as_deref().map(|node| (node, false)));
+
+        while let Some((node, expanded)) = todo.pop() {
+            if expanded {
+                let right = node.right.as_ref().and_then(|_| done.pop()).unwrap_or_default();
+                let left = node.left.as_ref().and_then(|_| done.pop()).unwrap_or_default();
+                done.push(Tree::join(left, node.elem.clone(), right));
+            } else {
+                todo.push((node, true));
+                todo.extend(node.right.as_deref().map(|node| (node, false)));
+                todo.extend(node.left.as_deref().map(|node| (node, false)));
             }
         }
+        done.pop().unwrap_or_default()
     }
 }
 
+impl<T: PartialEq> PartialEq for Tree<T> {
+    /// 形状和对应位置的元素都相同才相等
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len { return false; }
+
+        let mut todo = vec![(self.root.as_deref(), other.root.as_deref())];
+        while let Some(pair) = todo.pop() {
+            match pair {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    if a.elem != b.elem { return false; }
+                    todo.push((a.right.as_deref(), b.right.as_deref()));
+                    todo.push((a.left.as_deref(), b.left.as_deref()));
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for Tree<T> {}
+
+impl<T> IntoIterator for Tree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter(Walk::new(self.root.take(), Order::In, self.len))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Tree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Tree<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.0.front_mut().and_then(|node_it| node_it.next()) {
-                Some(State::Elem(elem)) => return Some(elem),
-                Some(State::Node(node)) => self.0.push_front(node.iter_mut()),
-                None => if let None = self.0.pop_front() { return None },
-            }
-        }
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
     }
 }
 
 impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.0.back_mut().and_then(|node_it| node_it.next_back()) {
-                Some(State::Elem(elem)) => return Some(elem),
-                Some(State::Node(node)) => self.0.push_back(node.iter_mut()),
-                None => if let None = self.0.pop_back() { return None },
-            }
-        }
+        self.0.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+// 剩下的节点逐个展开再丢弃，避免递归 drop 子树
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for LevelOrder<'a, T> {}
+impl<'a, T> FusedIterator for LevelOrder<'a, T> {}
+
+impl<'a, T> Iterator for LevelOrderMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for LevelOrderMut<'a, T> {}
+impl<'a, T> FusedIterator for LevelOrderMut<'a, T> {}
+
+impl<T> Iterator for IntoLevelOrder<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoLevelOrder<T> {}
+impl<T> FusedIterator for IntoLevelOrder<T> {}
+
+impl<T> Drop for IntoLevelOrder<T> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
     }
 }
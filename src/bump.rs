@@ -0,0 +1,219 @@
+use std::alloc::{self, Layout};
+use std::cell::{Cell, RefCell};
+use std::ptr::{self, NonNull};
+
+use crate::allocator::{AllocError, Allocator};
+use crate::vecx::Vecx;
+
+// 第一块的字节数，之后每块翻倍
+const FIRST_CHUNK_SIZE: usize = 4096;
+const CHUNK_ALIGN: usize = 16;
+
+/// bump 分配器：在一块块连续内存上只移动指针进行分配，单独释放基本什么都不做，
+/// 所有内存在 `reset` 或 drop 的时候一次性回收
+///
+/// 通过 `&Bump` 作为分配器使用：`Vecx::new_in(&bump)`
+pub struct Bump {
+    chunks: RefCell<Vecx<Chunk>>,
+    // 当前块的开头、下一个空闲字节和块的末尾
+    start: Cell<*mut u8>,
+    ptr: Cell<*mut u8>,
+    end: Cell<*mut u8>,
+    allocated: Cell<usize>,
+    wasted: Cell<usize>,
+}
+
+/// 分配统计
+///
+/// `capacity == allocated + wasted + 当前块剩余的字节数`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BumpStats {
+    /// 仍在使用中的字节数
+    pub allocated: usize,
+    pub chunks: usize,
+    /// 所有块的总字节数
+    pub capacity: usize,
+    /// 对齐填充、换块时丢下的块尾，以及释放后无法回收的内存
+    pub wasted: usize,
+}
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Chunk {
+    fn new(layout: Layout) -> Self {
+        let ptr = match NonNull::new(unsafe { alloc::alloc(layout) }) {
+            Some(p) => p,
+            None => alloc::handle_alloc_error(layout),
+        };
+        Chunk { ptr, layout }
+    }
+
+    fn end(&self) -> *mut u8 {
+        unsafe { self.ptr.as_ptr().add(self.layout.size()) }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+// 块内的内存只通过 &Bump 分出去，Bump 本身可以在没有借用时转移到其他线程
+unsafe impl Send for Bump {}
+
+impl Bump {
+    pub fn new() -> Self {
+        Bump {
+            chunks: RefCell::new(Vecx::new()),
+            start: Cell::new(ptr::null_mut()),
+            ptr: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+            allocated: Cell::new(0),
+            wasted: Cell::new(0),
+        }
+    }
+
+    /// 预先分配一块至少 `bytes` 字节的内存
+    pub fn with_capacity(bytes: usize) -> Self {
+        let bump = Bump::new();
+        if bytes != 0 {
+            bump.new_chunk(bytes);
+        }
+        bump
+    }
+
+    pub fn stats(&self) -> BumpStats {
+        let chunks = self.chunks.borrow();
+        BumpStats {
+            allocated: self.allocated.get(),
+            chunks: chunks.len(),
+            capacity: chunks.iter().map(|c| c.layout.size()).sum(),
+            wasted: self.wasted.get(),
+        }
+    }
+
+    /// 回收所有分配：只保留最后（也是最大）的一块，指针回到块首
+    ///
+    /// 需要 `&mut self`，所以所有借用了 `&Bump` 的 Vecx 都必须已经结束
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let last = chunks.pop();
+        *chunks = Vecx::new();
+        match last {
+            Some(chunk) => {
+                self.start.set(chunk.ptr.as_ptr());
+                self.ptr.set(chunk.ptr.as_ptr());
+                self.end.set(chunk.end());
+                chunks.push(chunk);
+            }
+            None => {
+                self.start.set(ptr::null_mut());
+                self.ptr.set(ptr::null_mut());
+                self.end.set(ptr::null_mut());
+            }
+        }
+        self.allocated.set(0);
+        self.wasted.set(0);
+    }
+
+    fn remaining(&self) -> usize {
+        self.end.get() as usize - self.ptr.get() as usize
+    }
+
+    // 在当前块里对齐后移动指针，放不下时返回 None
+    fn try_bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.ptr.get();
+        let pad = ptr.align_offset(layout.align());
+        let remaining = self.remaining();
+        if pad > remaining || layout.size() > remaining - pad {
+            return None;
+        }
+        unsafe {
+            let start = ptr.add(pad);
+            self.ptr.set(start.add(layout.size()));
+            self.wasted.set(self.wasted.get() + pad);
+            self.allocated.set(self.allocated.get() + layout.size());
+            Some(NonNull::new_unchecked(start))
+        }
+    }
+
+    // 开一块新块，当前块剩下的部分算作浪费
+    fn new_chunk(&self, min_size: usize) {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.last().map_or(0, |c| c.layout.size());
+        let size = last.saturating_mul(2).max(FIRST_CHUNK_SIZE).max(min_size);
+        let layout = Layout::from_size_align(size, CHUNK_ALIGN).expect("capacity overflow");
+        let chunk = Chunk::new(layout);
+
+        self.wasted.set(self.wasted.get() + self.remaining());
+        self.start.set(chunk.ptr.as_ptr());
+        self.ptr.set(chunk.ptr.as_ptr());
+        self.end.set(chunk.end());
+        chunks.push(chunk);
+    }
+
+    // `ptr` 是不是当前块里最近一次分配（紧挨着 bump 指针）。
+    // 只比较末尾不够：旧块里的分配可能恰好结束在新块的开头
+    fn is_last(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        let p = ptr.as_ptr();
+        p >= self.start.get() && p.wrapping_add(size) == self.ptr.get()
+    }
+}
+
+unsafe impl Allocator for Bump {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) });
+        }
+        if let Some(ptr) = self.try_bump(layout) {
+            return Ok(ptr);
+        }
+        // 新块按 CHUNK_ALIGN 对齐，更大的对齐要求预留足够的填充空间
+        let min_size = layout.size().checked_add(layout.align()).ok_or(AllocError)?;
+        self.new_chunk(min_size);
+        self.try_bump(layout).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let size = layout.size();
+        if size == 0 {
+            return;
+        }
+        self.allocated.set(self.allocated.get() - size);
+        // 最近一次分配可以直接退回指针，其余的只能等 reset
+        if self.is_last(ptr, size) {
+            self.ptr.set(ptr.as_ptr());
+        } else {
+            self.wasted.set(self.wasted.get() + size);
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let old_size = old_layout.size();
+        // 最近一次分配，并且当前块后面还放得下：原地扩大
+        if old_size != 0
+            && self.is_last(ptr, old_size)
+            && ptr.as_ptr().align_offset(new_layout.align()) == 0
+            && new_layout.size() - old_size <= self.remaining()
+        {
+            self.ptr.set(ptr.as_ptr().add(new_layout.size()));
+            self.allocated.set(self.allocated.get() + new_layout.size() - old_size);
+            return Ok(ptr);
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_size);
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Bump::new()
+    }
+}
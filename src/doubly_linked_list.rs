@@ -0,0 +1,559 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+// NonNull<T> 在 T 上是协变的，而 *mut T 是不变的，
+// 所以链接都用 NonNull，这样 DoublyLinkedList<&'static str> 可以当作 DoublyLinkedList<&'a str> 使用
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    front: Link<T>,
+    back: Link<T>,
+    elem: T,
+}
+
+pub struct DoublyLinkedList<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    // 告诉 drop 检查器：我们拥有 T，drop 时会 drop 掉 T
+    _own: PhantomData<T>,
+}
+
+pub struct Iter<'a, T: 'a> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T: 'a> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a mut T>,
+}
+
+pub struct IntoIter<T>(DoublyLinkedList<T>);
+
+/// 可以在链表中间 O(1) 插入、删除的游标
+///
+/// 除了各个元素之外还有一个“幽灵”位置（`cur` 为 None），位于尾部和头部之间，
+/// 在幽灵位置上 `move_next` 会到头部，`move_prev` 会到尾部，链表首尾因此连成一个环
+pub struct CursorMut<'a, T: 'a> {
+    list: &'a mut DoublyLinkedList<T>,
+    cur: Link<T>,
+    index: Option<usize>,
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        DoublyLinkedList { front: None, back: None, len: 0, _own: PhantomData }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                front: None,
+                back: None,
+                elem,
+            })));
+            if let Some(old) = self.front {
+                (*old.as_ptr()).front = Some(new);
+                (*new.as_ptr()).back = Some(old);
+            } else {
+                self.back = Some(new);
+            }
+            self.front = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                front: None,
+                back: None,
+                elem,
+            })));
+            if let Some(old) = self.back {
+                (*old.as_ptr()).back = Some(new);
+                (*new.as_ptr()).front = Some(old);
+            } else {
+                self.front = Some(new);
+            }
+            self.back = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.front.map(|node| unsafe {
+            let boxed = Box::from_raw(node.as_ptr());
+            self.front = boxed.back;
+            if let Some(new) = self.front {
+                (*new.as_ptr()).front = None;
+            } else {
+                self.back = None;
+            }
+            self.len -= 1;
+            boxed.elem
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.back.map(|node| unsafe {
+            let boxed = Box::from_raw(node.as_ptr());
+            self.back = boxed.front;
+            if let Some(new) = self.back {
+                (*new.as_ptr()).back = None;
+            } else {
+                self.front = None;
+            }
+            self.len -= 1;
+            boxed.elem
+        })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.front.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.front.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.back.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { front: self.front, back: self.back, len: self.len, _list: PhantomData }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { front: self.front, back: self.back, len: self.len, _list: PhantomData }
+    }
+
+    /// 从幽灵位置开始的游标，`move_next` 一次即到达头部
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { list: self, cur: None, index: None }
+    }
+
+    /// 指向头部的游标，空链表时位于幽灵位置
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let cur = self.front;
+        let index = cur.map(|_| 0);
+        CursorMut { list: self, cur, index }
+    }
+
+    /// 指向尾部的游标，空链表时位于幽灵位置
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let cur = self.back;
+        let index = cur.map(|_| self.len - 1);
+        CursorMut { list: self, cur, index }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// 当前元素的下标，幽灵位置返回 None
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    /// 在当前元素之前插入；在幽灵位置上等同于 `push_back`
+    pub fn insert_before(&mut self, elem: T) {
+        let mut single = DoublyLinkedList::new();
+        single.push_back(elem);
+        self.splice_before(single);
+    }
+
+    /// 在当前元素之后插入；在幽灵位置上等同于 `push_front`
+    pub fn insert_after(&mut self, elem: T) {
+        let mut single = DoublyLinkedList::new();
+        single.push_back(elem);
+        self.splice_after(single);
+    }
+
+    /// 删除当前元素并返回，游标移动到下一个元素（可能是幽灵位置）
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.cur?;
+        unsafe {
+            let boxed = Box::from_raw(node.as_ptr());
+            let (prev, next) = (boxed.front, boxed.back);
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).back = next,
+                None => self.list.front = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).front = prev,
+                None => self.list.back = prev,
+            }
+
+            self.list.len -= 1;
+            self.cur = next;
+            if next.is_none() { self.index = None; }
+            Some(boxed.elem)
+        }
+    }
+
+    /// 把 `input` 整个接到当前元素之后，O(1)；在幽灵位置上接到链表头部
+    pub fn splice_after(&mut self, mut input: DoublyLinkedList<T>) {
+        let (in_front, in_back) = match (input.front.take(), input.back.take()) {
+            (Some(front), Some(back)) => (front, back),
+            _ => return,
+        };
+        let in_len = std::mem::replace(&mut input.len, 0);
+
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).back,
+                None => self.list.front,
+            };
+
+            (*in_front.as_ptr()).front = self.cur;
+            match self.cur {
+                Some(cur) => (*cur.as_ptr()).back = Some(in_front),
+                None => self.list.front = Some(in_front),
+            }
+
+            (*in_back.as_ptr()).back = next;
+            match next {
+                Some(next) => (*next.as_ptr()).front = Some(in_back),
+                None => self.list.back = Some(in_back),
+            }
+        }
+        self.list.len += in_len;
+    }
+
+    /// 把 `input` 整个接到当前元素之前，O(1)；在幽灵位置上接到链表尾部
+    pub fn splice_before(&mut self, mut input: DoublyLinkedList<T>) {
+        let (in_front, in_back) = match (input.front.take(), input.back.take()) {
+            (Some(front), Some(back)) => (front, back),
+            _ => return,
+        };
+        let in_len = std::mem::replace(&mut input.len, 0);
+
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).front,
+                None => self.list.back,
+            };
+
+            (*in_back.as_ptr()).back = self.cur;
+            match self.cur {
+                Some(cur) => (*cur.as_ptr()).front = Some(in_back),
+                None => self.list.back = Some(in_back),
+            }
+
+            (*in_front.as_ptr()).front = prev;
+            match prev {
+                Some(prev) => (*prev.as_ptr()).back = Some(in_front),
+                None => self.list.front = Some(in_front),
+            }
+        }
+        self.list.len += in_len;
+        if let Some(index) = self.index.as_mut() { *index += in_len; }
+    }
+
+    /// 把当前元素之后的部分切下来返回，O(1)；在幽灵位置上切走整个链表
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let cur = match self.cur {
+            Some(cur) => cur,
+            None => return std::mem::take(self.list),
+        };
+        let kept = self.index.unwrap() + 1;
+
+        unsafe {
+            let next = (*cur.as_ptr()).back.take();
+            let rest = DoublyLinkedList {
+                front: next,
+                back: next.and(self.list.back),
+                len: self.list.len - kept,
+                _own: PhantomData,
+            };
+            if let Some(next) = next {
+                (*next.as_ptr()).front = None;
+            }
+            self.list.back = Some(cur);
+            self.list.len = kept;
+            rest
+        }
+    }
+
+    /// 把当前元素之前的部分切下来返回，O(1)；在幽灵位置上切走整个链表
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let cur = match self.cur {
+            Some(cur) => cur,
+            None => return std::mem::take(self.list),
+        };
+        let cut = self.index.unwrap();
+
+        unsafe {
+            let prev = (*cur.as_ptr()).front.take();
+            let rest = DoublyLinkedList {
+                front: prev.and(self.list.front),
+                back: prev,
+                len: cut,
+                _own: PhantomData,
+            };
+            if let Some(prev) = prev {
+                (*prev.as_ptr()).back = None;
+            }
+            self.list.front = Some(cur);
+            self.list.len -= cut;
+            self.index = Some(0);
+            rest
+        }
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        DoublyLinkedList::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+// 和 Box<T> 一样：拥有 T，所以 Send/Sync 只取决于 T
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// 前后两端各自移动，用 len 判断是否相遇，而不是比较指针
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 { return None; }
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = (*node.as_ptr()).back;
+            &(*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 { return None; }
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = (*node.as_ptr()).front;
+            &(*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 { return None; }
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = (*node.as_ptr()).back;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 { return None; }
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = (*node.as_ptr()).front;
+            &mut (*node.as_ptr()).elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
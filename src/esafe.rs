@@ -58,3 +58,39 @@
 //         }
 //     }
 // }
+
+// 上面是 nomicon 里 BinaryHeap 的例子，下面两个守卫用同样的思路服务于 `vecx::sort`：
+// 调用用户的比较函数之前，先把“洞”登记在守卫里，不管正常返回还是 panic 展开，
+// 守卫 drop 时都会把暂存的元素写回洞里，保证切片中每个元素恰好出现一次
+
+use std::ptr;
+
+/// 插入排序时被取出来的那个元素：drop 时把 `src` 写回当前的洞 `dest`
+pub(crate) struct InsertionHole<T> {
+    pub src: *const T,
+    pub dest: *mut T,
+}
+
+impl<T> Drop for InsertionHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.src, self.dest, 1);
+        }
+    }
+}
+
+/// 归并时暂存在 scratch 里、还没放回去的 `[start, end)`：drop 时整段复制到 `dest`
+pub(crate) struct MergeHole<T> {
+    pub start: *mut T,
+    pub end: *mut T,
+    pub dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
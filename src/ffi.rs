@@ -0,0 +1,140 @@
+//! 给 C/C++ 用的 `Vecx<u8>` 接口
+//!
+//! C 端只拿到不透明的 `VecxU8*` 句柄，由 `vecx_u8_new`/`vecx_u8_with_capacity`/`vecx_u8_from_raw`
+//! 创建，最后必须交给 `vecx_u8_free`（或 `vecx_u8_into_raw`）收回。
+//! 头文件 `include/vecx.h` 由 cbindgen 根据本文件生成：`cbindgen --config cbindgen.toml -o include/vecx.h`
+//!
+//! 除 `vecx_u8_free` 外，所有函数的句柄参数都不能为 NULL。
+//! 分配失败或容量溢出时进程直接 abort，不会把 panic 传到 C 端
+
+use std::ptr;
+
+use crate::vecx::Vecx;
+
+/// 不透明的字节缓冲区句柄
+pub struct VecxU8 {
+    inner: Vecx<u8>,
+}
+
+fn into_handle(inner: Vecx<u8>) -> *mut VecxU8 {
+    Box::into_raw(Box::new(VecxU8 { inner }))
+}
+
+/// 创建一个空缓冲区，不分配数据内存
+#[no_mangle]
+pub extern "C" fn vecx_u8_new() -> *mut VecxU8 {
+    into_handle(Vecx::new())
+}
+
+/// 创建一个预留 `cap` 字节的空缓冲区
+#[no_mangle]
+pub extern "C" fn vecx_u8_with_capacity(cap: usize) -> *mut VecxU8 {
+    into_handle(Vecx::with_capacity(cap))
+}
+
+/// 在末尾追加一个字节
+///
+/// # Safety
+///
+/// `v` 必须是本模块创建、还没有释放的句柄
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_push(v: *mut VecxU8, byte: u8) {
+    (&mut *v).inner.push(byte);
+}
+
+/// 在末尾追加 `len` 个字节
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`；`data` 必须指向至少 `len` 个可读字节（`len == 0` 时可以为 NULL），
+/// 并且不能指向 `v` 自己的数据（追加时可能重新分配）
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_extend(v: *mut VecxU8, data: *const u8, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let v = &mut (&mut *v).inner;
+    v.reserve(len);
+    ptr::copy_nonoverlapping(data, v.as_mut_ptr().add(v.len()), len);
+    v.set_len(v.len() + len);
+}
+
+/// 数据起始地址，可以读写前 `vecx_u8_len` 个字节
+///
+/// 下一次 `push`/`extend`/`reserve` 之后可能失效；空缓冲区返回一个非 NULL 但不可访问的地址
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_data(v: *mut VecxU8) -> *mut u8 {
+    (&mut *v).inner.as_mut_ptr()
+}
+
+/// 已写入的字节数
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_len(v: *const VecxU8) -> usize {
+    (&*v).inner.len()
+}
+
+/// 当前容量
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_capacity(v: *const VecxU8) -> usize {
+    (&*v).inner.capacity()
+}
+
+/// 预留至少 `additional` 字节，之后追加这么多字节不会再重新分配
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_reserve(v: *mut VecxU8, additional: usize) {
+    (&mut *v).inner.reserve(additional);
+}
+
+/// 释放句柄，把数据内存的所有权交给调用者，返回数据指针并写出长度和容量
+///
+/// 返回的内存不能用 C 的 `free` 释放，只能原样交给 `vecx_u8_from_raw` 收回
+///
+/// # Safety
+///
+/// `v` 同 `vecx_u8_push`，调用之后不能再使用；`len`、`cap` 必须是可写的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_into_raw(v: *mut VecxU8, len: *mut usize, cap: *mut usize) -> *mut u8 {
+    let (data, n, c) = Box::from_raw(v).inner.into_raw_parts();
+    *len = n;
+    *cap = c;
+    data
+}
+
+/// 用 `vecx_u8_into_raw` 得到的三元组重新创建句柄
+///
+/// # Safety
+///
+/// `data`、`len`、`cap` 必须原样来自同一次 `vecx_u8_into_raw`，并且 `len` 只能改小
+/// （不能超过容量，多出来的字节必须已经写过）；这块内存之后归新句柄所有
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_from_raw(data: *mut u8, len: usize, cap: usize) -> *mut VecxU8 {
+    into_handle(Vecx::from_raw_parts(data, len, cap))
+}
+
+/// 释放句柄和它的数据；`v` 为 NULL 时什么都不做
+///
+/// # Safety
+///
+/// `v` 为 NULL，或者是本模块创建、还没有释放的句柄
+#[no_mangle]
+pub unsafe extern "C" fn vecx_u8_free(v: *mut VecxU8) {
+    if !v.is_null() {
+        drop(Box::from_raw(v));
+    }
+}
@@ -1,5 +1,25 @@
+// nightly 上用 `--cfg read_buf` 打开 `vecx::io::read_to_end_into` 不清零的读取路径
+#![cfg_attr(read_buf, feature(read_buf, core_io_borrowed_buf))]
+
+#[allow(non_snake_case)]
 pub mod LinkedList;
+pub mod doubly_linked_list;
 pub mod slice;
 pub mod btree;
 pub mod esafe;
+pub mod persistent;
 pub mod vecx;
+pub mod arena;
+pub mod slot_map;
+pub mod allocator;
+pub mod bump;
+pub mod merkle;
+pub mod snapshot;
+pub mod bloom;
+pub mod bit_vecx;
+pub mod thin_vecx;
+pub mod stringx;
+pub mod ffi;
+pub mod sync;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
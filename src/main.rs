@@ -1,23 +1,25 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 
+use test_demo::sync::ShardedCounter;
+
 fn main() {
-    let counter = Arc::new(AtomicUsize::new(0));
+    // 只是计数，不发布其他数据，分片计数器内部用 Relaxed 即可；
+    // join 保证了主线程读取时所有线程的加法都已经可见
+    let counter = Arc::new(ShardedCounter::new());
     let mut handles = vec![];
 
     for _ in 0..10 {
         let c = counter.clone();
         let handle = thread::spawn(move || {
             for _ in 0..1000 {
-                c.fetch_add(1, Ordering::Release);
+                c.increment();
             }
         });
 
         handles.push(handle);
     }
-    // handles.into_iter().map(|handle| handle.join().unwrap());
     handles.into_iter().for_each(|handle| { handle.join().unwrap(); });
 
-    println!("最终计数：{}", counter.load(Ordering::Acquire));
+    println!("最终计数：{}", counter.get());
 }
@@ -0,0 +1,193 @@
+//! 基于 SHA-256 的 Merkle 树，用来校验大块复制数据的一致性
+//!
+//! 叶子和内部节点的哈希使用不同的前缀（0x00 / 0x01）做域分离，
+//! 防止把内部节点伪装成叶子（second preimage）。某一层节点数为奇数时，最后一个节点原样提升到上一层
+
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::vecx::Vecx;
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+pub struct MerkleTree {
+    // levels[0] 是叶子的哈希，最后一层只有根；空树没有任何层
+    levels: Vecx<Vecx<Hash>>,
+}
+
+/// 某个叶子的包含证明：从叶子往上每一层的兄弟节点（被提升的层没有兄弟，直接跳过）
+pub struct Proof {
+    index: usize,
+    leaves: usize,
+    siblings: Vecx<Hash>,
+}
+
+impl MerkleTree {
+    /// 每个元素作为一个叶子
+    pub fn from_leaves<T: AsRef<[u8]>>(items: &[T]) -> Self {
+        let mut leaves = Vecx::with_capacity(items.len());
+        for item in items {
+            leaves.push(leaf_hash(item.as_ref()));
+        }
+        MerkleTree::build(leaves)
+    }
+
+    /// 按 `chunk_size` 字节切块，每块作为一个叶子，最后一块可以不满
+    pub fn from_chunks(data: &[u8], chunk_size: usize) -> Self {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        let mut leaves = Vecx::with_capacity(data.len().div_ceil(chunk_size));
+        for chunk in data.chunks(chunk_size) {
+            leaves.push(leaf_hash(chunk));
+        }
+        MerkleTree::build(leaves)
+    }
+
+    fn build(leaves: Vecx<Hash>) -> Self {
+        let mut levels = Vecx::new();
+        if leaves.is_empty() {
+            return MerkleTree { levels };
+        }
+        levels.push(leaves);
+        while levels[levels.len() - 1].len() > 1 {
+            let below = &levels[levels.len() - 1];
+            let mut level = Vecx::with_capacity(below.len().div_ceil(2));
+            for pair in below.chunks(2) {
+                level.push(match pair {
+                    [left, right] => node_hash(left, right),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                });
+            }
+            levels.push(level);
+        }
+        MerkleTree { levels }
+    }
+
+    /// 叶子个数
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, |leaves| leaves.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// 根哈希；空树的根是空输入的 SHA-256
+    pub fn root(&self) -> Hash {
+        match self.levels.last() {
+            Some(top) => top[0],
+            None => Sha256::digest([]).into(),
+        }
+    }
+
+    pub fn leaf(&self, index: usize) -> Option<&Hash> {
+        self.levels.first()?.get(index)
+    }
+
+    pub fn proof(&self, index: usize) -> Option<Proof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut siblings = Vecx::new();
+        let mut i = index;
+        for level in self.levels.iter() {
+            if let Some(sibling) = level.get(i ^ 1) {
+                siblings.push(*sibling);
+            }
+            i /= 2;
+        }
+        Some(Proof { index, leaves: self.len(), siblings })
+    }
+
+    /// 替换一个叶子的数据，只重新计算它到根的这一条路径，O(log n)
+    pub fn update(&mut self, index: usize, data: &[u8]) {
+        assert!(index < self.len(), "index out of bounds");
+        self.levels[0][index] = leaf_hash(data);
+        let mut i = index;
+        for depth in 1..self.levels.len() {
+            let below = &self.levels[depth - 1];
+            let hash = match below.get(i ^ 1) {
+                Some(sibling) if i & 1 == 0 => node_hash(&below[i], sibling),
+                Some(sibling) => node_hash(sibling, &below[i]),
+                None => below[i],
+            };
+            i /= 2;
+            self.levels[depth][i] = hash;
+        }
+    }
+}
+
+impl Proof {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn siblings(&self) -> &[Hash] {
+        &self.siblings
+    }
+
+    /// 检查 `data` 是否是根为 `root` 的树中第 `index` 个叶子
+    pub fn verify(&self, root: &Hash, data: &[u8]) -> bool {
+        self.verify_hash(root, leaf_hash(data))
+    }
+
+    pub fn verify_hash(&self, root: &Hash, leaf: Hash) -> bool {
+        if self.index >= self.leaves {
+            return false;
+        }
+        let mut hash = leaf;
+        let mut siblings = self.siblings.iter();
+        let (mut i, mut n) = (self.index, self.leaves);
+        // 和建树时一样逐层往上，只有兄弟存在的层才消耗一个证明节点
+        while n > 1 {
+            if i ^ 1 < n {
+                let sibling = match siblings.next() {
+                    Some(s) => s,
+                    None => return false,
+                };
+                hash = if i & 1 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+            }
+            i /= 2;
+            n = n.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == *root
+    }
+}
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proof")
+            .field("index", &self.index)
+            .field("leaves", &self.leaves)
+            .field("siblings", &&self.siblings[..])
+            .finish()
+    }
+}
+
+impl fmt::Debug for MerkleTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("leaves", &self.len())
+            .field("root", &self.root())
+            .finish()
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use super::Ptr;
+
+type Link<T> = Option<Ptr<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+/// 持久化的单链表（栈）：`push` 和 `tail` 都不修改自身，而是返回共享剩余节点的新链表
+pub struct PersistentList<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+pub struct Iter<'a, T: 'a> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<T> PersistentList<T> {
+    pub fn new() -> Self {
+        PersistentList { head: None, len: 0 }
+    }
+
+    /// 返回一个以 `elem` 为表头、其余节点和 `self` 共享的新链表，O(1)
+    pub fn push(&self, elem: T) -> Self {
+        PersistentList {
+            head: Some(Ptr::new(Node { elem, next: self.head.clone() })),
+            len: self.len + 1,
+        }
+    }
+
+    /// 去掉表头之后的链表，O(1)；空链表的 tail 仍是空链表
+    pub fn tail(&self) -> Self {
+        PersistentList {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+            len: self.len.saturating_sub(1),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head.as_deref(), len: self.len }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        PersistentList::new()
+    }
+}
+
+/// 只复制表头指针，不复制任何节点
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        PersistentList { head: self.head.clone(), len: self.len }
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        // 只释放这个版本独占的前缀，遇到还被其他版本引用的节点就停下，
+        // 剩下的部分由最后一个持有者负责。
+        // 不能用 try_unwrap：两个线程同时 drop 最后两个引用时可能都失败，
+        // 最后那个 Arc 就会走默认的递归 drop；into_inner 保证恰好有一方拿到节点
+        let mut cur = self.head.take();
+        while let Some(node) = cur {
+            cur = Ptr::into_inner(node).and_then(|mut node| node.next.take());
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
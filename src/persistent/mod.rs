@@ -0,0 +1,14 @@
+//! 不可变（持久化）的链表和二叉搜索树：每次修改都返回一个新版本，
+//! 新旧版本之间共享没有变化的节点，旧版本始终保持可用
+
+pub mod list;
+pub mod tree;
+
+pub use list::PersistentList;
+pub use tree::PersistentTree;
+
+// 默认用 Rc，打开 `sync` feature 后换成 Arc，这样不同版本可以跨线程共享
+#[cfg(not(feature = "sync"))]
+use std::rc::Rc as Ptr;
+#[cfg(feature = "sync")]
+use std::sync::Arc as Ptr;
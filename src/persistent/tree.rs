@@ -0,0 +1,243 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::Ptr;
+use crate::vecx::Vecx;
+
+type Link<K, V> = Option<Ptr<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// 持久化的二叉搜索树（不做平衡，和 `btree::Tree` 一样）
+///
+/// `insert` 和 `remove` 采用路径复制：只复制从根到被修改位置这一条路径上的节点，
+/// 其余子树和旧版本共享，所以需要 `K: Clone, V: Clone`
+pub struct PersistentTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+/// 按 key 从小到大的中序遍历
+pub struct Iter<'a, K: 'a, V: 'a> {
+    stack: Vecx<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<K, V> PersistentTree<K, V> {
+    pub fn new() -> Self {
+        PersistentTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vecx::new(), len: self.len };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            cur = match key.cmp(node.key.borrow()) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentTree<K, V> {
+    /// 返回插入（或替换）`key` 之后的新版本，`self` 不变
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let mut path = Vecx::new();
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            match key.cmp(&node.key) {
+                Ordering::Less => {
+                    path.push((node, Side::Left));
+                    cur = node.left.as_deref();
+                }
+                Ordering::Greater => {
+                    path.push((node, Side::Right));
+                    cur = node.right.as_deref();
+                }
+                Ordering::Equal => break,
+            }
+        }
+
+        let (sub, len) = match cur {
+            // 替换已有的 key，左右子树原样共享
+            Some(node) => {
+                let sub = Node { key, value, left: node.left.clone(), right: node.right.clone() };
+                (sub, self.len)
+            }
+            None => (Node { key, value, left: None, right: None }, self.len + 1),
+        };
+        PersistentTree { root: rebuild(path, Some(Ptr::new(sub))), len }
+    }
+
+    /// 返回删除 `key` 之后的新版本；`key` 不存在时返回共享全部节点的副本
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut path = Vecx::new();
+        let mut cur = self.root.as_deref();
+        let target = loop {
+            let node = match cur {
+                Some(node) => node,
+                None => return self.clone(),
+            };
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => {
+                    path.push((node, Side::Left));
+                    cur = node.left.as_deref();
+                }
+                Ordering::Greater => {
+                    path.push((node, Side::Right));
+                    cur = node.right.as_deref();
+                }
+                Ordering::Equal => break node,
+            }
+        };
+
+        let sub = match (&target.left, &target.right) {
+            (None, right) => right.clone(),
+            (left, None) => left.clone(),
+            // 左右都有：用右子树中最小的节点顶替被删除的节点
+            (Some(_), Some(right)) => {
+                let mut min_path = Vecx::new();
+                let mut min = &**right;
+                while let Some(left) = min.left.as_deref() {
+                    min_path.push((min, Side::Left));
+                    min = left;
+                }
+                let right = rebuild(min_path, min.right.clone());
+                Some(Ptr::new(Node {
+                    key: min.key.clone(),
+                    value: min.value.clone(),
+                    left: target.left.clone(),
+                    right,
+                }))
+            }
+        };
+        PersistentTree { root: rebuild(path, sub), len: self.len - 1 }
+    }
+}
+
+// 自底向上复制路径上的节点：每个祖先换掉走过的那一侧，另一侧直接共享
+fn rebuild<K: Clone, V: Clone>(path: Vecx<(&Node<K, V>, Side)>, mut sub: Link<K, V>) -> Link<K, V> {
+    for (node, side) in path.into_iter().rev() {
+        let (left, right) = match side {
+            Side::Left => (sub, node.right.clone()),
+            Side::Right => (node.left.clone(), sub),
+        };
+        sub = Some(Ptr::new(Node { key: node.key.clone(), value: node.value.clone(), left, right }));
+    }
+    sub
+}
+
+impl<K, V> Default for PersistentTree<K, V> {
+    fn default() -> Self {
+        PersistentTree::new()
+    }
+}
+
+/// 只复制根指针，不复制任何节点
+impl<K, V> Clone for PersistentTree<K, V> {
+    fn clone(&self) -> Self {
+        PersistentTree { root: self.root.clone(), len: self.len }
+    }
+}
+
+impl<K, V> Drop for PersistentTree<K, V> {
+    fn drop(&mut self) {
+        // 用显式的栈代替递归：只拆开这个版本独占的节点，
+        // 还被其他版本引用的子树只减少一次引用计数（into_inner 的理由见 `PersistentList` 的 Drop）
+        let mut stack = Vecx::new();
+        if let Some(root) = self.root.take() {
+            stack.push(root);
+        }
+        while let Some(node) = stack.pop() {
+            if let Some(mut node) = Ptr::into_inner(node) {
+                for child in [node.left.take(), node.right.take()].into_iter().flatten() {
+                    stack.push(child);
+                }
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a PersistentTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut cur: Option<&'a Node<K, V>>) {
+        while let Some(node) = cur {
+            self.stack.push(node);
+            cur = node.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
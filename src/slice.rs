@@ -1,7 +1,146 @@
+use std::error::Error;
+use std::fmt;
+use std::iter::FusedIterator;
 use std::mem;
 
+// 所有可变迭代器都是同一个套路：用 `mem::take` 把 `&'a mut [T]` 整个拿出来（留下一个空切片），
+// 再用 `split_at_mut` 切成互不重叠的两半，一半返回，另一半放回去，
+// 这样返回的引用带着完整的 'a 生命周期，也不需要 unsafe
+
 pub struct IterMut<'a, T: 'a>(&'a mut[T]);
 
+/// 每次返回 `size` 个元素，最后一块可能不足 `size`
+pub struct ChunksMut<'a, T: 'a> {
+    slice: &'a mut [T],
+    size: usize,
+}
+
+/// 每次返回恰好 `size` 个元素，凑不满的尾部通过 `into_remainder` 取得
+pub struct ChunksExactMut<'a, T: 'a> {
+    slice: &'a mut [T],
+    rem: &'a mut [T],
+    size: usize,
+}
+
+/// 和 `ChunksMut` 相同，但从尾部开始切，不足 `size` 的一块在最前面
+pub struct RChunksMut<'a, T: 'a> {
+    slice: &'a mut [T],
+    size: usize,
+}
+
+/// 长度为 `size` 的重叠窗口
+///
+/// 相邻的窗口互相重叠，不能同时持有两个 `&mut` 窗口，所以这里不是 `Iterator`，
+/// 而是“借出式”迭代：`next` 返回的窗口借用了 `self`，下一次调用前必须先用完
+pub struct WindowsMut<'a, T: 'a> {
+    slice: &'a mut [T],
+    size: usize,
+    // 还没有返回的窗口起点范围 [front, back)
+    front: usize,
+    back: usize,
+}
+
+/// 以满足 `pred` 的元素为分隔符切分，分隔符本身不包含在结果中
+///
+/// 段数要扫描完才知道，所以 `size_hint` 只给出上下界，不实现 `ExactSizeIterator`
+pub struct SplitMut<'a, T: 'a, P> {
+    slice: &'a mut [T],
+    pred: P,
+    finished: bool,
+}
+
+/// 把相邻且 `pred(a, b)` 为 true 的元素归为一组，每次返回一个最长的组
+///
+/// 和 `SplitMut` 一样，组数只有上下界
+pub struct GroupByMut<'a, T: 'a, P> {
+    slice: &'a mut [T],
+    pred: P,
+}
+
+pub fn iter_mut<T>(slice: &mut [T]) -> IterMut<'_, T> {
+    IterMut(slice)
+}
+
+pub fn chunks_mut<T>(slice: &mut [T], size: usize) -> ChunksMut<'_, T> {
+    assert!(size != 0, "chunk size must be non-zero");
+    ChunksMut { slice, size }
+}
+
+pub fn chunks_exact_mut<T>(slice: &mut [T], size: usize) -> ChunksExactMut<'_, T> {
+    assert!(size != 0, "chunk size must be non-zero");
+    let exact = slice.len() - slice.len() % size;
+    let (slice, rem) = slice.split_at_mut(exact);
+    ChunksExactMut { slice, rem, size }
+}
+
+pub fn rchunks_mut<T>(slice: &mut [T], size: usize) -> RChunksMut<'_, T> {
+    assert!(size != 0, "chunk size must be non-zero");
+    RChunksMut { slice, size }
+}
+
+pub fn windows_mut<T>(slice: &mut [T], size: usize) -> WindowsMut<'_, T> {
+    assert!(size != 0, "window size must be non-zero");
+    let back = (slice.len() + 1).saturating_sub(size);
+    WindowsMut { slice, size, front: 0, back }
+}
+
+pub fn split_mut<T, P>(slice: &mut [T], pred: P) -> SplitMut<'_, T, P>
+where
+    P: FnMut(&T) -> bool,
+{
+    SplitMut { slice, pred, finished: false }
+}
+
+pub fn group_by_mut<T, P>(slice: &mut [T], pred: P) -> GroupByMut<'_, T, P>
+where
+    P: FnMut(&T, &T) -> bool,
+{
+    GroupByMut { slice, pred }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetManyMutError {
+    IndexOutOfBounds { index: usize, len: usize },
+    OverlappingIndices { index: usize },
+}
+
+impl fmt::Display for GetManyMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetManyMutError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds for slice of length {}", index, len)
+            }
+            GetManyMutError::OverlappingIndices { index } => {
+                write!(f, "index {} is requested more than once", index)
+            }
+        }
+    }
+}
+
+impl Error for GetManyMutError {}
+
+/// 同时取得多个下标处元素的可变引用
+///
+/// 所有下标都必须在范围内且两两不同，否则返回错误而不是产生别名的 `&mut`
+pub fn get_many_mut<T, const N: usize>(
+    slice: &mut [T],
+    indices: [usize; N],
+) -> Result<[&mut T; N], GetManyMutError> {
+    let len = slice.len();
+    for (i, &index) in indices.iter().enumerate() {
+        if index >= len {
+            return Err(GetManyMutError::IndexOutOfBounds { index, len });
+        }
+        if indices[..i].contains(&index) {
+            return Err(GetManyMutError::OverlappingIndices { index });
+        }
+    }
+
+    // 上面已经检查过下标都在范围内且互不相同，所以这些引用互不重叠
+    let ptr = slice.as_mut_ptr();
+    Ok(indices.map(|index| unsafe { &mut *ptr.add(index) }))
+}
+
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
 
@@ -13,6 +152,10 @@ impl<'a, T> Iterator for IterMut<'a, T> {
         self.0 = r;
         l.get_mut(0)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
 }
 
 impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
@@ -26,3 +169,252 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
         r.get_mut(0)
     }
 }
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iterator for ChunksMut<'a, T> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let mid = self.size.min(slice.len());
+        let (l, r) = slice.split_at_mut(mid);
+        self.slice = r;
+        Some(l)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.slice.len().div_ceil(self.size);
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ChunksMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        // 最后一块的长度：整除时是 size，否则是余数
+        let last = match slice.len() % self.size {
+            0 => self.size,
+            rem => rem,
+        };
+        let mid = slice.len() - last;
+        let (l, r) = slice.split_at_mut(mid);
+        self.slice = l;
+        Some(r)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ChunksMut<'a, T> {}
+impl<'a, T> FusedIterator for ChunksMut<'a, T> {}
+
+impl<'a, T> ChunksExactMut<'a, T> {
+    pub fn into_remainder(self) -> &'a mut [T] {
+        self.rem
+    }
+}
+
+impl<'a, T> Iterator for ChunksExactMut<'a, T> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let (l, r) = slice.split_at_mut(self.size);
+        self.slice = r;
+        Some(l)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.slice.len() / self.size;
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ChunksExactMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let mid = slice.len() - self.size;
+        let (l, r) = slice.split_at_mut(mid);
+        self.slice = l;
+        Some(r)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ChunksExactMut<'a, T> {}
+impl<'a, T> FusedIterator for ChunksExactMut<'a, T> {}
+
+impl<'a, T> Iterator for RChunksMut<'a, T> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let mid = slice.len().saturating_sub(self.size);
+        let (l, r) = slice.split_at_mut(mid);
+        self.slice = l;
+        Some(r)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.slice.len().div_ceil(self.size);
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RChunksMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        // 最前面一块的长度：整除时是 size，否则是余数
+        let first = match slice.len() % self.size {
+            0 => self.size,
+            rem => rem,
+        };
+        let (l, r) = slice.split_at_mut(first);
+        self.slice = r;
+        Some(l)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RChunksMut<'a, T> {}
+impl<'a, T> FusedIterator for RChunksMut<'a, T> {}
+
+#[allow(clippy::should_implement_trait)]
+impl<'a, T> WindowsMut<'a, T> {
+    pub fn next(&mut self) -> Option<&mut [T]> {
+        if self.front == self.back { return None; }
+        let start = self.front;
+        self.front += 1;
+        Some(&mut self.slice[start..start + self.size])
+    }
+
+    pub fn next_back(&mut self) -> Option<&mut [T]> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        let start = self.back;
+        Some(&mut self.slice[start..start + self.size])
+    }
+
+    /// 剩余窗口的个数
+    pub fn len(&self) -> usize {
+        self.back - self.front
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front == self.back
+    }
+}
+
+impl<'a, T, P> Iterator for SplitMut<'a, T, P>
+where
+    P: FnMut(&T) -> bool,
+{
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished { return None; }
+
+        let slice = mem::take(&mut self.slice);
+        match slice.iter().position(|x| (self.pred)(x)) {
+            Some(index) => {
+                let (l, r) = slice.split_at_mut(index);
+                // 跳过分隔符本身
+                self.slice = &mut r[1..];
+                Some(l)
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+
+    // 没有分隔符时至少还有一段，每个元素都是分隔符时有 len + 1 段
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            (0, Some(0))
+        } else {
+            (1, Some(self.slice.len() + 1))
+        }
+    }
+}
+
+impl<'a, T, P> DoubleEndedIterator for SplitMut<'a, T, P>
+where
+    P: FnMut(&T) -> bool,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished { return None; }
+
+        let slice = mem::take(&mut self.slice);
+        match slice.iter().rposition(|x| (self.pred)(x)) {
+            Some(index) => {
+                let (l, r) = slice.split_at_mut(index);
+                self.slice = l;
+                Some(&mut r[1..])
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+}
+
+impl<'a, T, P> FusedIterator for SplitMut<'a, T, P> where P: FnMut(&T) -> bool {}
+
+impl<'a, T, P> Iterator for GroupByMut<'a, T, P>
+where
+    P: FnMut(&T, &T) -> bool,
+{
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let mut len = 1;
+        while len < slice.len() && (self.pred)(&slice[len - 1], &slice[len]) {
+            len += 1;
+        }
+        let (l, r) = slice.split_at_mut(len);
+        self.slice = r;
+        Some(l)
+    }
+
+    // 所有元素同组时只有一组，两两不同组时每个元素一组
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.slice.len();
+        (len.min(1), Some(len))
+    }
+}
+
+impl<'a, T, P> DoubleEndedIterator for GroupByMut<'a, T, P>
+where
+    P: FnMut(&T, &T) -> bool,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = mem::take(&mut self.slice);
+        if slice.is_empty() { return None; }
+
+        let mut start = slice.len() - 1;
+        while start > 0 && (self.pred)(&slice[start - 1], &slice[start]) {
+            start -= 1;
+        }
+        let (l, r) = slice.split_at_mut(start);
+        self.slice = l;
+        Some(r)
+    }
+}
+
+impl<'a, T, P> FusedIterator for GroupByMut<'a, T, P> where P: FnMut(&T, &T) -> bool {}
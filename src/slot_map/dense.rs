@@ -0,0 +1,292 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use super::Key;
+use crate::vecx::Vecx;
+
+enum Place {
+    // 元素在 `values` 中的位置
+    Occupied(usize),
+    // 空闲链表：指向下一个空槽
+    Vacant(Option<usize>),
+}
+
+struct Slot {
+    generation: u32,
+    place: Place,
+}
+
+/// 元素紧凑地存放在一个 Vecx 里，槽位只记录元素的位置，遍历和 `values()` 都是连续内存
+///
+/// 删除时把最后一个元素换到空出来的位置（swap remove），所以元素的顺序会变化，
+/// 但 key 始终有效
+pub struct DenseSlotMap<T> {
+    slots: Vecx<Slot>,
+    values: Vecx<T>,
+    // `values[i]` 所在的槽位
+    owners: Vecx<usize>,
+    free: Option<usize>,
+}
+
+pub struct Iter<'a, T> {
+    map: &'a DenseSlotMap<T>,
+    values: std::iter::Zip<std::slice::Iter<'a, usize>, std::slice::Iter<'a, T>>,
+}
+
+pub struct IterMut<'a, T> {
+    slots: &'a [Slot],
+    values: std::iter::Zip<std::slice::Iter<'a, usize>, std::slice::IterMut<'a, T>>,
+}
+
+/// 从末尾开始移出所有元素；迭代器被提前 drop 时剩下的元素也会被移除
+pub struct Drain<'a, T> {
+    map: &'a mut DenseSlotMap<T>,
+}
+
+impl<T> DenseSlotMap<T> {
+    pub fn new() -> Self {
+        DenseSlotMap { slots: Vecx::new(), values: Vecx::new(), owners: Vecx::new(), free: None }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        DenseSlotMap {
+            slots: Vecx::with_capacity(cap),
+            values: Vecx::with_capacity(cap),
+            owners: Vecx::with_capacity(cap),
+            free: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn insert(&mut self, value: T) -> Key {
+        self.insert_with_key(|_| value)
+    }
+
+    pub fn insert_with_key<F>(&mut self, f: F) -> Key
+    where
+        F: FnOnce(Key) -> T,
+    {
+        let pos = self.values.len();
+        let key = match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                let key = Key::new(index, slot.generation);
+                self.values.push(f(key));
+                if let Place::Vacant(next) = slot.place {
+                    self.free = next;
+                }
+                slot.place = Place::Occupied(pos);
+                key
+            }
+            None => {
+                let index = self.slots.len();
+                let key = Key::new(index, 0);
+                self.values.push(f(key));
+                self.slots.push(Slot { generation: 0, place: Place::Occupied(pos) });
+                key
+            }
+        };
+        self.owners.push(key.index());
+        key
+    }
+
+    // key 有效时返回元素在 `values` 中的位置
+    fn position(&self, key: Key) -> Option<usize> {
+        match self.slots.get(key.index())? {
+            Slot { generation, place: Place::Occupied(pos) } if *generation == key.generation => Some(*pos),
+            _ => None,
+        }
+    }
+
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        let pos = self.position(key)?;
+        let index = key.index();
+
+        // swap remove：把最后一个元素换到 pos，再修正它所在槽位记录的位置
+        let last = self.values.len() - 1;
+        self.values.swap(pos, last);
+        self.owners.swap(pos, last);
+        let value = self.values.pop().unwrap();
+        self.owners.pop();
+        if pos != last {
+            let moved = self.owners[pos];
+            self.slots[moved].place = Place::Occupied(pos);
+        }
+
+        // 代数用完的槽位不再放回空闲链表
+        let slot = &mut self.slots[index];
+        slot.place = Place::Vacant(self.free);
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free = Some(index);
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, key: Key) -> Option<&T> {
+        let pos = self.position(key)?;
+        Some(&self.values[pos])
+    }
+
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        let pos = self.position(key)?;
+        Some(&mut self.values[pos])
+    }
+
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.position(key).is_some()
+    }
+
+    fn key_at(&self, pos: usize) -> Key {
+        let index = self.owners[pos];
+        Key::new(index, self.slots[index].generation)
+    }
+
+    /// 只保留 `f` 返回 true 的元素
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Key, &mut T) -> bool,
+    {
+        let mut pos = 0;
+        while pos < self.values.len() {
+            let key = self.key_at(pos);
+            if f(key, &mut self.values[pos]) {
+                pos += 1;
+            } else {
+                // 最后一个元素被换到了 pos，下一轮接着检查它
+                self.remove(key);
+            }
+        }
+    }
+
+    /// 移除所有元素，已有的 key 全部失效
+    pub fn clear(&mut self) {
+        self.drain();
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { map: self }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { map: self, values: self.owners.iter().zip(self.values.iter()) }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { slots: &self.slots, values: self.owners.iter().zip(self.values.iter_mut()) }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        (0..self.len()).map(|pos| self.key_at(pos))
+    }
+
+    /// 所有元素，连续存放，顺序不固定
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+}
+
+impl<T> Default for DenseSlotMap<T> {
+    fn default() -> Self {
+        DenseSlotMap::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DenseSlotMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<Key> for DenseSlotMap<T> {
+    type Output = T;
+    fn index(&self, key: Key) -> &T {
+        self.get(key).expect("invalid slot map key")
+    }
+}
+
+impl<T> IndexMut<Key> for DenseSlotMap<T> {
+    fn index_mut(&mut self, key: Key) -> &mut T {
+        self.get_mut(key).expect("invalid slot map key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseSlotMap<T> {
+    type Item = (Key, &'a T);
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DenseSlotMap<T> {
+    type Item = (Key, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&index, value) = self.values.next()?;
+        Some((Key::new(index, self.map.slots[index].generation), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Key, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&index, value) = self.values.next()?;
+        Some((Key::new(index, self.slots[index].generation), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = (Key, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 每次移出最后一个元素，不需要移动其他元素
+        let last = self.map.len().checked_sub(1)?;
+        let key = self.map.key_at(last);
+        self.map.remove(key).map(|value| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len(), Some(self.map.len()))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
@@ -0,0 +1,320 @@
+//! 带代数（generation）的 slot map：用 `Key { index, generation }` 代替裸下标引用元素，
+//! 元素被删除后槽位的代数加一，旧的 key 再来访问只会得到 `None`，不会指向后来插入的新元素
+
+pub mod dense;
+
+pub use dense::DenseSlotMap;
+
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+use crate::vecx::Vecx;
+
+/// 指向 slot map 中一个元素的 key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl Key {
+    fn new(index: usize, generation: u32) -> Self {
+        let index = u32::try_from(index).expect("slot map index overflow");
+        Key { index, generation }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+enum Entry<T> {
+    Occupied(T),
+    // 空闲链表：指向下一个空槽
+    Vacant(Option<usize>),
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+/// 元素原地存放在槽位里，空槽串成一个空闲链表，`insert`/`remove`/`get` 都是 O(1)
+pub struct SlotMap<T> {
+    slots: Vecx<Slot<T>>,
+    free: Option<usize>,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+/// 按下标顺序移出所有元素；迭代器被提前 drop 时剩下的元素也会被移除
+pub struct Drain<'a, T> {
+    map: &'a mut SlotMap<T>,
+    index: usize,
+}
+
+impl<T> Slot<T> {
+    fn get(&self, generation: u32) -> Option<&T> {
+        match &self.entry {
+            Entry::Occupied(value) if self.generation == generation => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, generation: u32) -> Option<&mut T> {
+        match &mut self.entry {
+            Entry::Occupied(value) if self.generation == generation => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<T> SlotMap<T> {
+    pub fn new() -> Self {
+        SlotMap { slots: Vecx::new(), free: None, len: 0 }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        SlotMap { slots: Vecx::with_capacity(cap), free: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Key {
+        self.insert_with_key(|_| value)
+    }
+
+    /// 元素需要知道自己的 key 时使用
+    pub fn insert_with_key<F>(&mut self, f: F) -> Key
+    where
+        F: FnOnce(Key) -> T,
+    {
+        let key = match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                let key = Key::new(index, slot.generation);
+                let value = f(key);
+                if let Entry::Vacant(next) = slot.entry {
+                    self.free = next;
+                }
+                slot.entry = Entry::Occupied(value);
+                key
+            }
+            None => {
+                let key = Key::new(self.slots.len(), 0);
+                let value = f(key);
+                self.slots.push(Slot { generation: 0, entry: Entry::Occupied(value) });
+                key
+            }
+        };
+        self.len += 1;
+        key
+    }
+
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        let index = key.index();
+        let slot = self.slots.get_mut(index)?;
+        slot.get(key.generation)?;
+
+        // 代数用完的槽位不再放回空闲链表，这样旧 key 永远不会和新元素撞上
+        let next = match slot.generation.checked_add(1) {
+            Some(generation) => {
+                slot.generation = generation;
+                Some(index)
+            }
+            None => None,
+        };
+        let entry = mem::replace(&mut slot.entry, Entry::Vacant(self.free));
+        if next.is_some() {
+            self.free = next;
+        }
+        self.len -= 1;
+        match entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant(_) => unreachable!(),
+        }
+    }
+
+    pub fn get(&self, key: Key) -> Option<&T> {
+        self.slots.get(key.index())?.get(key.generation)
+    }
+
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        self.slots.get_mut(key.index())?.get_mut(key.generation)
+    }
+
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 只保留 `f` 返回 true 的元素
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Key, &mut T) -> bool,
+    {
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            let key = Key::new(index, slot.generation);
+            if let Entry::Occupied(value) = &mut slot.entry {
+                if !f(key, value) {
+                    self.remove(key);
+                }
+            }
+        }
+    }
+
+    /// 移除所有元素，已有的 key 全部失效
+    pub fn clear(&mut self) {
+        self.drain();
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { map: self, index: 0 }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { slots: self.slots.iter().enumerate(), len: self.len }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { slots: self.slots.iter_mut().enumerate(), len: self.len }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        SlotMap::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SlotMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<Key> for SlotMap<T> {
+    type Output = T;
+    fn index(&self, key: Key) -> &T {
+        self.get(key).expect("invalid slot map key")
+    }
+}
+
+impl<T> IndexMut<Key> for SlotMap<T> {
+    fn index_mut(&mut self, key: Key) -> &mut T {
+        self.get_mut(key).expect("invalid slot map key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+    type Item = (Key, &'a T);
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SlotMap<T> {
+    type Item = (Key, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Entry::Occupied(value) = &slot.entry {
+                self.len -= 1;
+                return Some((Key::new(index, slot.generation), value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Key, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Entry::Occupied(value) = &mut slot.entry {
+                self.len -= 1;
+                return Some((Key::new(index, slot.generation), value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = (Key, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.map.slots.len() {
+            let index = self.index;
+            self.index += 1;
+            let slot = &self.map.slots[index];
+            if let Entry::Occupied(_) = slot.entry {
+                let key = Key::new(index, slot.generation);
+                return self.map.remove(key).map(|value| (key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len, Some(self.map.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
@@ -0,0 +1,452 @@
+//! 基于 `Vecx<u8>` 的 UTF-8 字符串
+
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::mem::ManuallyDrop;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use std::ptr;
+use std::str::{self, Chars, Utf8Error};
+
+use crate::vecx::Vecx;
+
+/// 拥有所有权的字符串，相当于 `String`
+///
+/// 内部的 `Vecx<u8>` 始终是合法的 UTF-8：所有按字节下标操作的方法都要求下标落在字符边界上，
+/// 否则 panic
+pub struct Stringx {
+    vec: Vecx<u8>,
+}
+
+/// `Stringx::from_utf8` 失败，原来的字节随错误一起返回
+pub struct FromUtf8Error {
+    bytes: Vecx<u8>,
+    error: Utf8Error,
+}
+
+/// `Stringx::drain` 返回的迭代器，drop 时把区间内的字节从字符串里删掉
+pub struct Drain<'a> {
+    // 迭代期间还借着字符串里的字节，所以只能存裸指针，drop 时才回头修改
+    string: *mut Stringx,
+    start: usize,
+    end: usize,
+    iter: Chars<'a>,
+}
+
+impl Stringx {
+    pub fn new() -> Self {
+        Stringx { vec: Vecx::new() }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Stringx { vec: Vecx::with_capacity(cap) }
+    }
+
+    /// 检查字节是不是合法的 UTF-8，不复制
+    pub fn from_utf8(vec: Vecx<u8>) -> Result<Self, FromUtf8Error> {
+        match str::from_utf8(&vec) {
+            Ok(_) => Ok(Stringx { vec }),
+            Err(error) => Err(FromUtf8Error { bytes: vec, error }),
+        }
+    }
+
+    /// 不检查的 `from_utf8`
+    ///
+    /// # Safety
+    ///
+    /// `vec` 必须是合法的 UTF-8
+    pub unsafe fn from_utf8_unchecked(vec: Vecx<u8>) -> Self {
+        Stringx { vec }
+    }
+
+    /// 把不合法的字节序列替换成 U+FFFD（`�`）
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+        let mut s = Stringx::with_capacity(bytes.len());
+        for chunk in bytes.utf8_chunks() {
+            s.push_str(chunk.valid());
+            if !chunk.invalid().is_empty() {
+                s.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+        s
+    }
+
+    pub fn as_str(&self) -> &str {
+        self
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        self
+    }
+
+    pub fn into_bytes(self) -> Vecx<u8> {
+        self.vec
+    }
+
+    /// 容量按字节计
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.vec.extend_from_slice(s.as_bytes());
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        let new_len = self.len() - ch.len_utf8();
+        unsafe { self.vec.set_len(new_len) };
+        Some(ch)
+    }
+
+    /// 在字节下标 `idx` 处插入一个字符
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.insert_str(idx, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        assert!(self.is_char_boundary(idx), "insertion index is not a char boundary");
+        let len = self.len();
+        let amt = s.len();
+        self.vec.reserve(amt);
+
+        unsafe {
+            let p = self.vec.as_mut_ptr();
+            ptr::copy(p.add(idx), p.add(idx + amt), len - idx);
+            ptr::copy_nonoverlapping(s.as_ptr(), p.add(idx), amt);
+            self.vec.set_len(len + amt);
+        }
+    }
+
+    /// 删除并返回字节下标 `idx` 处的字符
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match self[idx..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        let next = idx + ch.len_utf8();
+        let len = self.len();
+
+        unsafe {
+            let p = self.vec.as_mut_ptr();
+            ptr::copy(p.add(next), p.add(idx), len - next);
+            self.vec.set_len(len - (next - idx));
+        }
+        ch
+    }
+
+    /// 截断到 `new_len` 字节，比当前长度长时什么都不做
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(self.is_char_boundary(new_len), "new length is not a char boundary");
+            unsafe { self.vec.set_len(new_len) };
+        }
+    }
+
+    pub fn clear(&mut self) {
+        unsafe { self.vec.set_len(0) };
+    }
+
+    /// 在字节下标 `at` 处一分为二，返回 `[at, len)`，自己保留 `[0, at)`，容量不变
+    pub fn split_off(&mut self, at: usize) -> Stringx {
+        assert!(self.is_char_boundary(at), "split index is not a char boundary");
+        let mut other = Stringx::with_capacity(self.len() - at);
+        other.push_str(&self[at..]);
+        unsafe { self.vec.set_len(at) };
+        other
+    }
+
+    /// 按字节区间移除一段字符，返回被移除字符的迭代器；区间两端都必须落在字符边界上
+    ///
+    /// 和 `Vecx::drain` 一样，迭代器被 `mem::forget` 时字符串保持原样
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_> {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("range start overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("range end overflow"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(start <= end, "drain start is greater than end");
+        assert!(end <= len, "drain end is out of bounds");
+        assert!(self.is_char_boundary(start), "drain start is not a char boundary");
+        assert!(self.is_char_boundary(end), "drain end is not a char boundary");
+
+        let string: *mut Stringx = self;
+        let iter = unsafe { (*string).get_unchecked(start..end) }.chars();
+        Drain { string, start, end, iter }
+    }
+}
+
+impl<'a> Drain<'a> {
+    /// 还没有被迭代的部分
+    pub fn as_str(&self) -> &str {
+        self.iter.as_str()
+    }
+}
+
+impl<'a> Iterator for Drain<'a> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Drain<'a> {
+    fn next_back(&mut self) -> Option<char> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a> FusedIterator for Drain<'a> {}
+
+impl<'a> Drop for Drain<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            let vec = &mut (*self.string).vec;
+            let len = vec.len();
+            vec.copy_within(self.end..len, self.start);
+            vec.set_len(len - (self.end - self.start));
+        }
+    }
+}
+
+impl FromUtf8Error {
+    /// 出错的具体位置，见 `Utf8Error::valid_up_to` 和 `Utf8Error::error_len`
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+
+    /// 从开头算起合法的字节数
+    pub fn valid_up_to(&self) -> usize {
+        self.error.valid_up_to()
+    }
+
+    /// 出错的字节序列长度；`None` 表示字节在中途结束，后面再补字节可能变得合法
+    pub fn error_len(&self) -> Option<usize> {
+        self.error.error_len()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vecx<u8> {
+        self.bytes
+    }
+}
+
+impl fmt::Debug for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromUtf8Error")
+            .field("bytes", &&self.bytes[..])
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for FromUtf8Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl Deref for Stringx {
+    type Target = str;
+    fn deref(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+}
+
+impl DerefMut for Stringx {
+    fn deref_mut(&mut self) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+}
+
+impl Default for Stringx {
+    fn default() -> Self {
+        Stringx::new()
+    }
+}
+
+impl Clone for Stringx {
+    fn clone(&self) -> Self {
+        Stringx::from(self.as_str())
+    }
+}
+
+impl fmt::Write for Stringx {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stringx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for Stringx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for Stringx {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Stringx {}
+
+impl PartialEq<str> for Stringx {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Stringx {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for Stringx {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Stringx {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// 和 str 的哈希一致，配合 Borrow<str> 可以用 &str 查 HashMap<Stringx, _>
+impl Hash for Stringx {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl Borrow<str> for Stringx {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<str> for Stringx {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for Stringx {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<&str> for Stringx {
+    fn from(s: &str) -> Self {
+        let mut string = Stringx::with_capacity(s.len());
+        string.push_str(s);
+        string
+    }
+}
+
+impl From<char> for Stringx {
+    fn from(ch: char) -> Self {
+        let mut string = Stringx::new();
+        string.push(ch);
+        string
+    }
+}
+
+/// 直接接管 `String` 的缓冲区，不复制：两者都用全局分配器按 `[u8; cap]` 分配
+impl From<String> for Stringx {
+    fn from(s: String) -> Self {
+        let mut bytes = ManuallyDrop::new(s.into_bytes());
+        unsafe {
+            let vec = Vecx::from_raw_parts(bytes.as_mut_ptr(), bytes.len(), bytes.capacity());
+            Stringx::from_utf8_unchecked(vec)
+        }
+    }
+}
+
+/// 反方向同样不复制
+impl From<Stringx> for String {
+    fn from(s: Stringx) -> Self {
+        let (ptr, len, cap) = s.vec.into_raw_parts();
+        unsafe { String::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl FromIterator<char> for Stringx {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut s = Stringx::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Stringx {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut s = Stringx::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl Extend<char> for Stringx {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        // 下界按每个字符一个字节预留
+        self.reserve(iter.size_hint().0);
+        for ch in iter {
+            self.push(ch);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for Stringx {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
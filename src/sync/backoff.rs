@@ -0,0 +1,59 @@
+use std::cell::Cell;
+use std::hint;
+use std::thread;
+
+// 超过这个步数后 `spin` 不再加倍
+const SPIN_LIMIT: u32 = 6;
+// 超过这个步数后 `is_completed` 返回 true，调用者应该改用阻塞等待
+const YIELD_LIMIT: u32 = 10;
+
+/// 自旋等待的指数退避
+///
+/// `spin` 用于 CAS 失败后的重试（别的线程刚刚取得了进展），只忙等；
+/// `snooze` 用于等待别的线程完成某件事，忙等一段时间后改为 `yield_now` 让出 CPU
+pub struct Backoff {
+    step: Cell<u32>,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Backoff { step: Cell::new(0) }
+    }
+
+    pub fn reset(&self) {
+        self.step.set(0);
+    }
+
+    pub fn spin(&self) {
+        for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
+            hint::spin_loop();
+        }
+        if self.step.get() <= SPIN_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    pub fn snooze(&self) {
+        if self.step.get() <= SPIN_LIMIT {
+            for _ in 0..1 << self.step.get() {
+                hint::spin_loop();
+            }
+        } else {
+            thread::yield_now();
+        }
+        if self.step.get() <= YIELD_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    /// 已经退避了足够久，继续自旋不划算，应该 park 或者换用锁
+    pub fn is_completed(&self) -> bool {
+        self.step.get() > YIELD_LIMIT
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::new()
+    }
+}
@@ -0,0 +1,92 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::vecx::Vecx;
+
+/// 按 128 字节对齐，保证相邻的两个值不会落在同一个缓存行上
+/// （x86_64 的相邻行预取会成对加载 64 字节的缓存行，所以用 128）
+#[repr(align(128))]
+#[derive(Debug, Default)]
+pub struct CachePadded<T>(pub T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// 分片计数器：每个线程只写自己的分片，读的时候把所有分片加起来
+///
+/// 和单个 `AtomicUsize` 相比，写入时不再争抢同一个缓存行；代价是读取要遍历所有分片，
+/// 并且在有并发写入时读到的只是某个中间值
+///
+/// 内存序：计数器只用来计数，不用来发布其他数据，所以读写都用 `Relaxed`。
+/// 需要“所有线程都加完之后的准确值”时，由 `join` 等同步操作提供 happens-before，
+/// 不需要计数器自己提供
+pub struct ShardedCounter {
+    shards: Vecx<CachePadded<AtomicUsize>>,
+}
+
+// 给每个线程分配一个固定的编号，用来选择分片
+static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD_INDEX: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+impl ShardedCounter {
+    /// 分片数取可用 CPU 数的 4 倍
+    pub fn new() -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        ShardedCounter::with_shards(cpus * 4)
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards != 0, "shard count must be non-zero");
+        let mut v = Vecx::with_capacity(shards);
+        for _ in 0..shards {
+            v.push(CachePadded(AtomicUsize::new(0)));
+        }
+        ShardedCounter { shards: v }
+    }
+
+    fn shard(&self) -> &AtomicUsize {
+        let index = THREAD_INDEX.with(|i| *i);
+        &self.shards[index % self.shards.len()]
+    }
+
+    pub fn add(&self, n: usize) {
+        self.shard().fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// 所有分片之和（wrapping）
+    pub fn get(&self) -> usize {
+        self.shards
+            .iter()
+            .fold(0usize, |sum, shard| sum.wrapping_add(shard.load(Ordering::Relaxed)))
+    }
+
+    pub fn reset(&mut self) {
+        for shard in self.shards.iter_mut() {
+            *shard.get_mut() = 0;
+        }
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        ShardedCounter::new()
+    }
+}
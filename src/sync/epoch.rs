@@ -0,0 +1,246 @@
+//! 基于 epoch 的延迟回收
+//!
+//! 无锁结构把节点摘下来之后，别的线程可能还拿着指向它的指针，不能马上释放。
+//! 做法和 crossbeam-epoch 一样，只是简化了很多：
+//! - 全局有一个 epoch 计数；线程访问共享结构之前先 `pin`，记下当时的全局 epoch
+//! - 摘下的节点交给 `Guard::defer_destroy`，标上摘下时的全局 epoch，放进线程自己的垃圾袋
+//! - 只有所有被 pin 住的线程都已经处在当前 epoch 时，全局 epoch 才能加一
+//! - 标记为 `e` 的垃圾在全局 epoch 到达 `e + 2` 之后释放：此时还 pin 着的线程
+//!   都是在摘下之后才 pin 的，不可能再看到这个节点
+//!
+//! 内存序：pin 时写入自己的 epoch 之后、摘节点之后读全局 epoch 之前、推进 epoch
+//! 之前各有一个 `SeqCst` fence，保证“读到旧节点的线程”和“推进 epoch 的线程”
+//! 至少有一方看到另一方的写入
+
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use super::OnceSlot;
+use crate::vecx::Vecx;
+
+// 垃圾袋每攒这么多个就尝试推进一次 epoch 并回收
+const COLLECT_THRESHOLD: usize = 64;
+
+/// 一个待释放的对象
+struct Deferred {
+    ptr: *mut u8,
+    destroy: unsafe fn(*mut u8),
+}
+
+// 只在 defer_destroy 的调用者保证 T: Send 时才会跨线程
+unsafe impl Send for Deferred {}
+
+unsafe fn destroy_box<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+impl Deferred {
+    unsafe fn run(self) {
+        (self.destroy)(self.ptr)
+    }
+}
+
+/// 参与者记录：每个线程占用一个，线程退出后留给后来的线程复用，永不释放
+struct Participant {
+    // 0 表示没有 pin，否则是 `epoch << 1 | 1`
+    state: AtomicUsize,
+    in_use: AtomicBool,
+    next: *const Participant,
+}
+
+struct Global {
+    epoch: AtomicUsize,
+    participants: AtomicPtr<Participant>,
+    // 已退出线程留下的垃圾
+    orphans: OnceSlot<Mutex<Vecx<(usize, Deferred)>>>,
+}
+
+unsafe impl Sync for Global {}
+
+static GLOBAL: Global = Global {
+    epoch: AtomicUsize::new(0),
+    participants: AtomicPtr::new(ptr::null_mut()),
+    orphans: OnceSlot::new(),
+};
+
+impl Global {
+    fn orphans(&self) -> &Mutex<Vecx<(usize, Deferred)>> {
+        self.orphans.get_or_init(|| Mutex::new(Vecx::new()))
+    }
+
+    fn register(&self) -> &'static Participant {
+        let mut p = self.participants.load(Ordering::Acquire);
+        while !p.is_null() {
+            let participant = unsafe { &*p };
+            if !participant.in_use.load(Ordering::Relaxed)
+                && participant
+                    .in_use
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return participant;
+            }
+            p = participant.next as *mut Participant;
+        }
+
+        let new = Box::into_raw(Box::new(Participant {
+            state: AtomicUsize::new(0),
+            in_use: AtomicBool::new(true),
+            next: ptr::null(),
+        }));
+        let mut head = self.participants.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*new).next = head };
+            match self.participants.compare_exchange_weak(head, new, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return unsafe { &*new },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// 所有被 pin 住的线程都处在当前 epoch 时把 epoch 加一，返回最新的 epoch
+    fn try_advance(&self) -> usize {
+        fence(Ordering::SeqCst);
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        let mut p = self.participants.load(Ordering::Acquire);
+        while !p.is_null() {
+            let participant = unsafe { &*p };
+            let state = participant.state.load(Ordering::Relaxed);
+            if state & 1 == 1 && state >> 1 != epoch & (usize::MAX >> 1) {
+                return epoch;
+            }
+            p = participant.next as *mut Participant;
+        }
+        fence(Ordering::Acquire);
+        match self.epoch.compare_exchange(epoch, epoch.wrapping_add(1), Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => epoch.wrapping_add(1),
+            Err(current) => current,
+        }
+    }
+}
+
+// 把已经过了两个 epoch 的垃圾从 bag 里取出来，其余的留在 bag 里
+//
+// 这里只取不放：析构函数可能会再调用 `defer_destroy`（比如节点的 drop 把子节点也交给 epoch），
+// 所以必须先释放 bag 的借用或者 orphans 的锁，再用 `run_all` 执行
+fn collect(bag: &mut Vecx<(usize, Deferred)>, epoch: usize) -> Vecx<Deferred> {
+    let mut young = Vecx::new();
+    let mut ripe = Vecx::new();
+    for (tag, deferred) in bag.drain() {
+        if epoch.wrapping_sub(tag) >= 2 {
+            ripe.push(deferred);
+        } else {
+            young.push((tag, deferred));
+        }
+    }
+    *bag = young;
+    ripe
+}
+
+fn run_all(ripe: Vecx<Deferred>) {
+    for deferred in ripe {
+        unsafe { deferred.run() };
+    }
+}
+
+struct Local {
+    participant: &'static Participant,
+    guards: Cell<usize>,
+    bag: RefCell<Vecx<(usize, Deferred)>>,
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        let bag = self.bag.get_mut();
+        if !bag.is_empty() {
+            let mut orphans = GLOBAL.orphans().lock().unwrap();
+            for item in bag.drain() {
+                orphans.push(item);
+            }
+        }
+        self.participant.state.store(0, Ordering::Release);
+        self.participant.in_use.store(false, Ordering::Release);
+    }
+}
+
+thread_local! {
+    static LOCAL: Local = Local {
+        participant: GLOBAL.register(),
+        guards: Cell::new(0),
+        bag: RefCell::new(Vecx::new()),
+    };
+}
+
+/// pin 住当前线程，`Guard` 存活期间读到的节点都不会被释放
+///
+/// 可以嵌套；在线程局部变量已经销毁的阶段（线程退出时的 TLS 析构）调用会 panic
+pub fn pin() -> Guard {
+    let local = LOCAL.with(|local| local as *const Local);
+    let l = unsafe { &*local };
+    let guards = l.guards.get();
+    if guards == 0 {
+        let epoch = GLOBAL.epoch.load(Ordering::Relaxed);
+        l.participant.state.store(epoch << 1 | 1, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+    }
+    l.guards.set(guards + 1);
+    Guard { local, _not_send: PhantomData }
+}
+
+/// `pin` 返回的凭证，不能跨线程
+pub struct Guard {
+    local: *const Local,
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl Guard {
+    fn local(&self) -> &Local {
+        // Guard 不能离开创建它的线程，而线程局部变量比线程上的 Guard 活得久
+        unsafe { &*self.local }
+    }
+
+    /// 等到所有线程都不可能再访问 `ptr` 之后，把它当作 `Box<T>` 释放
+    ///
+    /// # Safety
+    /// `ptr` 必须来自 `Box::into_raw`，并且已经从共享结构上摘下，之后不会再有线程能新读到它；
+    /// 释放可能发生在别的线程上，所以 `T` 需要能跨线程 drop
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        let deferred = Deferred { ptr: ptr as *mut u8, destroy: destroy_box::<T> };
+        fence(Ordering::SeqCst);
+        let tag = GLOBAL.epoch.load(Ordering::Relaxed);
+        let full = {
+            let mut bag = self.local().bag.borrow_mut();
+            bag.push((tag, deferred));
+            bag.len().is_multiple_of(COLLECT_THRESHOLD)
+        };
+        if full {
+            self.flush();
+        }
+    }
+
+    /// 尝试推进 epoch，并释放本线程和已退出线程留下的、已经安全的垃圾
+    pub fn flush(&self) {
+        let epoch = GLOBAL.try_advance();
+        let ripe = collect(&mut self.local().bag.borrow_mut(), epoch);
+        run_all(ripe);
+        let orphaned = match GLOBAL.orphans().try_lock() {
+            Ok(mut orphans) => collect(&mut orphans, epoch),
+            Err(_) => return,
+        };
+        run_all(orphaned);
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let local = self.local();
+        let guards = local.guards.get() - 1;
+        local.guards.set(guards);
+        if guards == 0 {
+            local.participant.state.store(0, Ordering::Release);
+        }
+    }
+}
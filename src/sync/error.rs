@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+
+/// `try_push` 失败，原值随错误一起返回
+#[derive(Clone, PartialEq, Eq)]
+pub enum TryPushError<T> {
+    /// 队列已满
+    Full(T),
+    /// 另一端已经全部关闭，再也不会有人取走
+    Disconnected(T),
+}
+
+impl<T> TryPushError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            TryPushError::Full(value) | TryPushError::Disconnected(value) => value,
+        }
+    }
+}
+
+/// 阻塞的 `push` 只会因为另一端关闭而失败
+#[derive(Clone, PartialEq, Eq)]
+pub struct PushError<T>(pub T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryPopError {
+    /// 队列暂时为空
+    Empty,
+    /// 队列为空，并且写入端已经全部关闭
+    Disconnected,
+}
+
+// 和 std 的 SendError 一样，Debug 不要求 T: Debug
+impl<T> fmt::Debug for TryPushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryPushError::Full(_) => f.write_str("Full(..)"),
+            TryPushError::Disconnected(_) => f.write_str("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PushError(..)")
+    }
+}
+
+impl<T> fmt::Display for TryPushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryPushError::Full(_) => f.write_str("pushing into a full queue"),
+            TryPushError::Disconnected(_) => f.write_str("pushing into a disconnected queue"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("pushing into a disconnected queue")
+    }
+}
+
+impl fmt::Display for TryPopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryPopError::Empty => f.write_str("popping from an empty queue"),
+            TryPopError::Disconnected => f.write_str("popping from an empty and disconnected queue"),
+        }
+    }
+}
+
+impl<T> Error for TryPushError<T> {}
+impl<T> Error for PushError<T> {}
+impl Error for TryPopError {}
@@ -0,0 +1,23 @@
+//! 并发原语
+//!
+//! 每个类型的文档里都写明了用到的内存序以及为什么够用。约定：
+//! 只用来计数、不用来发布其他数据的原子变量一律用 `Relaxed`；
+//! 发布数据的一方用 `Release`，读取数据的一方用 `Acquire`，两者成对出现
+
+pub mod backoff;
+pub mod counter;
+pub mod epoch;
+pub mod error;
+pub mod mpmc;
+pub mod once;
+pub mod seqlock;
+pub mod spsc;
+pub mod stack;
+mod waiter;
+
+pub use backoff::Backoff;
+pub use counter::{CachePadded, ShardedCounter};
+pub use error::{PushError, TryPopError, TryPushError};
+pub use once::OnceSlot;
+pub use seqlock::SeqLock;
+pub use stack::AtomicStack;
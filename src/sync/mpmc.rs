@@ -0,0 +1,304 @@
+//! 多生产者多消费者的有界队列（Dmitry Vyukov 的有界 MPMC 队列）
+//!
+//! 每个槽位带一个序号 `seq`，由它判断槽位当前处于哪一轮、是否可写或可读：
+//! - 写入位置为 `pos` 时，`seq == pos` 表示槽位空闲，可以抢占 `pos`
+//! - 读取位置为 `pos` 时，`seq == pos + 1` 表示槽位已写好，可以抢占 `pos`
+//! - 读完之后把 `seq` 设为 `pos + cap`，留给下一轮写入
+//!
+//! 内存序：抢占位置的 CAS 只是分配下标，用 `Relaxed`；槽位里的数据由 `seq` 发布，
+//! 写完（读完）数据后用 `Release` 写 `seq`，另一方用 `Acquire` 读 `seq` 之后才碰数据
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::error::{PushError, TryPopError, TryPushError};
+use super::waiter::WaitList;
+use super::{Backoff, CachePadded};
+use crate::vecx::raw_vec::RawVec;
+
+struct Slot<T> {
+    seq: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Queue<T> {
+    buf: RawVec<Slot<T>>,
+    mask: usize,
+    enqueue: CachePadded<AtomicUsize>,
+    dequeue: CachePadded<AtomicUsize>,
+    // 还活着的句柄数，降到 0 表示这一端已经关闭
+    producers: AtomicUsize,
+    consumers: AtomicUsize,
+    readers: WaitList,
+    writers: WaitList,
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn slot(&self, pos: usize) -> &Slot<T> {
+        unsafe { &*self.buf.ptr.as_ptr().add(pos & self.mask) }
+    }
+
+    fn try_push(&self, value: T) -> Result<(), TryPushError<T>> {
+        if self.consumers.load(Ordering::Acquire) == 0 {
+            return Err(TryPushError::Disconnected(value));
+        }
+        let backoff = Backoff::new();
+        let mut pos = self.enqueue.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            let diff = seq.wrapping_sub(pos) as isize;
+            if diff == 0 {
+                match self.enqueue.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.seq.store(pos.wrapping_add(1), Ordering::Release);
+                        self.readers.notify_all();
+                        return Ok(());
+                    }
+                    Err(current) => {
+                        pos = current;
+                        backoff.spin();
+                    }
+                }
+            } else if diff < 0 {
+                // 槽位还是上一轮的数据，没被读走
+                return Err(TryPushError::Full(value));
+            } else {
+                // 别的生产者已经抢走了 pos
+                pos = self.enqueue.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn try_pop(&self) -> Result<T, TryPopError> {
+        let backoff = Backoff::new();
+        let mut pos = self.dequeue.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            let diff = seq.wrapping_sub(pos.wrapping_add(1)) as isize;
+            if diff == 0 {
+                match self.dequeue.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.seq.store(pos.wrapping_add(self.capacity()), Ordering::Release);
+                        self.writers.notify_all();
+                        return Ok(value);
+                    }
+                    Err(current) => {
+                        pos = current;
+                        backoff.spin();
+                    }
+                }
+            } else if diff < 0 {
+                if self.producers.load(Ordering::Acquire) != 0 {
+                    return Err(TryPopError::Empty);
+                }
+                // 最后一个生产者关闭前的写入一定先于计数归零可见，再确认一次
+                let pos = self.dequeue.load(Ordering::Relaxed);
+                if self.slot(pos).seq.load(Ordering::Acquire) != pos.wrapping_add(1) {
+                    return Err(TryPopError::Disconnected);
+                }
+                return self.try_pop();
+            } else {
+                pos = self.dequeue.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    // 阻塞等待时用的近似判断，真正的结果以 try_* 为准
+    fn has_room(&self) -> bool {
+        let pos = self.enqueue.load(Ordering::Relaxed);
+        self.slot(pos).seq.load(Ordering::Acquire) == pos || self.consumers.load(Ordering::Acquire) == 0
+    }
+
+    fn has_data(&self) -> bool {
+        let pos = self.dequeue.load(Ordering::Relaxed);
+        self.slot(pos).seq.load(Ordering::Acquire) == pos.wrapping_add(1)
+            || self.producers.load(Ordering::Acquire) == 0
+    }
+
+    fn len(&self) -> usize {
+        let dequeue = self.dequeue.load(Ordering::Relaxed);
+        let enqueue = self.enqueue.load(Ordering::Relaxed);
+        enqueue.wrapping_sub(dequeue).min(self.capacity())
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // 所有句柄都已经 drop，[dequeue, enqueue) 里的槽位都写完了
+        let mut pos = *self.dequeue.get_mut();
+        let end = *self.enqueue.get_mut();
+        while pos != end {
+            let slot = self.slot(pos);
+            unsafe { ptr::drop_in_place((*slot.value.get()).as_mut_ptr()) };
+            pos = pos.wrapping_add(1);
+        }
+    }
+}
+
+/// 创建一个容量至少为 `cap` 的队列，容量会向上取到 2 的幂（最小为 2）
+pub fn channel<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(cap != 0, "capacity must be non-zero");
+    // 只有一个槽位时，读完后留给下一轮的 seq（pos + cap）和“已写好”的 seq（pos + 1）相同，至少要两个
+    let cap = cap.max(2).checked_next_power_of_two().expect("capacity overflow");
+    let buf = RawVec::<Slot<T>>::with_capacity(cap);
+    for i in 0..cap {
+        let slot = Slot { seq: AtomicUsize::new(i), value: UnsafeCell::new(MaybeUninit::uninit()) };
+        unsafe { ptr::write(buf.ptr.as_ptr().add(i), slot) };
+    }
+    let queue = Arc::new(Queue {
+        buf,
+        mask: cap - 1,
+        enqueue: CachePadded(AtomicUsize::new(0)),
+        dequeue: CachePadded(AtomicUsize::new(0)),
+        producers: AtomicUsize::new(1),
+        consumers: AtomicUsize::new(1),
+        readers: WaitList::new(),
+        writers: WaitList::new(),
+    });
+    (Producer { queue: queue.clone() }, Consumer { queue })
+}
+
+/// 写入端，可以 clone 给多个线程
+pub struct Producer<T> {
+    queue: Arc<Queue<T>>,
+}
+
+/// 读取端，可以 clone 给多个线程
+pub struct Consumer<T> {
+    queue: Arc<Queue<T>>,
+}
+
+impl<T> Producer<T> {
+    pub fn try_push(&self, value: T) -> Result<(), TryPushError<T>> {
+        self.queue.try_push(value)
+    }
+
+    /// 队列满时阻塞，消费者全部关闭时返回原值
+    pub fn push(&self, mut value: T) -> Result<(), PushError<T>> {
+        loop {
+            match self.queue.try_push(value) {
+                Ok(()) => return Ok(()),
+                Err(TryPushError::Disconnected(v)) => return Err(PushError(v)),
+                Err(TryPushError::Full(v)) => value = v,
+            }
+            self.queue.writers.wait_until(|| self.queue.has_room());
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// 并发下只是一个近似值
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 消费者是否已经全部关闭
+    pub fn is_disconnected(&self) -> bool {
+        self.queue.consumers.load(Ordering::Acquire) == 0
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        self.queue.try_pop()
+    }
+
+    /// 队列空时阻塞；生产者全部关闭并且队列已经取空时返回 `None`
+    pub fn pop(&self) -> Option<T> {
+        loop {
+            match self.queue.try_pop() {
+                Ok(value) => return Some(value),
+                Err(TryPopError::Disconnected) => return None,
+                Err(TryPopError::Empty) => {}
+            }
+            self.queue.readers.wait_until(|| self.queue.has_data());
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 生产者是否已经全部关闭，队列里可能还有没取走的元素
+    pub fn is_disconnected(&self) -> bool {
+        self.queue.producers.load(Ordering::Acquire) == 0
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        self.queue.producers.fetch_add(1, Ordering::Relaxed);
+        Producer { queue: self.queue.clone() }
+    }
+}
+
+impl<T> Clone for Consumer<T> {
+    fn clone(&self) -> Self {
+        self.queue.consumers.fetch_add(1, Ordering::Relaxed);
+        Consumer { queue: self.queue.clone() }
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        // 最后一个生产者关闭：叫醒所有等数据的消费者，让它们看到关闭
+        if self.queue.producers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.readers.notify_all();
+        }
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        if self.queue.consumers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.writers.notify_all();
+        }
+    }
+}
+
+impl<T> Iterator for Consumer<T> {
+    type Item = T;
+
+    /// 阻塞地逐个取出，直到生产者全部关闭
+    fn next(&mut self) -> Option<T> {
+        self.pop()
+    }
+}
@@ -0,0 +1,124 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::Backoff;
+
+const EMPTY: u8 = 0;
+const RUNNING: u8 = 1;
+const READY: u8 = 2;
+
+/// 只能写入一次的槽，类似 `std::sync::OnceLock`
+///
+/// 内存序：写入值之后用 `Release` 把状态改成 READY，读者用 `Acquire` 看到 READY 之后
+/// 才去读值，所以读者一定能看到完整的值。抢初始化权的 CAS 成功时用 `Acquire`，
+/// 和上一个初始化失败（panic）的线程用 `Release` 恢复 EMPTY 配对
+pub struct OnceSlot<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Send for OnceSlot<T> {}
+// 多个线程可以同时拿到 &T，初始化也可能发生在别的线程上
+unsafe impl<T: Send + Sync> Sync for OnceSlot<T> {}
+
+// 初始化函数 panic 时把状态恢复为 EMPTY，让其他线程可以重试
+struct Reset<'a>(&'a AtomicU8);
+
+impl Drop for Reset<'_> {
+    fn drop(&mut self) {
+        self.0.store(EMPTY, Ordering::Release);
+    }
+}
+
+impl<T> OnceSlot<T> {
+    pub const fn new() -> Self {
+        OnceSlot { state: AtomicU8::new(EMPTY), value: UnsafeCell::new(MaybeUninit::uninit()) }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == READY {
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// 已经有值时返回 `Err(value)`
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// 返回已有的值；没有值时由当前线程调用 `f` 初始化，同时到来的其他线程等待它完成
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        let mut f = Some(f);
+        let backoff = Backoff::new();
+        loop {
+            if let Some(value) = self.get() {
+                return value;
+            }
+            match self.state.compare_exchange(EMPTY, RUNNING, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => {
+                    let reset = Reset(&self.state);
+                    let value = (f.take().unwrap())();
+                    std::mem::forget(reset);
+                    unsafe { (*self.value.get()).write(value) };
+                    self.state.store(READY, Ordering::Release);
+                }
+                // 别的线程正在初始化
+                Err(_) => backoff.snooze(),
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.state.get_mut() == READY {
+            Some(unsafe { self.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    pub fn take(&mut self) -> Option<T> {
+        if *self.state.get_mut() == READY {
+            *self.state.get_mut() = EMPTY;
+            Some(unsafe { self.value.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+}
+
+impl<T> Default for OnceSlot<T> {
+    fn default() -> Self {
+        OnceSlot::new()
+    }
+}
+
+impl<T> Drop for OnceSlot<T> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("OnceSlot").field(value).finish(),
+            None => f.write_str("OnceSlot(<uninit>)"),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+
+use super::Backoff;
+
+/// 顺序锁：读者不加锁，乐观地复制数据，再检查期间有没有写者进来过
+///
+/// 适合读多写少、数据很小并且是 `Copy` 的场景。写者之间通过序号互斥
+///
+/// 序号为奇数表示正在写。内存序：
+/// - 写者用 `Acquire` 的 CAS 把序号从偶数改成奇数，再用 `Release` fence 保证
+///   “序号变成奇数”先于数据的写入被看到；写完后用 `Release` 把序号改回偶数，发布数据
+/// - 读者先 `Acquire` 读序号，复制数据，再用 `Acquire` fence 保证复制先于第二次读序号完成；
+///   两次读到同一个偶数，说明复制期间没有写者，数据是完整的
+///
+/// 读者的复制可能和写者的写入同时发生，这在 Rust 的内存模型里是数据竞争，
+/// 这里和常见的 seqlock 实现一样用 `read_volatile` 复制。复制出来的是 `MaybeUninit<T>`，
+/// 撕裂的字节可能不是合法的 `T`（比如 `bool`、`char`、枚举），所以只有序号检查通过后
+/// 才 `assume_init`，撕裂的结果直接丢弃，从不当成 `T` 使用
+pub struct SeqLock<T: Copy> {
+    seq: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    pub const fn new(value: T) -> Self {
+        SeqLock { seq: AtomicUsize::new(0), data: UnsafeCell::new(value) }
+    }
+
+    pub fn read(&self) -> T {
+        let backoff = Backoff::new();
+        loop {
+            let s1 = self.seq.load(Ordering::Acquire);
+            if s1 & 1 == 0 {
+                let value = unsafe { ptr::read_volatile(self.data.get().cast::<MaybeUninit<T>>()) };
+                fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) == s1 {
+                    // 复制期间没有写者，字节来自同一次完整的写入
+                    return unsafe { value.assume_init() };
+                }
+            }
+            backoff.snooze();
+        }
+    }
+
+    pub fn write(&self, value: T) {
+        let backoff = Backoff::new();
+        let s = loop {
+            let s = self.seq.load(Ordering::Relaxed);
+            if s & 1 == 0
+                && self
+                    .seq
+                    .compare_exchange_weak(s, s.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                break s;
+            }
+            backoff.snooze();
+        };
+        fence(Ordering::Release);
+        unsafe { ptr::write_volatile(self.data.get(), value) };
+        self.seq.store(s.wrapping_add(2), Ordering::Release);
+    }
+
+    /// 当前序号，每次写入加 2
+    pub fn version(&self) -> usize {
+        self.seq.load(Ordering::Acquire)
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    fn default() -> Self {
+        SeqLock::new(T::default())
+    }
+}
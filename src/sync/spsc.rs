@@ -0,0 +1,237 @@
+//! 单生产者单消费者的有界队列
+//!
+//! 两端各自只写自己的下标（生产者写 `tail`，消费者写 `head`），`try_push`/`try_pop`
+//! 没有循环也没有 CAS。对端没有阻塞等待时它们是 wait-free 的；对端阻塞在 `push`/`pop` 里时，
+//! 唤醒它需要拿 `WaitList` 的锁，这一步不是无锁的。每一端还缓存了对端下标的旧值，
+//! 只有按缓存看队列满（空）时才去读对端的缓存行
+//!
+//! 内存序：生产者写完槽位后用 `Release` 推进 `tail`，消费者用 `Acquire` 读 `tail`
+//! 之后才读槽位；反方向同理，消费者读完槽位后用 `Release` 推进 `head`，
+//! 生产者用 `Acquire` 读 `head` 之后才覆盖槽位
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::error::{PushError, TryPopError, TryPushError};
+use super::waiter::WaitList;
+use super::CachePadded;
+use crate::vecx::raw_vec::RawVec;
+
+struct Ring<T> {
+    buf: RawVec<T>,
+    mask: usize,
+    // 下一个要读的位置，只由消费者写
+    head: CachePadded<AtomicUsize>,
+    // 下一个要写的位置，只由生产者写
+    tail: CachePadded<AtomicUsize>,
+    // 任意一端被 drop 后置位
+    disconnected: AtomicBool,
+    // 等待数据的消费者 / 等待空位的生产者
+    readers: WaitList,
+    writers: WaitList,
+}
+
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        unsafe { self.buf.ptr.as_ptr().add(index & self.mask) }
+    }
+
+    fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    fn disconnect(&self) {
+        self.disconnected.store(true, Ordering::Release);
+        self.readers.notify_all();
+        self.writers.notify_all();
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        // 两端都已经 drop，剩下的元素在 [head, tail) 里
+        let mut head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        while head != tail {
+            unsafe { ptr::drop_in_place(self.slot(head)) };
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+/// 创建一个容量至少为 `cap` 的队列，容量会向上取到 2 的幂
+pub fn channel<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(cap != 0, "capacity must be non-zero");
+    let cap = cap.checked_next_power_of_two().expect("capacity overflow");
+    let ring = Arc::new(Ring {
+        buf: RawVec::with_capacity(cap),
+        mask: cap - 1,
+        head: CachePadded(AtomicUsize::new(0)),
+        tail: CachePadded(AtomicUsize::new(0)),
+        disconnected: AtomicBool::new(false),
+        readers: WaitList::new(),
+        writers: WaitList::new(),
+    });
+    (
+        Producer { ring: ring.clone(), tail: 0, cached_head: 0 },
+        Consumer { ring, head: 0, cached_tail: 0 },
+    )
+}
+
+/// 写入端，不能 clone
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    // 自己的下标不需要从原子变量里读回来
+    tail: usize,
+    cached_head: usize,
+}
+
+/// 读取端，不能 clone
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    head: usize,
+    cached_tail: usize,
+}
+
+impl<T> Producer<T> {
+    pub fn try_push(&mut self, value: T) -> Result<(), TryPushError<T>> {
+        let ring = &*self.ring;
+        if ring.disconnected.load(Ordering::Relaxed) {
+            return Err(TryPushError::Disconnected(value));
+        }
+        if self.tail.wrapping_sub(self.cached_head) == ring.capacity() {
+            self.cached_head = ring.head.load(Ordering::Acquire);
+            if self.tail.wrapping_sub(self.cached_head) == ring.capacity() {
+                return Err(TryPushError::Full(value));
+            }
+        }
+        unsafe { ptr::write(ring.slot(self.tail), value) };
+        self.tail = self.tail.wrapping_add(1);
+        ring.tail.store(self.tail, Ordering::Release);
+        ring.readers.notify_all();
+        Ok(())
+    }
+
+    /// 队列满时阻塞，消费者已经关闭时返回原值
+    pub fn push(&mut self, mut value: T) -> Result<(), PushError<T>> {
+        loop {
+            match self.try_push(value) {
+                Ok(()) => return Ok(()),
+                Err(TryPushError::Disconnected(v)) => return Err(PushError(v)),
+                Err(TryPushError::Full(v)) => value = v,
+            }
+            let ring = &*self.ring;
+            let tail = self.tail;
+            ring.writers.wait_until(|| {
+                tail.wrapping_sub(ring.head.load(Ordering::Acquire)) < ring.capacity()
+                    || ring.disconnected.load(Ordering::Acquire)
+            });
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// 并发下只是一个近似值
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 消费者是否已经关闭
+    pub fn is_disconnected(&self) -> bool {
+        self.ring.disconnected.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn try_pop(&mut self) -> Result<T, TryPopError> {
+        let ring = &*self.ring;
+        if self.head == self.cached_tail {
+            self.cached_tail = ring.tail.load(Ordering::Acquire);
+            if self.head == self.cached_tail {
+                if !ring.disconnected.load(Ordering::Acquire) {
+                    return Err(TryPopError::Empty);
+                }
+                // 生产者在关闭前推进的 tail 一定先于 disconnected 可见，这里再读一次
+                self.cached_tail = ring.tail.load(Ordering::Acquire);
+                if self.head == self.cached_tail {
+                    return Err(TryPopError::Disconnected);
+                }
+            }
+        }
+        let value = unsafe { ptr::read(ring.slot(self.head)) };
+        self.head = self.head.wrapping_add(1);
+        ring.head.store(self.head, Ordering::Release);
+        ring.writers.notify_all();
+        Ok(value)
+    }
+
+    /// 队列空时阻塞；生产者已经关闭并且队列已经取空时返回 `None`
+    pub fn pop(&mut self) -> Option<T> {
+        loop {
+            match self.try_pop() {
+                Ok(value) => return Some(value),
+                Err(TryPopError::Disconnected) => return None,
+                Err(TryPopError::Empty) => {}
+            }
+            let ring = &*self.ring;
+            let head = self.head;
+            ring.readers.wait_until(|| {
+                ring.tail.load(Ordering::Acquire) != head || ring.disconnected.load(Ordering::Acquire)
+            });
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 生产者是否已经关闭，队列里可能还有没取走的元素
+    pub fn is_disconnected(&self) -> bool {
+        self.ring.disconnected.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.ring.disconnect();
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.ring.disconnect();
+    }
+}
+
+impl<T> Iterator for Consumer<T> {
+    type Item = T;
+
+    /// 阻塞地逐个取出，直到生产者关闭
+    fn next(&mut self) -> Option<T> {
+        self.pop()
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use super::{epoch, Backoff};
+use crate::LinkedList::LinkedList;
+
+struct Node<T> {
+    // 元素在 pop 时按位读走，释放节点时不能再 drop 一次
+    elem: ManuallyDrop<T>,
+    // 发布之后不再修改
+    next: *mut Node<T>,
+}
+
+/// Treiber 无锁栈，`push`/`pop` 只需要 `&self`
+///
+/// 表头是一个 `AtomicPtr`，push 和 pop 都是“读表头 -> 准备新值 -> CAS 表头”。
+/// 弹出的节点交给 [`epoch`] 延迟释放：别的线程可能刚读到这个节点、正要读它的 `next`，
+/// 只要它还 pin 着，节点的内存就不会被释放或复用，所以不存在 ABA 问题
+///
+/// 内存序：push 的 CAS 用 `Release` 发布节点内容，pop 用 `Acquire` 读表头之后才读节点
+pub struct AtomicStack<T> {
+    head: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for AtomicStack<T> {}
+unsafe impl<T: Send> Sync for AtomicStack<T> {}
+
+impl<T> AtomicStack<T> {
+    pub const fn new() -> Self {
+        AtomicStack { head: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node { elem: ManuallyDrop::new(elem), next: ptr::null_mut() }));
+        let backoff = Backoff::new();
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // 节点还没发布，可以随便写
+            unsafe { (*node).next = head };
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => {
+                    head = current;
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = epoch::pin();
+        let backoff = Backoff::new();
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            if head.is_null() {
+                return None;
+            }
+            // pin 住期间 head 不会被释放，即使它已经被别的线程弹出
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                let elem = unsafe { ptr::read(&*(*head).elem) };
+                unsafe { guard.defer_destroy(head) };
+                return Some(elem);
+            }
+            backoff.spin();
+        }
+    }
+
+    /// 并发下只是一个瞬间的快照
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Relaxed).is_null()
+    }
+
+    /// 一次取走所有元素，返回的链表从原来的栈顶开始
+    ///
+    /// 摘下的节点可能还有 pop 到一半的线程在读，不能直接挂到 `LinkedList` 上，
+    /// 元素被移进新的链表节点，旧节点照常延迟释放
+    pub fn take_all(&self) -> LinkedList<T> {
+        let guard = epoch::pin();
+        let mut node = self.head.swap(ptr::null_mut(), Ordering::Acquire);
+        let mut list = LinkedList::new();
+        while !node.is_null() {
+            unsafe {
+                list.push(ptr::read(&*(*node).elem));
+                let next = (*node).next;
+                guard.defer_destroy(node);
+                node = next;
+            }
+        }
+        // push 之后顺序是反的
+        list.reverse();
+        list
+    }
+}
+
+impl<T> Default for AtomicStack<T> {
+    fn default() -> Self {
+        AtomicStack::new()
+    }
+}
+
+impl<T> Drop for AtomicStack<T> {
+    fn drop(&mut self) {
+        // 已经没有别的线程能访问，直接释放
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next;
+            unsafe { ManuallyDrop::drop(&mut boxed.elem) };
+        }
+    }
+}
+
+impl<T> fmt::Debug for AtomicStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicStack").field("is_empty", &self.is_empty()).finish()
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, Thread};
+
+use super::Backoff;
+use crate::vecx::Vecx;
+
+/// 阻塞等待的线程列表，队列的阻塞版本用它在 `try_*` 失败后睡眠
+///
+/// 快路径（没有人睡眠）上 `notify` 只读一个原子变量，不加锁。
+/// 防止丢失唤醒靠的是两边各一个 `SeqCst` fence：
+/// 等待方先登记（`sleepers` 加一）再 fence 再检查条件；
+/// 通知方先修改队列再 fence 再读 `sleepers`。
+/// 两个 fence 之间总有先后，所以要么等待方看到了修改，要么通知方看到了登记
+pub(crate) struct WaitList {
+    sleepers: AtomicUsize,
+    threads: Mutex<Vecx<Thread>>,
+}
+
+impl WaitList {
+    pub(crate) fn new() -> Self {
+        WaitList { sleepers: AtomicUsize::new(0), threads: Mutex::new(Vecx::new()) }
+    }
+
+    /// 阻塞直到 `ready` 返回 true。先自旋一小会儿，再 park
+    pub(crate) fn wait_until<F: Fn() -> bool>(&self, ready: F) {
+        let backoff = Backoff::new();
+        while !backoff.is_completed() {
+            if ready() {
+                return;
+            }
+            backoff.snooze();
+        }
+
+        let me = thread::current();
+        let id = me.id();
+        self.threads.lock().unwrap().push(me);
+        self.sleepers.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        // park 可能被虚假唤醒，所以在循环里重新检查条件
+        while !ready() {
+            thread::park();
+        }
+        let mut threads = self.threads.lock().unwrap();
+        if let Some(pos) = threads.iter().position(|t| t.id() == id) {
+            threads.remove(pos);
+        }
+        self.sleepers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// 唤醒所有在等的线程，由它们自己重新检查条件
+    pub(crate) fn notify_all(&self) {
+        fence(Ordering::SeqCst);
+        // Acquire 和等待方登记时的 fetch_add 配对，保证看到计数时也能在列表里看到线程
+        if self.sleepers.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        for t in self.threads.lock().unwrap().iter() {
+            t.unpark();
+        }
+    }
+}
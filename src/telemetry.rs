@@ -0,0 +1,184 @@
+//! RawVec 的分配统计，打开 `telemetry` feature 后生效
+//!
+//! `RawVec` 在分配、扩容、缩小、释放时按元素类型名记录到全局注册表里，
+//! 用 `snapshot()` 取出当前的统计，`report()` 打印成表格，方便找出应该用 `with_capacity` 的地方
+//!
+//! 缓冲区也可能不经过 RawVec 的分配和释放就进出 Vecx（`from_raw_parts`、`into_raw_parts`、
+//! `leak`、`into_boxed_slice`，以及建立在它们之上的 `Stringx` 和 `String` 互转），
+//! 这些转移单独记在 `adopted` 和 `released` 里，所以任何时刻
+//! `allocations + adopted - deallocations - released` 都是还活着的缓冲区个数
+//!
+//! 注册表本身只使用标准库的容器，不会再经过 RawVec，避免递归
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// 扩容直方图的桶数：第 i 个桶统计扩容后容量落在 `[2^i, 2^(i+1))` 的次数
+pub const GROWTH_BUCKETS: usize = usize::BITS as usize;
+
+/// 一种元素类型的统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeStats {
+    /// 第一次分配（`with_capacity` 或从空容量扩容）
+    pub allocations: u64,
+    /// 已有内存上的扩容
+    pub reallocations: u64,
+    /// 缩小已有的内存（`into_boxed_slice` 等），不计入 `bytes_requested` 和扩容直方图
+    pub shrinks: u64,
+    pub deallocations: u64,
+    /// 通过 `from_raw_parts` 从外部接管的缓冲区
+    pub adopted: u64,
+    /// 通过 `into_raw_parts`、`leak`、`into_boxed_slice` 交出去、不再由 RawVec 释放的缓冲区
+    pub released: u64,
+    /// 分配和扩容时申请的字节数之和
+    pub bytes_requested: u64,
+    /// 见过的最大容量（元素个数）
+    pub peak_capacity: usize,
+    pub growth: [u64; GROWTH_BUCKETS],
+}
+
+/// 某一时刻所有类型的统计，按类型名排序
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    types: BTreeMap<&'static str, TypeStats>,
+}
+
+static REGISTRY: Mutex<BTreeMap<&'static str, TypeStats>> = Mutex::new(BTreeMap::new());
+
+impl Default for TypeStats {
+    fn default() -> Self {
+        TypeStats {
+            allocations: 0,
+            reallocations: 0,
+            shrinks: 0,
+            deallocations: 0,
+            adopted: 0,
+            released: 0,
+            bytes_requested: 0,
+            peak_capacity: 0,
+            growth: [0; GROWTH_BUCKETS],
+        }
+    }
+}
+
+impl TypeStats {
+    /// 非空的直方图桶：（桶的容量下界，次数）
+    pub fn growth_histogram(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.growth
+            .iter()
+            .enumerate()
+            .filter(|(_, &n)| n != 0)
+            .map(|(i, &n)| (1usize << i, n))
+    }
+
+    fn record_capacity(&mut self, cap: usize, bytes: usize) {
+        self.bytes_requested += bytes as u64;
+        self.peak_capacity = self.peak_capacity.max(cap);
+        self.growth[cap.ilog2() as usize] += 1;
+    }
+}
+
+impl Snapshot {
+    pub fn get(&self, type_name: &str) -> Option<&TypeStats> {
+        self.types.get(type_name)
+    }
+
+    pub fn of<T>(&self) -> Option<&TypeStats> {
+        self.get(std::any::type_name::<T>())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &TypeStats)> + '_ {
+        self.types.iter().map(|(name, stats)| (*name, stats))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+/// 按扩容次数从多到少列出各类型，扩容多的就是最需要 `with_capacity` 的
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rows: Vec<_> = self.iter().collect();
+        rows.sort_by(|a, b| b.1.reallocations.cmp(&a.1.reallocations).then(a.0.cmp(b.0)));
+
+        writeln!(
+            f,
+            "{:<40} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>12} {:>10}",
+            "type", "allocs", "reallocs", "shrinks", "deallocs", "adopted", "released", "bytes", "peak cap"
+        )?;
+        for (name, s) in rows {
+            writeln!(
+                f,
+                "{:<40} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>12} {:>10}",
+                name,
+                s.allocations,
+                s.reallocations,
+                s.shrinks,
+                s.deallocations,
+                s.adopted,
+                s.released,
+                s.bytes_requested,
+                s.peak_capacity
+            )?;
+            let histogram: Vec<String> = s.growth_histogram().map(|(cap, n)| format!("{}:{}", cap, n)).collect();
+            if !histogram.is_empty() {
+                writeln!(f, "    growth {}", histogram.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn snapshot() -> Snapshot {
+    Snapshot { types: registry().clone() }
+}
+
+pub fn reset() {
+    registry().clear();
+}
+
+/// 当前统计的文字报告，等同于 `snapshot().to_string()`
+pub fn report() -> String {
+    snapshot().to_string()
+}
+
+// 统计不涉及任何不变式，某个线程在持有锁时 panic 也可以继续使用
+fn registry() -> std::sync::MutexGuard<'static, BTreeMap<&'static str, TypeStats>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn with_stats<T>(f: impl FnOnce(&mut TypeStats)) {
+    f(registry().entry(std::any::type_name::<T>()).or_default());
+}
+
+pub(crate) fn record_alloc<T>(cap: usize, bytes: usize) {
+    with_stats::<T>(|s| {
+        s.allocations += 1;
+        s.record_capacity(cap, bytes);
+    });
+}
+
+pub(crate) fn record_realloc<T>(cap: usize, bytes: usize) {
+    with_stats::<T>(|s| {
+        s.reallocations += 1;
+        s.record_capacity(cap, bytes);
+    });
+}
+
+pub(crate) fn record_shrink<T>() {
+    with_stats::<T>(|s| s.shrinks += 1);
+}
+
+pub(crate) fn record_dealloc<T>() {
+    with_stats::<T>(|s| s.deallocations += 1);
+}
+
+pub(crate) fn record_adopt<T>() {
+    with_stats::<T>(|s| s.adopted += 1);
+}
+
+pub(crate) fn record_release<T>() {
+    with_stats::<T>(|s| s.released += 1);
+}
@@ -0,0 +1,346 @@
+//! 只占一个指针宽度的向量
+
+use std::alloc::{self, Layout};
+use std::cmp;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+use crate::allocator::{Allocator, Global};
+use crate::vecx::drain::Drain;
+use crate::vecx::raw_val_iter::RawValIter;
+
+/// 放在元素前面的头部
+#[repr(C)]
+struct Header {
+    len: usize,
+    cap: usize,
+}
+
+// 所有空的 ThinVecx 共用这一个头部，空向量不分配内存。它是只读的，
+// 任何写入之前都要先 grow 出自己的分配
+static EMPTY_HEADER: Header = Header { len: 0, cap: 0 };
+
+/// 和 `Vecx` 用法相同，但是自身只有一个指针：`len` 和 `cap` 放在堆上元素的前面
+///
+/// 适合放在枚举、语法树节点这类大量存在、多数时候为空或很短的结构里。
+/// 代价是读 `len` 要多一次解引用，并且每个分配多出一个头部
+pub struct ThinVecx<T> {
+    ptr: NonNull<Header>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for ThinVecx<T> {}
+unsafe impl<T: Sync> Sync for ThinVecx<T> {}
+
+pub struct IntoIter<T> {
+    // len 已经设为 0，只负责在最后释放内存
+    _vec: ThinVecx<T>,
+    iter: RawValIter<T>,
+}
+
+// 头部加上 cap 个元素的布局，以及元素相对分配开头的偏移
+fn layout<T>(cap: usize) -> (Layout, usize) {
+    let n = if mem::size_of::<T>() == 0 { 0 } else { cap };
+    let elems = Layout::array::<T>(n).expect("capacity overflow");
+    let (layout, offset) = Layout::new::<Header>().extend(elems).expect("capacity overflow");
+    assert!(layout.size() <= isize::MAX as usize, "Allocation too large");
+    (layout.pad_to_align(), offset)
+}
+
+impl<T> ThinVecx<T> {
+    pub fn new() -> Self {
+        ThinVecx { ptr: NonNull::from(&EMPTY_HEADER), _marker: PhantomData }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut v = ThinVecx::new();
+        if cap != 0 {
+            v.grow_to(cap);
+        }
+        v
+    }
+
+    fn is_singleton(&self) -> bool {
+        ptr::eq(self.ptr.as_ptr(), &EMPTY_HEADER)
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    // 调用前必须确认不是共享的空头部
+    unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(!self.is_singleton());
+        (*self.ptr.as_ptr()).len = len;
+    }
+
+    fn ptr(&self) -> *mut T {
+        if self.is_singleton() {
+            // 空头部后面没有元素，按 T 对齐的悬垂指针即可
+            NonNull::dangling().as_ptr()
+        } else {
+            unsafe { self.ptr.as_ptr().cast::<u8>().add(layout::<T>(0).1).cast() }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.header().cap
+    }
+
+    /// 预留至少 `additional` 个元素的空间，之后的这么多次 push 不会再重新分配
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len().checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity() {
+            return;
+        }
+        if mem::size_of::<T>() == 0 {
+            // 只有空头部的容量是 0，分配出头部之后就是 usize::MAX
+            self.grow();
+        } else {
+            self.grow_to(cmp::max(2 * self.capacity(), required));
+        }
+    }
+
+    /// 把 `other` 的元素依次 clone 到末尾，最多扩容一次
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(other.len());
+        for elem in other {
+            self.push(elem.clone());
+        }
+    }
+
+    fn grow(&mut self) {
+        let cap = self.capacity();
+        let new_cap = if mem::size_of::<T>() == 0 {
+            // ZST 不占空间，只需要分配头部，容量看作无穷大
+            assert!(cap == 0, "capacity overflow");
+            usize::MAX
+        } else if cap == 0 {
+            1
+        } else {
+            2 * cap
+        };
+        self.grow_to(new_cap);
+    }
+
+    fn grow_to(&mut self, new_cap: usize) {
+        let len = self.len();
+        let (new_layout, _) = layout::<T>(new_cap);
+        let new_ptr = if self.is_singleton() {
+            Global.allocate(new_layout)
+        } else {
+            let (old_layout, _) = layout::<T>(self.capacity());
+            unsafe { Global.grow(self.ptr.cast(), old_layout, new_layout) }
+        };
+        let new_ptr = match new_ptr {
+            Ok(p) => p.cast::<Header>(),
+            Err(_) => alloc::handle_alloc_error(new_layout),
+        };
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { new_cap };
+        unsafe { new_ptr.as_ptr().write(Header { len, cap }) };
+        self.ptr = new_ptr;
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let len = self.len();
+        if len == self.capacity() { self.grow(); }
+
+        unsafe {
+            ptr::write(self.ptr().add(len), elem);
+            self.set_len(len + 1);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            None
+        } else {
+            unsafe {
+                self.set_len(len - 1);
+                Some(ptr::read(self.ptr().add(len - 1)))
+            }
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, elem: T) {
+        let len = self.len();
+        assert!(index <= len, "index out bounds");
+        if len == self.capacity() { self.grow(); }
+
+        unsafe {
+            ptr::copy(self.ptr().add(index), self.ptr().add(index + 1), len - index);
+            ptr::write(self.ptr().add(index), elem);
+            self.set_len(len + 1);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(index < len, "index out of bounds");
+
+        unsafe {
+            self.set_len(len - 1);
+            let result = ptr::read(self.ptr().add(index));
+            ptr::copy(self.ptr().add(index + 1), self.ptr().add(index), len - index - 1);
+            result
+        }
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        let iter = unsafe { RawValIter::new(self) };
+        // 和 Vecx::drain 一样先把长度清零，Drain 被 forget 时只会泄漏元素
+        if !self.is_singleton() {
+            unsafe { self.set_len(0) };
+        }
+
+        Drain {
+            iter,
+            vec: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for ThinVecx<T> {
+    fn drop(&mut self) {
+        if self.is_singleton() {
+            return;
+        }
+        let len = self.len();
+        unsafe {
+            self.set_len(0);
+            // 元素的 Drop panic 时，守卫仍然会释放内存
+            let _dealloc = Dealloc::<T>(self.ptr, PhantomData);
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr(), len));
+        }
+    }
+}
+
+struct Dealloc<T>(NonNull<Header>, PhantomData<T>);
+
+impl<T> Drop for Dealloc<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let cap = self.0.as_ref().cap;
+            Global.deallocate(self.0.cast(), layout::<T>(cap).0);
+        }
+    }
+}
+
+impl<T> Deref for ThinVecx<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.ptr(), self.header().len) }
+    }
+}
+
+impl<T> DerefMut for ThinVecx<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr(), self.header().len) }
+    }
+}
+
+impl<T> Default for ThinVecx<T> {
+    fn default() -> Self {
+        ThinVecx::new()
+    }
+}
+
+impl<T: Clone> Clone for ThinVecx<T> {
+    fn clone(&self) -> Self {
+        let mut v = ThinVecx::with_capacity(self.len());
+        for elem in self.iter() {
+            v.push(elem.clone());
+        }
+        v
+    }
+}
+
+impl<T: PartialEq> PartialEq for ThinVecx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for ThinVecx<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for ThinVecx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for ThinVecx<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = ThinVecx::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T> Extend<T> for ThinVecx<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> IntoIterator for ThinVecx<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> IntoIter<T> {
+        let mut vec = ManuallyDrop::new(self);
+        let iter = unsafe { RawValIter::new(&vec) };
+        if !vec.is_singleton() {
+            unsafe { vec.set_len(0) };
+        }
+        IntoIter { _vec: ManuallyDrop::into_inner(vec), iter }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ThinVecx<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ThinVecx<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        // 剩下的元素在这里 drop，内存由 _vec 释放
+        for _ in &mut *self {}
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Deref;
+use std::process;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+
+use super::raw_vec::RawVec;
+use super::Vecx;
+use crate::allocator::Global;
+
+// 引用计数超过这个值就 abort，和 std 的 Arc 一样防止计数溢出
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+/// 放在分配里的头部
+struct Header {
+    count: AtomicUsize,
+    len: usize,
+    cap: usize,
+}
+
+/// 引用计数的共享切片，写时复制
+///
+/// 头部（引用计数、长度、容量）不单独分配，而是放在元素后面的空余容量里，
+/// 整块内存的布局和 `Vecx` 一样是 `[T; cap]`。所以 `Vecx` 的空余容量放得下头部时，
+/// `From<Vecx<T>>` 既不重新分配也不移动元素，`try_unwrap` 也能原样还回一个 `Vecx`。
+/// ZST 没有分配可以放头部，只能单独分配一个
+///
+/// 内存序和 `std::sync::Arc` 相同：clone 用 `Relaxed` 加一；drop 用 `Release` 减一，
+/// 减到 0 的线程再用 `Acquire` fence 看到其他线程对元素的所有访问，然后才释放
+pub struct ArcVecx<T> {
+    ptr: NonNull<T>,
+    header: NonNull<Header>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send + Sync> Send for ArcVecx<T> {}
+unsafe impl<T: Send + Sync> Sync for ArcVecx<T> {}
+
+// 在 ptr 开始的 `[T; cap]` 里，len 个元素之后放得下头部时返回头部的地址
+fn header_slot<T>(ptr: NonNull<T>, len: usize, cap: usize) -> Option<NonNull<Header>> {
+    let size = mem::size_of::<T>();
+    let base = ptr.as_ptr() as usize;
+    let offset = (base + len * size).next_multiple_of(mem::align_of::<Header>()) - base;
+    if offset + mem::size_of::<Header>() <= cap * size {
+        NonNull::new(unsafe { ptr.as_ptr().cast::<u8>().add(offset) }.cast::<Header>())
+    } else {
+        None
+    }
+}
+
+impl<T> ArcVecx<T> {
+    pub fn new() -> Self {
+        ArcVecx::from(Vecx::new())
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { self.header.as_ref() }
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().count.load(Ordering::Relaxed)
+    }
+
+    /// 两个句柄是否共享同一块存储
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.header == other.header
+    }
+
+    fn is_unique(&self) -> bool {
+        // Acquire 和其他句柄 drop 时的 Release 配对，之后可以安全地独占元素
+        self.header().count.load(Ordering::Acquire) == 1
+    }
+
+    /// 只有一个句柄时返回可变切片
+    pub fn get_mut(&mut self) -> Option<&mut [T]> {
+        if self.is_unique() {
+            Some(unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) })
+        } else {
+            None
+        }
+    }
+
+    /// 返回可变切片；存储被共享时先复制一份，之后只改自己这一份
+    pub fn make_mut(&mut self) -> &mut [T]
+    where
+        T: Clone,
+    {
+        if !self.is_unique() {
+            *self = ArcVecx::from(&**self);
+        }
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
+    }
+
+    /// 只有一个句柄时取回底层的 `Vecx`，不复制元素；否则原样返回
+    pub fn try_unwrap(this: Self) -> Result<Vecx<T>, Self> {
+        if this
+            .header()
+            .count
+            .compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        Ok(unsafe { this.take_vecx() })
+    }
+
+    /// 只有一个句柄时取回 `Vecx`，否则复制出一个
+    pub fn unwrap_or_clone(this: Self) -> Vecx<T>
+    where
+        T: Clone,
+    {
+        ArcVecx::try_unwrap(this).unwrap_or_else(|this| {
+            let mut v = Vecx::with_capacity(this.len());
+            for elem in this.iter() {
+                v.push(elem.clone());
+            }
+            v
+        })
+    }
+
+    // 计数已经归零：释放单独分配的头部（ZST），把存储还原成 Vecx
+    unsafe fn take_vecx(&self) -> Vecx<T> {
+        let Header { len, cap, .. } = ptr::read(self.header.as_ptr());
+        if mem::size_of::<T>() == 0 {
+            drop(Box::from_raw(self.header.as_ptr()));
+        }
+        Vecx { buf: RawVec { ptr: self.ptr, cap, alloc: Global }, len }
+    }
+}
+
+impl<T> From<Vecx<T>> for ArcVecx<T> {
+    fn from(mut v: Vecx<T>) -> Self {
+        let header = Header { count: AtomicUsize::new(1), len: v.len, cap: v.cap() };
+        if mem::size_of::<T>() == 0 {
+            let v = ManuallyDrop::new(v);
+            return ArcVecx {
+                ptr: v.buf.ptr,
+                header: NonNull::from(Box::leak(Box::new(header))),
+                _marker: PhantomData,
+            };
+        }
+
+        if header_slot(v.buf.ptr, v.len, v.cap()).is_none() {
+            // 空余容量放不下头部，换一块大一点的内存，把元素搬过去
+            let extra = (mem::size_of::<Header>() + mem::align_of::<Header>()).div_ceil(mem::size_of::<T>());
+            let mut bigger = Vecx::with_capacity(v.len + extra);
+            unsafe { ptr::copy_nonoverlapping(v.ptr(), bigger.ptr(), v.len) };
+            bigger.len = v.len;
+            v.len = 0;
+            v = bigger;
+        }
+        let slot = header_slot(v.buf.ptr, v.len, v.cap()).unwrap();
+        unsafe { slot.as_ptr().write(Header { cap: v.cap(), ..header }) };
+        let v = ManuallyDrop::new(v);
+        ArcVecx { ptr: v.buf.ptr, header: slot, _marker: PhantomData }
+    }
+}
+
+impl<T: Clone> From<&[T]> for ArcVecx<T> {
+    fn from(elems: &[T]) -> Self {
+        let mut v = Vecx::with_capacity(elems.len());
+        for elem in elems {
+            v.push(elem.clone());
+        }
+        ArcVecx::from(v)
+    }
+}
+
+impl<T> FromIterator<T> for ArcVecx<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Vecx::new();
+        for elem in iter {
+            v.push(elem);
+        }
+        ArcVecx::from(v)
+    }
+}
+
+impl<T> Clone for ArcVecx<T> {
+    fn clone(&self) -> Self {
+        if self.header().count.fetch_add(1, Ordering::Relaxed) > MAX_REFCOUNT {
+            process::abort();
+        }
+        ArcVecx { ptr: self.ptr, header: self.header, _marker: PhantomData }
+    }
+}
+
+impl<T> Drop for ArcVecx<T> {
+    fn drop(&mut self) {
+        if self.header().count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+        // 还原成 Vecx，由它 drop 元素并释放内存
+        drop(unsafe { self.take_vecx() });
+    }
+}
+
+impl<T> Deref for ArcVecx<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.header().len) }
+    }
+}
+
+impl<T> AsRef<[T]> for ArcVecx<T> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Default for ArcVecx<T> {
+    fn default() -> Self {
+        ArcVecx::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcVecx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for ArcVecx<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for ArcVecx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
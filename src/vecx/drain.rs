@@ -1,13 +1,12 @@
 use std::marker::PhantomData;
 
-use super::Vecx;
 use super::raw_val_iter::RawValIter;
 
 pub struct Drain<'a, T:'a> {
     // 这里需要限制生命周期，因此使用了 `&'a mut Vec<T>`
     // 也就是语义上包含的内容
     // 只会调用 `pop()` 和 `remove(0)` 两个方法
-    pub vec: PhantomData<&'a mut Vecx<T>>,
+    pub vec: PhantomData<&'a mut [T]>,
     pub iter: RawValIter<T>,
 }
 
@@ -3,6 +3,8 @@
 // use std::alloc::{self, Layout};
 use std::{ptr, mem};
 
+use crate::allocator::{Allocator, Global};
+
 use super::raw_val_iter::RawValIter;
 use super::raw_vec::RawVec;
 use super::Vecx;
@@ -20,16 +22,16 @@ use super::Vecx;
 //     end: *const T,
 // }
 
-pub struct IntoIterx<T> {
-    _buf: RawVec<T>,
+pub struct IntoIterx<T, A: Allocator = Global> {
+    _buf: RawVec<T, A>,
     iter: RawValIter<T>,
 }
 
 // next 和 next_back 保持不变，因为它们并没有用到 buf
 
-impl<T> IntoIterator for Vecx<T> {
+impl<T, A: Allocator> IntoIterator for Vecx<T, A> {
     type Item = T;
-    type IntoIter = IntoIterx<T>;
+    type IntoIter = IntoIterx<T, A>;
     fn into_iter(self) -> Self::IntoIter {
         // // 确保 Vecx 不会被 drop
         // // 将原来 Vecx 持有数据的内存释放工作交给 IntoIterx
@@ -85,7 +87,7 @@ impl<T> IntoIterator for Vecx<T> {
 }
 
 // 向前迭代
-impl<T> Iterator for IntoIterx<T> {
+impl<T, A: Allocator> Iterator for IntoIterx<T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         // if self.start == self.end {
@@ -111,7 +113,7 @@ impl<T> Iterator for IntoIterx<T> {
 }
 
 // 向后迭代
-impl<T> DoubleEndedIterator for IntoIterx<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIterx<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         // if self.start == self.end {
         //     None
@@ -126,7 +128,7 @@ impl<T> DoubleEndedIterator for IntoIterx<T> {
 }
 
 // 因为 IntoIterx 拥有其分配的所有权，需要实现 Drop 来释放它
-impl<T> Drop for IntoIterx<T> {
+impl<T, A: Allocator> Drop for IntoIterx<T, A> {
     fn drop(&mut self) {
         // if self.cap != 0 {
         //     // 将剩下的元素 drop
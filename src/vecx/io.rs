@@ -0,0 +1,417 @@
+//! 把 `Vecx<u8>` 当作 I/O 缓冲区用：写入、按游标读取、读到结尾，以及带缓冲的写入
+
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+#[cfg(read_buf)]
+use std::io::BorrowedBuf;
+use std::io::{self, BufRead, IoSlice, Read, Seek, SeekFrom, Write};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ptr;
+#[cfg(not(read_buf))]
+use std::slice;
+
+use crate::allocator::{Allocator, Global};
+
+use super::Vecx;
+
+// 写入总是追加到末尾，和 `Vec<u8>` 的行为一致，不会失败（分配失败直接 abort）
+impl<A: Allocator> Write for Vecx<u8, A> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend_from_slice(buf);
+        }
+        Ok(len)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<A: Allocator> fmt::Write for Vecx<u8, A> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// 从 `reader` 一直读到 EOF，追加到 `buf` 末尾，返回读到的字节数
+///
+/// 直接读进 `buf` 的空余容量，不经过中间缓冲区。遇到 `Interrupted` 会重试；
+/// 出错时已经读到的数据留在 `buf` 里
+///
+/// 用 `RUSTFLAGS="--cfg read_buf"` 在 nightly 上编译时，空余容量通过 `BorrowedBuf`
+/// 交给 `Read::read_buf`，完全不清零。stable 上 `Read::read` 只接受已初始化的切片
+/// （把未初始化的内存交给 `read` 是未定义行为），只能退而求其次：每个字节在第一次
+/// 交给 `reader` 之前清零一次，`reader` 没写到的部分下一轮直接复用，不会重复清零
+pub fn read_to_end_into<R, A>(reader: &mut R, buf: &mut Vecx<u8, A>) -> io::Result<usize>
+where
+    R: Read + ?Sized,
+    A: Allocator,
+{
+    let start = buf.len();
+    // 空余容量开头已经初始化过的字节数，扩容之后归零
+    let mut initialized = 0;
+    loop {
+        if buf.len() == buf.capacity() {
+            // 容量正好用完时先用栈上的小缓冲区探测一下，已经到 EOF 就不用白白扩容
+            let mut probe = [0u8; 32];
+            let n = loop {
+                match reader.read(&mut probe) {
+                    Ok(n) => break n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            };
+            if n == 0 {
+                return Ok(buf.len() - start);
+            }
+            buf.extend_from_slice(&probe[..n]);
+            buf.reserve(probe.len());
+            initialized = 0;
+            continue;
+        }
+
+        let spare = buf.spare_capacity_mut();
+        let spare_len = spare.len();
+        match read_spare(reader, spare, &mut initialized) {
+            Ok(0) => return Ok(buf.len() - start),
+            Ok(n) => {
+                assert!(n <= spare_len, "reader returned more bytes than the buffer holds");
+                unsafe { buf.set_len(buf.len() + n) };
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 读进空余容量，返回读到的字节数；`initialized` 是 `spare` 开头已经初始化的字节数，
+// 返回时更新为读到的数据之后还剩下的已初始化字节数
+//
+// `BorrowedBuf` 自己记录哪些字节初始化过，不需要 `initialized`
+#[cfg(read_buf)]
+fn read_spare<R>(reader: &mut R, spare: &mut [MaybeUninit<u8>], _initialized: &mut usize) -> io::Result<usize>
+where
+    R: Read + ?Sized,
+{
+    let mut borrowed = BorrowedBuf::from(spare);
+    let result = reader.read_buf(borrowed.unfilled());
+    let n = borrowed.len();
+    result.map(|()| n)
+}
+
+#[cfg(not(read_buf))]
+fn read_spare<R>(reader: &mut R, spare: &mut [MaybeUninit<u8>], initialized: &mut usize) -> io::Result<usize>
+where
+    R: Read + ?Sized,
+{
+    let len = spare.len();
+    let dst = unsafe {
+        let p = spare.as_mut_ptr().cast::<u8>();
+        ptr::write_bytes(p.add(*initialized), 0, len - *initialized);
+        slice::from_raw_parts_mut(p, len)
+    };
+    *initialized = len;
+    let n = reader.read(dst)?;
+    *initialized = len - n.min(len);
+    Ok(n)
+}
+
+/// 在 `Vecx<u8>` 上按游标读取，相当于 `io::Cursor<Vec<u8>>`
+///
+/// 位置可以 seek 到数据末尾之后，此时读到的都是 EOF
+pub struct VecxReader<A: Allocator = Global> {
+    inner: Vecx<u8, A>,
+    pos: u64,
+}
+
+impl<A: Allocator> VecxReader<A> {
+    pub fn new(inner: Vecx<u8, A>) -> Self {
+        VecxReader { inner, pos: 0 }
+    }
+
+    pub fn into_inner(self) -> Vecx<u8, A> {
+        self.inner
+    }
+
+    pub fn get_ref(&self) -> &Vecx<u8, A> {
+        &self.inner
+    }
+
+    /// 修改数据不会调整当前位置
+    pub fn get_mut(&mut self) -> &mut Vecx<u8, A> {
+        &mut self.inner
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// 当前位置之后还没读的数据
+    pub fn remaining_slice(&self) -> &[u8] {
+        let start = cmp::min(self.pos, self.inner.len() as u64) as usize;
+        &self.inner[start..]
+    }
+}
+
+impl<A: Allocator> Read for VecxReader<A> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = Read::read(&mut self.remaining_slice(), buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let rest = self.remaining_slice();
+        if rest.len() < buf.len() {
+            // 和 Cursor 一样，数据不够时把位置移到末尾
+            self.pos = self.inner.len() as u64;
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+        }
+        buf.copy_from_slice(&rest[..buf.len()]);
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<A: Allocator> BufRead for VecxReader<A> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining_slice())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<A: Allocator> Seek for VecxReader<A> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match style {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.inner.len() as u64, n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+impl<A: Allocator> fmt::Debug for VecxReader<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VecxReader")
+            .field("len", &self.inner.len())
+            .field("pos", &self.pos)
+            .finish()
+    }
+}
+
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// 用 `Vecx<u8>` 做缓冲区的 `io::BufWriter`
+///
+/// 小块写入先攒在缓冲区里，满了才一次性写给底层；比缓冲区还大的写入直接交给底层。
+/// drop 时会尝试写出剩余数据并忽略错误，需要知道结果时先调用 `flush` 或 `into_inner`
+pub struct VecxBufWriter<W: Write> {
+    inner: W,
+    buf: Vecx<u8>,
+    // 底层 write panic 时不知道写出去了多少，drop 时不能再写一遍
+    panicked: bool,
+}
+
+impl<W: Write> VecxBufWriter<W> {
+    /// 默认 8 KiB 缓冲区
+    pub fn new(inner: W) -> Self {
+        VecxBufWriter::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    pub fn with_capacity(cap: usize, inner: W) -> Self {
+        VecxBufWriter { inner, buf: Vecx::with_capacity(cap), panicked: false }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// 直接写底层会绕过缓冲区，顺序可能和预期不一致
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// 已缓冲、还没写给底层的数据
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// 写出缓冲区后取回底层 writer；写出失败时原样返回自己，缓冲的数据不会丢
+    pub fn into_inner(mut self) -> Result<W, IntoInnerError<W>> {
+        if let Err(error) = self.flush_buf() {
+            return Err(IntoInnerError { writer: self, error });
+        }
+        let me = ManuallyDrop::new(self);
+        unsafe {
+            drop(ptr::read(&me.buf));
+            Ok(ptr::read(&me.inner))
+        }
+    }
+
+    // 把缓冲区尽量写给底层，没写出去的部分挪到缓冲区开头
+    fn flush_buf(&mut self) -> io::Result<()> {
+        let mut written = 0;
+        let mut ret = Ok(());
+        while written < self.buf.len() {
+            self.panicked = true;
+            let r = self.inner.write(&self.buf[written..]);
+            self.panicked = false;
+            match r {
+                Ok(0) => {
+                    ret = Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write the buffered data"));
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    ret = Err(e);
+                    break;
+                }
+            }
+        }
+        if written > 0 {
+            let len = self.buf.len();
+            self.buf.copy_within(written.., 0);
+            unsafe { self.buf.set_len(len - written) };
+        }
+        ret
+    }
+}
+
+impl<W: Write> Write for VecxBufWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + data.len() > self.buf.capacity() {
+            self.flush_buf()?;
+        }
+        if data.len() >= self.buf.capacity() {
+            self.panicked = true;
+            let r = self.inner.write(data);
+            self.panicked = false;
+            r
+        } else {
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        }
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        // 能放进缓冲区时不逐次调用 write，省掉循环
+        if self.buf.len() + data.len() > self.buf.capacity() {
+            self.flush_buf()?;
+        }
+        if data.len() >= self.buf.capacity() {
+            self.panicked = true;
+            let r = self.inner.write_all(data);
+            self.panicked = false;
+            r
+        } else {
+            self.buf.extend_from_slice(data);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for VecxBufWriter<W> {
+    fn drop(&mut self) {
+        if !self.panicked {
+            let _ = self.flush_buf();
+        }
+    }
+}
+
+impl<W: Write + fmt::Debug> fmt::Debug for VecxBufWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VecxBufWriter")
+            .field("writer", &self.inner)
+            .field("buffer", &format_args!("{}/{}", self.buf.len(), self.buf.capacity()))
+            .finish()
+    }
+}
+
+/// `VecxBufWriter::into_inner` 写出缓冲区失败
+pub struct IntoInnerError<W: Write> {
+    writer: VecxBufWriter<W>,
+    error: io::Error,
+}
+
+impl<W: Write> IntoInnerError<W> {
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// 取回原来的 writer，缓冲的数据还在里面
+    pub fn into_inner(self) -> VecxBufWriter<W> {
+        self.writer
+    }
+
+    pub fn into_parts(self) -> (io::Error, VecxBufWriter<W>) {
+        (self.error, self.writer)
+    }
+}
+
+// 和 std 的 IntoInnerError 一样，Debug 不要求 W: Debug
+impl<W: Write> fmt::Debug for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoInnerError").field(&self.error).finish()
+    }
+}
+
+impl<W: Write> fmt::Display for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<W: Write> Error for IntoInnerError<W> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
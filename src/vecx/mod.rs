@@ -2,6 +2,11 @@ pub mod into_iter;
 pub mod raw_vec;
 pub mod drain;
 pub mod raw_val_iter;
+pub mod sort;
+pub mod par;
+pub mod arc;
+pub mod raw_parts;
+pub mod io;
 
 use std::marker::PhantomData;
 // use std::ptr::NonNull;  // 保证指针非空，在 T 上是协变的
@@ -10,19 +15,36 @@ use std::marker::PhantomData;
 use std::ptr;
 use std::ops::{Drop, Deref, DerefMut};
 
+use crate::allocator::{Allocator, Global};
 use raw_val_iter::RawValIter;
 use raw_vec::RawVec;
 use drain::Drain;
 
-pub struct Vecx<T> {
+pub struct Vecx<T, A: Allocator = Global> {
     // ptr: NonNull<T>,    // 指向堆内存的指针
     // cap: usize,         // 分配的容量（capacity）
     // len: usize,         // 已初始化的元素个数（length）
-    buf: RawVec<T>,
+    buf: RawVec<T, A>,
     len: usize,
 }
 
 impl<T> Vecx<T> {
+    pub fn new() -> Self {
+        Vecx {
+            buf: RawVec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Vecx {
+            buf: RawVec::with_capacity(cap),
+            len: 0,
+        }
+    }
+}
+
+impl<T, A: Allocator> Vecx<T, A> {
     fn ptr(&self) -> *mut T {
         self.buf.ptr.as_ptr()
     }
@@ -31,13 +53,51 @@ impl<T> Vecx<T> {
         self.buf.cap
     }
 
-    pub fn new() -> Self {
+    // 同时借出元素和分配器，排序等需要从同一个分配器申请临时空间的方法用
+    fn slice_and_alloc(&mut self) -> (&mut [T], &A) {
+        let slice = unsafe { std::slice::from_raw_parts_mut(self.ptr(), self.len) };
+        (slice, &self.buf.alloc)
+    }
+
+    /// 使用指定的分配器，例如 `Vecx::new_in(&bump)`
+    pub fn new_in(alloc: A) -> Self {
         Vecx {
-            buf: RawVec::new(),
+            buf: RawVec::new_in(alloc),
             len: 0,
         }
     }
 
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Vecx {
+            buf: RawVec::with_capacity_in(cap, alloc),
+            len: 0,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.buf.alloc
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap()
+    }
+
+    /// 预留至少 `additional` 个元素的空间，之后的这么多次 push 不会再重新分配
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(self.len, additional);
+    }
+
+    /// 把 `other` 的元素依次 clone 到末尾，最多扩容一次
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(other.len());
+        for elem in other {
+            self.push(elem.clone());
+        }
+    }
+
     pub fn push(&mut self, elem: T) {
         if self.len == self.cap() { self.buf.grow(); }
 
@@ -91,8 +151,8 @@ impl<T> Vecx<T> {
         }
     }
 
-    pub fn drain(&mut self) -> Drain<T> {
-        let iter = unsafe { RawValIter::new(&self) };
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        let iter = unsafe { RawValIter::new(self) };
         // 这里事关 mem::forget 的安全
         // 如果 Drain 被 forget,会导致整个 Vecx 的内存泄漏，
         // 因此在这里完成
@@ -105,6 +165,12 @@ impl<T> Vecx<T> {
     }
 }
 
+impl<T> Default for Vecx<T> {
+    fn default() -> Self {
+        Vecx::new()
+    }
+}
+
 // // NonNull<T> 本身不会自动传递 Send / Sync 语义
 // unsafe impl<T: Send> Send for Vecx<T> {}
 // unsafe impl<T: Sync> Sync for Vecx<T> {}
@@ -235,9 +301,15 @@ impl<T> Vecx<T> {
 //     }
 // }
 
-impl<T> Drop for Vecx<T> {
+impl<T, A: Allocator> Drop for Vecx<T, A> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        // 一次性 drop 所有元素：某个元素的 Drop panic 时，drop_in_place 会继续 drop 剩下的元素，
+        // 不会把后面的元素泄漏掉
+        let len = self.len;
+        self.len = 0;
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr(), len));
+        }
         // 剩余清理工作由 RawVec 自动完成
     }
 }
@@ -245,7 +317,7 @@ impl<T> Drop for Vecx<T> {
 
 /// 实现了 Deref 和 DerefMut 这两个 trait 就可以有 len、first、last、索引、切片、排序、iter、iter_mut 以及
 /// slice 提供的其他各种功能
-impl<T> Deref for Vecx<T> {
+impl<T, A: Allocator> Deref for Vecx<T, A> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
         unsafe {
@@ -255,7 +327,7 @@ impl<T> Deref for Vecx<T> {
     }
 }
 
-impl<T> DerefMut for Vecx<T> {
+impl<T, A: Allocator> DerefMut for Vecx<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe {
             // std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len)
@@ -0,0 +1,318 @@
+//! 基于 `std::thread::scope` 的并行操作，不依赖第三方线程池
+//!
+//! 数据按线程数切成连续的块，每块交给一个作用域线程处理，作用域结束前所有线程都会被 join，
+//! 所以可以直接借用调用者的切片。任何一个线程 panic，都会在其余线程结束后
+//! 用原来的 payload 在调用线程上重新 panic
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::mem;
+use std::panic;
+use std::ptr;
+use std::thread::{self, ScopedJoinHandle};
+
+use crate::allocator::Allocator;
+use crate::slice::chunks_mut;
+
+use super::raw_vec::RawVec;
+use super::sort::{merge, merge_sort_with};
+use super::Vecx;
+
+/// 并行配置，目前只有线程数
+#[derive(Debug, Clone, Copy)]
+pub struct Par {
+    threads: usize,
+}
+
+// 裸指针不是 Send；各个线程只会访问自己那一段，互不重叠
+struct SendPtr<T>(*mut T);
+
+// derive 会要求 T: Copy，这里手写
+impl<T> Clone for SendPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SendPtr<T> {}
+
+unsafe impl<T: Send> Send for SendPtr<T> {}
+
+impl<T> SendPtr<T> {
+    // 通过方法取值，闭包捕获的是整个 SendPtr 而不是里面的裸指针字段
+    fn get(self) -> *mut T {
+        self.0
+    }
+}
+
+/// `map` 中某个线程已经写好的前缀，panic 时由它负责 drop
+struct Written<U> {
+    ptr: *mut U,
+    len: usize,
+}
+
+impl<U> Drop for Written<U> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr, self.len));
+        }
+    }
+}
+
+impl Par {
+    /// 线程数取 `available_parallelism`，获取失败时为 1
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Par { threads }
+    }
+
+    pub fn with_threads(threads: usize) -> Self {
+        assert!(threads != 0, "thread count must be non-zero");
+        Par { threads }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // 每块的长度，至少为 1
+    fn chunk_len(&self, len: usize) -> usize {
+        len.div_ceil(self.threads).max(1)
+    }
+
+    pub fn for_each_mut<T, F>(&self, v: &mut [T], f: F)
+    where
+        T: Send,
+        F: Fn(&mut T) + Sync,
+    {
+        let chunk = self.chunk_len(v.len());
+        let f = &f;
+        thread::scope(|s| {
+            let handles: Vec<_> = chunks_mut(v, chunk)
+                .map(|part| s.spawn(move || part.iter_mut().for_each(f)))
+                .collect();
+            join_all(handles);
+        });
+    }
+
+    /// 对每个元素调用 `f`，结果按原顺序放进新的 Vecx
+    ///
+    /// 各线程直接把结果写进新 Vecx 的预留空间，不经过中间缓冲
+    pub fn map<T, U, F>(&self, v: &[T], f: F) -> Vecx<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        let len = v.len();
+        let chunk = self.chunk_len(len);
+        let mut out: Vecx<U> = Vecx::with_capacity(len);
+        let dst = SendPtr(out.ptr());
+        let f = &f;
+
+        let results: Vec<thread::Result<()>> = thread::scope(|s| {
+            let handles: Vec<_> = v
+                .chunks(chunk)
+                .enumerate()
+                .map(|(i, src)| {
+                    s.spawn(move || {
+                        let ptr = unsafe { dst.get().add(i * chunk) };
+                        let mut written = Written { ptr, len: 0 };
+                        for x in src {
+                            unsafe { ptr.add(written.len).write(f(x)) };
+                            written.len += 1;
+                        }
+                        // 整块写完，所有权交给 out
+                        mem::forget(written);
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        });
+
+        // 有线程 panic 时，panic 的那块已经被 Written 清理，这里再 drop 掉其他完整写好的块，
+        // out 的 len 仍为 0，只释放内存
+        if results.iter().any(|r| r.is_err()) {
+            let mut payload = None;
+            for (i, result) in results.into_iter().enumerate() {
+                match result {
+                    Ok(()) => unsafe {
+                        let start = i * chunk;
+                        let part = chunk.min(len - start);
+                        ptr::drop_in_place(ptr::slice_from_raw_parts_mut(dst.get().add(start), part));
+                    },
+                    Err(p) => {
+                        payload.get_or_insert(p);
+                    }
+                }
+            }
+            panic::resume_unwind(payload.unwrap());
+        }
+
+        out.len = len;
+        out
+    }
+
+    /// 用 `op` 归约所有元素；`op` 必须满足结合律，`identity` 必须是 `op` 的单位元
+    ///
+    /// 每块先从 `identity` 开始各自归约，再按块的顺序把结果合并，所以 `op` 不需要满足交换律
+    pub fn reduce<T, F>(&self, v: &[T], identity: T, op: F) -> T
+    where
+        T: Clone + Send + Sync,
+        F: Fn(T, T) -> T + Sync,
+    {
+        let chunk = self.chunk_len(v.len());
+        let op = &op;
+        let identity = &identity;
+        let parts = thread::scope(|s| {
+            let handles: Vec<_> = v
+                .chunks(chunk)
+                .map(|part| s.spawn(move || part.iter().cloned().fold(identity.clone(), op)))
+                .collect();
+            join_all(handles)
+        });
+        parts.into_iter().fold(identity.clone(), op)
+    }
+
+    /// 并行的稳定归并排序
+    pub fn sort<T>(&self, v: &mut [T])
+    where
+        T: Ord + Send,
+    {
+        self.sort_by(v, T::cmp);
+    }
+
+    pub fn sort_by<T, F>(&self, v: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let scratch = RawVec::<T>::with_capacity(v.len());
+        unsafe { self.sort_by_with(v, compare, scratch.ptr.as_ptr()) };
+    }
+
+    /// `sort_by` 的实现，所有线程都只用 `scratch`，自身不分配内存
+    ///
+    /// # Safety
+    ///
+    /// `scratch` 至少能容纳 `v.len()` 个元素，且不能和 `v` 重叠（ZST 时不会被使用）
+    unsafe fn sort_by_with<T, F>(&self, v: &mut [T], compare: F, scratch: *mut T)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let len = v.len();
+        let chunk = self.chunk_len(len);
+        let is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+        let is_less = &is_less;
+        // 每块、每一对都使用 scratch 中和自己相同偏移的区域，不同线程的 scratch 互不重叠
+        let buf = SendPtr(scratch);
+
+        // 1. 每块各自排序
+        thread::scope(|s| {
+            let handles: Vec<_> = chunks_mut(v, chunk)
+                .enumerate()
+                .map(|(i, part)| {
+                    s.spawn(move || unsafe {
+                        let mut is_less = is_less;
+                        merge_sort_with(part, &mut is_less, buf.get().add(i * chunk));
+                    })
+                })
+                .collect();
+            join_all(handles);
+        });
+        if chunk >= len {
+            return;
+        }
+
+        // 2. 相邻的有序段两两归并，每轮段长翻倍；merge 自身在 panic 时也会把元素放回切片
+        let mut width = chunk;
+        while width < len {
+            let pair = 2 * width;
+            thread::scope(|s| {
+                let handles: Vec<_> = chunks_mut(v, pair)
+                    .enumerate()
+                    .filter(|(_, part)| part.len() > width)
+                    .map(|(i, part)| {
+                        s.spawn(move || unsafe {
+                            let mut is_less = is_less;
+                            merge(part, width, buf.get().add(i * pair), &mut is_less);
+                        })
+                    })
+                    .collect();
+                join_all(handles);
+            });
+            width = pair;
+        }
+    }
+}
+
+impl Default for Par {
+    fn default() -> Self {
+        Par::new()
+    }
+}
+
+// 等所有线程结束；有线程 panic 时用第一个 payload 重新 panic
+fn join_all<R>(handles: Vec<ScopedJoinHandle<'_, R>>) -> Vec<R> {
+    let mut results = Vec::with_capacity(handles.len());
+    let mut payload: Option<Box<dyn Any + Send>> = None;
+    for handle in handles {
+        match handle.join() {
+            Ok(r) => results.push(r),
+            Err(p) => {
+                payload.get_or_insert(p);
+            }
+        }
+    }
+    if let Some(p) = payload {
+        panic::resume_unwind(p);
+    }
+    results
+}
+
+/// 使用默认线程数（`Par::new()`）的便捷方法，需要指定线程数时直接用 `Par`
+impl<T, A: Allocator> Vecx<T, A> {
+    pub fn par_for_each_mut<F>(&mut self, f: F)
+    where
+        T: Send,
+        F: Fn(&mut T) + Sync,
+    {
+        Par::new().for_each_mut(self, f);
+    }
+
+    pub fn par_map<U, F>(&self, f: F) -> Vecx<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        Par::new().map(self, f)
+    }
+
+    pub fn par_reduce<F>(&self, identity: T, op: F) -> T
+    where
+        T: Clone + Send + Sync,
+        F: Fn(T, T) -> T + Sync,
+    {
+        Par::new().reduce(self, identity, op)
+    }
+
+    pub fn par_sort(&mut self)
+    where
+        T: Ord + Send,
+    {
+        self.par_sort_by(T::cmp);
+    }
+
+    /// scratch 在调用线程上从 Vecx 自己的分配器申请，工作线程不分配内存
+    pub fn par_sort_by<F>(&mut self, compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let (v, alloc) = self.slice_and_alloc();
+        let scratch = RawVec::<T, &A>::with_capacity_in(v.len(), alloc);
+        unsafe { Par::new().sort_by_with(v, compare, scratch.ptr.as_ptr()) };
+    }
+}
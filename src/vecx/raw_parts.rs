@@ -0,0 +1,149 @@
+//! 直接操作 Vecx 底层缓冲区的接口，用于和 C 缓冲区、手写解码器之类的代码对接
+//!
+//! 这些接口的安全约定都来自 `RawVec` 的分配方式：
+//! - 容量为 `cap` 的缓冲区是用分配器 `A` 按 `Layout::array::<T>(cap)` 申请的
+//! - `cap == 0` 时不分配，指针是 `NonNull::dangling()`
+//! - ZST 从不分配，指针是 `NonNull::dangling()`，容量固定为 `usize::MAX`
+//! - 前 `len` 个元素已初始化，`[len, cap)` 是未初始化的空余容量
+//!
+//! 打开 `telemetry` 时，真正分配过的缓冲区经这些接口进出 Vecx 会记为 `adopted` / `released`
+
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+use crate::allocator::{Allocator, Global};
+
+use super::raw_vec::RawVec;
+use super::Vecx;
+
+impl<T> Vecx<T> {
+    /// 用指针、长度和容量重新组装一个 `Vecx`，通常和 `into_raw_parts` 配对使用
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` 必须是全局分配器按 `Layout::array::<T>(cap)` 分配的（例如来自另一个 `Vecx<T>`），
+    ///   或者在 `cap == 0`、`T` 是 ZST 时是非空且对齐的悬垂指针
+    /// - 容量必须和分配时的 `cap` 完全一致，释放时会用它重新算出 `Layout`；ZST 的 `cap` 会被忽略
+    /// - `len <= cap`，并且前 `len` 个元素都已初始化
+    /// - 这块内存的所有权转交给返回的 `Vecx`，调用者之后不能再使用或释放它
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize, cap: usize) -> Self {
+        Vecx::from_raw_parts_in(ptr, len, cap, Global)
+    }
+
+    /// 拆成 `(指针, 长度, 容量)`，不释放内存也不 drop 元素，之后由调用者负责
+    ///
+    /// 通常用 `from_raw_parts` 还原后再交给 `Vecx` 释放；ZST 返回的容量是 `usize::MAX`
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let (ptr, len, cap, _) = self.into_raw_parts_with_alloc();
+        (ptr, len, cap)
+    }
+
+    /// 把元素移进一个 `Box<[T]>`，空余容量会先被释放（`realloc` 到正好 `len` 个元素）
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        let mut me = ManuallyDrop::new(self);
+        let len = me.len;
+        me.buf.shrink_to(len);
+        #[cfg(feature = "telemetry")]
+        if me.owns_allocation() {
+            crate::telemetry::record_release::<T>();
+        }
+        // 此时分配正好是 `Layout::array::<T>(len)`，和 `Box<[T]>` 要求的布局一致
+        unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(me.ptr(), len)) }
+    }
+}
+
+impl<T, A: Allocator> Vecx<T, A> {
+    /// 使用指定分配器的 `from_raw_parts`
+    ///
+    /// # Safety
+    ///
+    /// 同 `from_raw_parts`，只是 `ptr` 必须由 `alloc` 分配
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, len: usize, cap: usize, alloc: A) -> Self {
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { cap };
+        debug_assert!(len <= cap, "len must not exceed capacity");
+        let v = Vecx {
+            buf: RawVec { ptr: NonNull::new_unchecked(ptr), cap, alloc },
+            len,
+        };
+        #[cfg(feature = "telemetry")]
+        if v.owns_allocation() {
+            crate::telemetry::record_adopt::<T>();
+        }
+        v
+    }
+
+    /// 拆成 `(指针, 长度, 容量, 分配器)`
+    pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, A) {
+        let me = ManuallyDrop::new(self);
+        #[cfg(feature = "telemetry")]
+        if me.owns_allocation() {
+            crate::telemetry::record_release::<T>();
+        }
+        let alloc = unsafe { ptr::read(&me.buf.alloc) };
+        (me.ptr(), me.len, me.cap(), alloc)
+    }
+
+    // 和 RawVec 的 drop 条件一致：只有这种缓冲区真正占着内存
+    #[cfg(feature = "telemetry")]
+    fn owns_allocation(&self) -> bool {
+        self.cap() != 0 && mem::size_of::<T>() != 0
+    }
+
+    /// 缓冲区的起始指针，没有分配时是悬垂指针
+    ///
+    /// 在下一次可能重新分配的操作（`push`、`insert` 等）之前一直有效；
+    /// 只能用来读，写入请用 `as_mut_ptr`
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr()
+    }
+
+    /// 可写的缓冲区起始指针，有效期同 `as_ptr`
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr()
+    }
+
+    /// 直接设置长度
+    ///
+    /// # Safety
+    ///
+    /// `new_len <= capacity()`，并且 `[old_len, new_len)` 的元素已经初始化（变长时），
+    /// 或者 `[new_len, old_len)` 的元素已经被移走、不需要再 drop（变短时）
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.cap(), "len must not exceed capacity");
+        self.len = new_len;
+    }
+
+    /// 未初始化的空余容量 `[len, cap)`
+    ///
+    /// 往里写完之后用 `set_len` 把写好的元素计入长度
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.ptr().add(self.len).cast::<MaybeUninit<T>>(),
+                self.cap() - self.len,
+            )
+        }
+    }
+
+    /// 只在不需要扩容时 push，容量已满时把元素原样返回
+    pub fn push_within_capacity(&mut self, elem: T) -> Result<(), T> {
+        if self.len == self.cap() {
+            return Err(elem);
+        }
+        unsafe { ptr::write(self.ptr().add(self.len), elem) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// 放弃所有权，返回一个可变切片（`Global` 时是 `&'static mut [T]`）；内存和元素都不会被释放
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where
+        A: 'a,
+    {
+        let (ptr, len, _, alloc) = self.into_raw_parts_with_alloc();
+        // 分配器随之 forget，不运行它的 Drop
+        mem::forget(alloc);
+        unsafe { slice::from_raw_parts_mut(ptr, len) }
+    }
+}
@@ -12,12 +12,17 @@ impl<T> RawValIter<T> {
     // 构建 RawVaIter 是不安全的，因为它没有关联的生命周期，
     // 将 RawValIter 存储在与它实际分配相同的结构体中是非常有必要的，
     // 但这里是具体的实现细节，不用对外公开
+    ///
+    /// # Safety
+    ///
+    /// 返回的迭代器不能比 `slice` 背后的内存活得更久，并且读出的元素所有权归调用者，
+    /// 原来的容器不能再次 drop 它们
     pub unsafe fn new(slice: &[T]) -> Self {
         RawValIter {
             start: slice.as_ptr(),
             end: if mem::size_of::<T>() == 0 {
                 ((slice.as_ptr() as usize) + slice.len()) as *const T
-            } else if slice.len() == 0 {
+            } else if slice.is_empty() {
                 slice.as_ptr()
             } else {
                 slice.as_ptr().add(slice.len())
@@ -1,18 +1,59 @@
-use std::isize;
 use std::ptr::NonNull;
 use std::alloc::{self, Layout};
+use std::cmp;
 use std::mem::{self};
 
-pub struct RawVec<T> {
+use crate::allocator::{Allocator, Global};
+
+pub struct RawVec<T, A: Allocator = Global> {
     pub ptr: NonNull<T>,
     pub cap: usize,
+    pub alloc: A,
 }
 
-unsafe impl<T:Send> Send for RawVec<T> {}
-unsafe impl<T:Sync> Sync for RawVec<T> {}
+unsafe impl<T: Send, A: Allocator + Send> Send for RawVec<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for RawVec<T, A> {}
 
 impl<T> RawVec<T> {
     pub fn new() -> Self {
+        RawVec::new_in(Global)
+    }
+
+    /// 一次性分配 `cap` 个元素的空间，ZST 和 `cap == 0` 时不分配
+    pub fn with_capacity(cap: usize) -> Self {
+        RawVec::with_capacity_in(cap, Global)
+    }
+
+    /// 把分配缩小到正好 `cap` 个元素，`cap == 0` 时释放内存；ZST 和 `cap >= self.cap` 时什么都不做
+    ///
+    /// `Allocator` 没有 shrink 接口，这里只对 `Global` 提供，直接用 `realloc`
+    pub fn shrink_to(&mut self, cap: usize) {
+        if mem::size_of::<T>() == 0 || cap >= self.cap {
+            return;
+        }
+        let old_layout = Layout::array::<T>(self.cap).unwrap();
+        if cap == 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), old_layout) };
+            #[cfg(feature = "telemetry")]
+            crate::telemetry::record_dealloc::<T>();
+            self.ptr = NonNull::dangling();
+            self.cap = 0;
+            return;
+        }
+        let new_layout = Layout::array::<T>(cap).unwrap();
+        let new_ptr = unsafe { alloc::realloc(self.ptr.as_ptr().cast(), old_layout, new_layout.size()) };
+        self.ptr = match NonNull::new(new_ptr.cast()) {
+            Some(p) => p,
+            None => alloc::handle_alloc_error(new_layout),
+        };
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::record_shrink::<T>();
+        self.cap = cap;
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         // assert!(mem::size_of::<T>() != 0, "TODO: implement ZST support");
         let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
 
@@ -21,43 +62,33 @@ impl<T> RawVec<T> {
         RawVec {
             ptr: NonNull::dangling(),
             cap,
+            alloc,
         }
     }
 
-    pub fn grow(&mut self) {
-        // 当 T 的 size 为 0 时，设置cap 为 usize::MAX
-        assert!(mem::size_of::<T>() != 0, "capacity overflow");
-
-        let (new_cap, new_layout) = if self.cap == 0 {
-            (1, Layout::array::<T>(1).unwrap())
-        } else {
-            // 保证新申请的内存没有超过 `isize::MAX` 字节
-            let new_cap = 2 * self.cap;
-
-            // `Layout::array` 会检查申请的空间 是否小于等于 usize::MAX，
-            // 但是因为 old_layout.size() <= isize::MAX，
-            // 所以这里的 unwrap 永远不会失败
-            let new_layout = Layout::array::<T>(new_cap).unwrap();
-            (new_cap, new_layout)
-        };
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        if mem::size_of::<T>() == 0 || cap == 0 {
+            return RawVec::new_in(alloc);
+        }
 
-        // 保证新申请的内存没有超过 `isize::MAX` 字节
-        assert!(new_layout.size() <= isize::MAX as usize, "Allocation too large");
+        let layout = Layout::array::<T>(cap).expect("capacity overflow");
+        assert!(layout.size() <= isize::MAX as usize, "Allocation too large");
 
-        let new_ptr = if self.cap == 0 {
-            unsafe { alloc::alloc(new_layout) }
-        } else {
-            let old_layout = Layout::array::<T>(self.cap).unwrap();
-            let old_ptr = self.ptr.as_ptr() as *mut u8;
-            unsafe { alloc::realloc(old_ptr, old_layout, new_layout.size()) }
+        let ptr = match alloc.allocate(layout) {
+            Ok(p) => p.cast(),
+            Err(_) => alloc::handle_alloc_error(layout),
         };
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::record_alloc::<T>(cap, layout.size());
+        RawVec { ptr, cap, alloc }
+    }
 
-        // 如果分配失败，`new_ptr` 就会成为空指针，需要处理这个意外情况
-        self.ptr = match NonNull::new(new_ptr as *mut T) {
-            Some(p) => p,
-            None => alloc::handle_alloc_error(new_layout),
-        };
-        self.cap = new_cap;
+    pub fn grow(&mut self) {
+        // 当 T 的 size 为 0 时，设置cap 为 usize::MAX
+        assert!(mem::size_of::<T>() != 0, "capacity overflow");
+
+        let new_cap = if self.cap == 0 { 1 } else { 2 * self.cap };
+        self.grow_to(new_cap);
 
         // // 保证新申请的内存没有超过 `isize::MAX` 字节
         // let new_cap = if self.cap == 0 { 1 } else { 2 * self.cap };
@@ -86,16 +117,64 @@ impl<T> RawVec<T> {
 
         // self.cap = new_cap;
     }
+
+    /// 保证在 `len` 个元素之后至少还能放下 `additional` 个，
+    /// 容量不够时扩到“翻倍”和“刚好够用”中较大的那个，保持均摊 O(1)
+    pub fn reserve(&mut self, len: usize, additional: usize) {
+        let required = len.checked_add(additional).expect("capacity overflow");
+        if required <= self.cap {
+            return;
+        }
+        // ZST 的容量是 usize::MAX，走到这里说明溢出了
+        assert!(mem::size_of::<T>() != 0, "capacity overflow");
+        self.grow_to(cmp::max(2 * self.cap, required));
+    }
+
+    fn grow_to(&mut self, new_cap: usize) {
+        // `Layout::array` 会检查申请的空间是否小于等于 usize::MAX
+        let new_layout = Layout::array::<T>(new_cap).expect("capacity overflow");
+
+        // 保证新申请的内存没有超过 `isize::MAX` 字节
+        assert!(new_layout.size() <= isize::MAX as usize, "Allocation too large");
+
+        let new_ptr = if self.cap == 0 {
+            self.alloc.allocate(new_layout)
+        } else {
+            let old_layout = Layout::array::<T>(self.cap).unwrap();
+            unsafe { self.alloc.grow(self.ptr.cast(), old_layout, new_layout) }
+        };
+
+        // 如果分配失败，需要处理这个意外情况
+        self.ptr = match new_ptr {
+            Ok(p) => p.cast(),
+            Err(_) => alloc::handle_alloc_error(new_layout),
+        };
+        #[cfg(feature = "telemetry")]
+        if self.cap == 0 {
+            crate::telemetry::record_alloc::<T>(new_cap, new_layout.size());
+        } else {
+            crate::telemetry::record_realloc::<T>(new_cap, new_layout.size());
+        }
+        self.cap = new_cap;
+    }
+}
+
+impl<T> Default for RawVec<T> {
+    fn default() -> Self {
+        RawVec::new()
+    }
 }
 
-impl<T> Drop for RawVec<T> {
+impl<T, A: Allocator> Drop for RawVec<T, A> {
     fn drop(&mut self) {
         let elem_size = mem::size_of::<T>();
 
         if self.cap != 0 && elem_size != 0 {
+            #[cfg(feature = "telemetry")]
+            crate::telemetry::record_dealloc::<T>();
             unsafe {
-                alloc::dealloc(
-                    self.ptr.as_ptr() as *mut u8,
+                self.alloc.deallocate(
+                    self.ptr.cast(),
                     Layout::array::<T>(self.cap).unwrap(),
                 );
             }
@@ -0,0 +1,608 @@
+//! Vecx 自己的排序实现
+//!
+//! - 稳定排序：自适应归并排序（TimSort 式的 run 检测），scratch 用 `RawVec` 从 Vecx 自己的分配器申请
+//! - 不稳定排序：pattern-defeating quicksort（pdqsort），原地进行，不需要额外内存
+//!
+//! 用户的比较函数随时可能 panic，所有会暂时把元素“挖走”的地方都用 `esafe` 里的守卫兜底，
+//! panic 之后切片里的元素仍然是原来那些，只是顺序未定
+
+use std::cmp::{self, Ordering};
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+
+use crate::allocator::{Allocator, Global};
+use crate::esafe::{InsertionHole, MergeHole};
+
+use super::raw_vec::RawVec;
+use super::Vecx;
+
+impl<T, A: Allocator> Vecx<T, A> {
+    /// 稳定排序，相等的元素保持原来的先后顺序
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        let (v, alloc) = self.slice_and_alloc();
+        merge_sort_in(v, |a, b| a.lt(b), alloc);
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (v, alloc) = self.slice_and_alloc();
+        merge_sort_in(v, |a, b| compare(a, b) == Ordering::Less, alloc);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        let (v, alloc) = self.slice_and_alloc();
+        merge_sort_in(v, |a, b| f(a).lt(&f(b)), alloc);
+    }
+
+    /// 稳定排序，每个元素的 key 只计算一次，适合 key 计算代价高的情况
+    pub fn sort_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        let len = self.len;
+        if len < 2 { return; }
+
+        // (key, 原下标)：下标互不相同，所以这里用不稳定排序也能得到稳定的结果
+        let (v, alloc) = self.slice_and_alloc();
+        let mut indices: Vecx<(K, usize), &A> = Vecx::with_capacity_in(len, alloc);
+        for (i, elem) in v.iter().enumerate() {
+            indices.push((f(elem), i));
+        }
+        indices.sort_unstable();
+
+        // 依次把第 i 小的元素换到位置 i；之前的交换可能已经把它挪走了，
+        // 顺着 indices 记录的去向一直找到它现在的位置
+        for i in 0..len {
+            let mut index = indices[i].1;
+            while index < i {
+                index = indices[index].1;
+            }
+            indices[i].1 = index;
+            v.swap(i, index);
+        }
+    }
+
+    /// 不稳定排序，原地进行，不分配内存
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        quicksort(self, |a, b| a.lt(b));
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        quicksort(self, |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        quicksort(self, |a, b| f(a).lt(&f(b)));
+    }
+}
+
+/// 把 `v[v.len() - 1]` 插入到已经有序的 `v[..v.len() - 1]` 中
+fn insert_tail<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    if len < 2 { return; }
+
+    unsafe {
+        let arr = v.as_mut_ptr();
+        let mut i = len - 1;
+        if !is_less(&*arr.add(i), &*arr.add(i - 1)) { return; }
+
+        // 把最后一个元素取出来，留下一个洞；之后每次比较都可能 panic，
+        // 由 hole 负责把它写回当前洞的位置
+        let tmp = ManuallyDrop::new(ptr::read(arr.add(i)));
+        let mut hole = InsertionHole { src: &*tmp, dest: arr.add(i - 1) };
+        ptr::copy_nonoverlapping(arr.add(i - 1), arr.add(i), 1);
+        i -= 1;
+
+        while i > 0 && is_less(&*tmp, &*arr.add(i - 1)) {
+            ptr::copy_nonoverlapping(arr.add(i - 1), arr.add(i), 1);
+            hole.dest = arr.add(i - 1);
+            i -= 1;
+        }
+        // hole 在这里 drop，把 tmp 写进最终位置
+    }
+}
+
+/// 把 `v[0]` 插入到已经有序的 `v[1..]` 中
+fn insert_head<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    if len < 2 { return; }
+
+    unsafe {
+        let arr = v.as_mut_ptr();
+        if !is_less(&*arr.add(1), &*arr) { return; }
+
+        let tmp = ManuallyDrop::new(ptr::read(arr));
+        let mut hole = InsertionHole { src: &*tmp, dest: arr.add(1) };
+        ptr::copy_nonoverlapping(arr.add(1), arr, 1);
+
+        for i in 2..len {
+            if !is_less(&*arr.add(i), &*tmp) { break; }
+            ptr::copy_nonoverlapping(arr.add(i), arr.add(i - 1), 1);
+            hole.dest = arr.add(i);
+        }
+    }
+}
+
+fn insertion_sort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in 2..=v.len() {
+        insert_tail(&mut v[..i], is_less);
+    }
+}
+
+/// 稳定归并 `v[..mid]` 和 `v[mid..]` 这两段有序区间
+///
+/// # Safety
+///
+/// `buf` 至少能容纳 `min(mid, v.len() - mid)` 个元素，且不能和 `v` 重叠（ZST 时不会被使用）
+pub(crate) unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // ZST 没有顺序可言，而且 MergeHole 没法用 offset_from 算出 ZST 指针之间的长度
+    if mem::size_of::<T>() == 0 { return; }
+
+    let len = v.len();
+    let v = v.as_mut_ptr();
+    let v_mid = v.add(mid);
+    let v_end = v.add(len);
+
+    // 较短的一段先搬进 buf，然后从另一端开始归并，这样写入位置永远不会追上还没读的元素。
+    // 比较函数 panic 时，hole 会把 buf 里剩下的 [start, end) 搬回 dest 开始的空位
+    if mid <= len - mid {
+        // 左边较短：从前往后归并
+        ptr::copy_nonoverlapping(v, buf, mid);
+        let mut hole = MergeHole { start: buf, end: buf.add(mid), dest: v };
+
+        let mut right = v_mid;
+        while hole.start < hole.end && right < v_end {
+            // 相等时取左边的元素，保证稳定
+            let src = if is_less(&*right, &*hole.start) {
+                let src = right;
+                right = right.add(1);
+                src
+            } else {
+                let src = hole.start;
+                hole.start = hole.start.add(1);
+                src
+            };
+            ptr::copy_nonoverlapping(src, hole.dest, 1);
+            hole.dest = hole.dest.add(1);
+        }
+    } else {
+        // 右边较短：从后往前归并
+        ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+        let mut hole = MergeHole { start: buf, end: buf.add(len - mid), dest: v_mid };
+
+        let mut out = v_end;
+        while v < hole.dest && buf < hole.end {
+            // 相等时取右边的元素（放在更靠后的位置），保证稳定
+            let src = if is_less(&*hole.end.sub(1), &*hole.dest.sub(1)) {
+                hole.dest = hole.dest.sub(1);
+                hole.dest
+            } else {
+                hole.end = hole.end.sub(1);
+                hole.end
+            };
+            out = out.sub(1);
+            ptr::copy_nonoverlapping(src, out, 1);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+// 不超过这个长度直接用插入排序，不需要 scratch
+const MERGE_MAX_INSERTION: usize = 20;
+
+/// 稳定的自适应归并排序，scratch 从全局分配器申请
+pub fn merge_sort<T, F>(v: &mut [T], is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    merge_sort_in(v, is_less, Global);
+}
+
+/// 和 `merge_sort` 相同，scratch 从 `alloc` 申请
+pub(crate) fn merge_sort_in<T, F, A>(v: &mut [T], mut is_less: F, alloc: A)
+where
+    F: FnMut(&T, &T) -> bool,
+    A: Allocator,
+{
+    if mem::size_of::<T>() == 0 || v.len() <= MERGE_MAX_INSERTION {
+        unsafe { merge_sort_with(v, &mut is_less, ptr::null_mut()) };
+        return;
+    }
+    // 每次合并时较短的一段不超过 len / 2
+    let buf = RawVec::<T, A>::with_capacity_in(v.len() / 2, alloc);
+    unsafe { merge_sort_with(v, &mut is_less, buf.ptr.as_ptr()) };
+}
+
+/// 稳定的自适应归并排序，使用调用者提供的 scratch，自身不分配内存
+///
+/// 从后往前扫描出天然有序的 run（严格降序的 run 原地反转），太短的 run 用插入排序补到 `MIN_RUN`，
+/// 然后按照 TimSort 的栈不变式合并相邻 run。已经有序或基本有序的输入是 O(n) 的
+///
+/// # Safety
+///
+/// `v.len() > 20` 且 `T` 不是 ZST 时，`buf` 至少能容纳 `v.len() / 2` 个元素，且不能和 `v` 重叠；
+/// 否则 `buf` 不会被使用
+pub(crate) unsafe fn merge_sort_with<T, F>(v: &mut [T], is_less: &mut F, buf: *mut T)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const MIN_RUN: usize = 10;
+    // collapse 保证 run 的长度自栈顶向下至少按斐波那契数列增长，
+    // 栈深度不会超过 log_φ(usize::MAX) ≈ 93，用定长数组就够了
+    const MAX_RUNS: usize = 2 * usize::BITS as usize;
+
+    // ZST 没有顺序可言
+    if mem::size_of::<T>() == 0 { return; }
+
+    let len = v.len();
+    if len <= MERGE_MAX_INSERTION {
+        insertion_sort(v, is_less);
+        return;
+    }
+
+    let mut runs = [Run { start: 0, len: 0 }; MAX_RUNS];
+    let mut n = 0;
+
+    let mut end = len;
+    while end > 0 {
+        let mut start = end - 1;
+        if start > 0 {
+            start -= 1;
+            if is_less(&v[start + 1], &v[start]) {
+                // 严格降序，反转之后不会打乱相等元素的顺序
+                while start > 0 && is_less(&v[start], &v[start - 1]) {
+                    start -= 1;
+                }
+                v[start..end].reverse();
+            } else {
+                while start > 0 && !is_less(&v[start], &v[start - 1]) {
+                    start -= 1;
+                }
+            }
+        }
+
+        while start > 0 && end - start < MIN_RUN {
+            start -= 1;
+            insert_head(&mut v[start..end], is_less);
+        }
+
+        runs[n] = Run { start, len: end - start };
+        n += 1;
+        end = start;
+
+        while let Some(r) = collapse(&runs[..n]) {
+            let left = runs[r + 1];
+            let right = runs[r];
+            merge(&mut v[left.start..right.start + right.len], left.len, buf, is_less);
+            runs[r] = Run { start: left.start, len: left.len + right.len };
+            runs.copy_within(r + 2..n, r + 1);
+            n -= 1;
+        }
+    }
+
+    debug_assert!(n == 1 && runs[0].start == 0 && runs[0].len == len);
+
+    // 栈顶是最靠前的 run。以下任一条件成立就需要合并（返回较小那一对的下标）：
+    // 1. 已经扫描到开头，剩下的 run 全部合并
+    // 2. runs[n - 2].len <= runs[n - 1].len
+    // 3. runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len
+    // 4. runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len
+    // 保证 run 长度自底向上至少按斐波那契数列增长，栈深度是 O(log n)
+    fn collapse(runs: &[Run]) -> Option<usize> {
+        let n = runs.len();
+        if n >= 2
+            && (runs[n - 1].start == 0
+                || runs[n - 2].len <= runs[n - 1].len
+                || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+                || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len))
+        {
+            if n >= 3 && runs[n - 3].len < runs[n - 1].len { Some(n - 3) } else { Some(n - 2) }
+        } else {
+            None
+        }
+    }
+}
+
+/// pattern-defeating quicksort
+///
+/// 小区间用插入排序；选主元时顺便判断是否已经基本有序；多次划分不平衡就打乱模式，
+/// 再不行退化成堆排序，所以最坏情况是 O(n log n)。分区只使用 `swap`，不会复制元素
+pub fn quicksort<T, F>(v: &mut [T], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if mem::size_of::<T>() == 0 { return; }
+
+    // 允许的不平衡划分次数，超过后改用堆排序
+    let limit = usize::BITS - v.len().leading_zeros();
+    recurse(v, &mut is_less, None, limit);
+}
+
+fn recurse<'a, T, F>(mut v: &'a mut [T], is_less: &mut F, mut pred: Option<&'a T>, mut limit: u32)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const MAX_INSERTION: usize = 20;
+
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = v.len();
+        if len <= MAX_INSERTION {
+            insertion_sort(v, is_less);
+            return;
+        }
+
+        if limit == 0 {
+            heapsort(v, is_less);
+            return;
+        }
+
+        if !was_balanced {
+            break_patterns(v);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(v, is_less);
+
+        // 上一次划分既平衡又没有发生交换，这次主元附近也有序，很可能整体已经有序
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(v, is_less) {
+            return;
+        }
+
+        // 前驱（左边最大的那个主元）不小于当前主元，说明当前主元就是区间内的最小值，
+        // 把所有等于它的元素一次性划到左边，之后只需要处理右边，大量重复元素时是 O(n)
+        if let Some(p) = pred {
+            if !is_less(p, &v[pivot]) {
+                let mid = partition_equal(v, pivot, is_less);
+                v = &mut v[mid..];
+                continue;
+            }
+        }
+
+        let (mid, partitioned) = partition(v, pivot, is_less);
+        was_balanced = cmp::min(mid, len - mid) >= len / 8;
+        was_partitioned = partitioned;
+
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // 递归处理较短的一边，较长的一边留在循环里，栈深度是 O(log n)
+        if left.len() < right.len() {
+            recurse(left, is_less, pred, limit);
+            v = right;
+            pred = Some(pivot);
+        } else {
+            recurse(right, is_less, Some(pivot), limit);
+            v = left;
+        }
+    }
+}
+
+/// 以 `v[pivot]` 为主元划分，返回主元的最终位置，以及划分前是否已经划分好
+fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    v.swap(0, pivot);
+    let (head, rest) = v.split_at_mut(1);
+    let pivot = &head[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    while l < r && is_less(&rest[l], pivot) {
+        l += 1;
+    }
+    while l < r && !is_less(&rest[r - 1], pivot) {
+        r -= 1;
+    }
+    let was_partitioned = l >= r;
+
+    // rest[..l] 都小于主元，rest[r..] 都不小于主元
+    loop {
+        while l < r && is_less(&rest[l], pivot) {
+            l += 1;
+        }
+        while l < r && !is_less(&rest[r - 1], pivot) {
+            r -= 1;
+        }
+        if l >= r { break; }
+        r -= 1;
+        rest.swap(l, r);
+        l += 1;
+    }
+
+    // 主元和最后一个小于它的元素交换，放到分界处
+    v.swap(0, l);
+    (l, was_partitioned)
+}
+
+/// 把所有等于 `v[pivot]` 的元素划到左边，返回这一段的长度（包括主元）
+///
+/// 调用前提：区间内没有比主元小的元素
+fn partition_equal<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    v.swap(0, pivot);
+    let (head, rest) = v.split_at_mut(1);
+    let pivot = &head[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    loop {
+        while l < r && !is_less(pivot, &rest[l]) {
+            l += 1;
+        }
+        while l < r && is_less(pivot, &rest[r - 1]) {
+            r -= 1;
+        }
+        if l >= r { break; }
+        r -= 1;
+        rest.swap(l, r);
+        l += 1;
+    }
+    l + 1
+}
+
+/// 选主元：较短的区间取三个点的中位数，较长的区间取九个点的中位数（ninther）；
+/// 同时根据比较过程中的交换次数猜测区间是否基本有序，全部逆序时先整体反转
+fn choose_pivot<T, F>(v: &mut [T], is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const SHORTEST_MEDIAN_OF_MEDIANS: usize = 50;
+    const MAX_SWAPS: usize = 4 * 3;
+
+    let len = v.len();
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        let mut sort2 = |a: &mut usize, b: &mut usize| {
+            if is_less(&v[*b], &v[*a]) {
+                mem::swap(a, b);
+                swaps += 1;
+            }
+        };
+        let mut sort3 = |a: &mut usize, b: &mut usize, c: &mut usize| {
+            sort2(a, b);
+            sort2(b, c);
+            sort2(a, b);
+        };
+
+        if len >= SHORTEST_MEDIAN_OF_MEDIANS {
+            let mut sort_adjacent = |a: &mut usize| {
+                let tmp = *a;
+                sort3(&mut (tmp - 1), a, &mut (tmp + 1));
+            };
+            sort_adjacent(&mut a);
+            sort_adjacent(&mut b);
+            sort_adjacent(&mut c);
+        }
+        sort3(&mut a, &mut b, &mut c);
+    }
+
+    if swaps < MAX_SWAPS {
+        (b, swaps == 0)
+    } else {
+        // 每次比较都要交换，区间很可能是逆序的
+        v.reverse();
+        (len - 1 - b, true)
+    }
+}
+
+/// 尝试用少量插入把基本有序的区间排好，成功返回 true
+fn partial_insertion_sort<T, F>(v: &mut [T], is_less: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const MAX_STEPS: usize = 5;
+    const SHORTEST_SHIFTING: usize = 50;
+
+    let len = v.len();
+    let mut i = 1;
+    for _ in 0..MAX_STEPS {
+        while i < len && !is_less(&v[i], &v[i - 1]) {
+            i += 1;
+        }
+        if i == len { return true; }
+        // 区间太短，不值得在这里修补
+        if len < SHORTEST_SHIFTING { return false; }
+
+        v.swap(i - 1, i);
+        insert_tail(&mut v[..i], is_less);
+        insert_head(&mut v[i..], is_less);
+    }
+    false
+}
+
+/// 用伪随机交换打乱可能导致划分不平衡的模式
+fn break_patterns<T>(v: &mut [T]) {
+    let len = v.len();
+    if len < 8 { return; }
+
+    // xorshift，只需要“看起来随机”，不需要真正的随机数
+    let mut seed = len as u32;
+    let mut gen_u32 = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    };
+    let modulus = len.next_power_of_two();
+
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = gen_u32() as usize & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        v.swap(pos - 1 + i, other);
+    }
+}
+
+fn heapsort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut sift_down = |v: &mut [T], mut node: usize| loop {
+        let mut child = 2 * node + 1;
+        if child >= v.len() { break; }
+        if child + 1 < v.len() && is_less(&v[child], &v[child + 1]) {
+            child += 1;
+        }
+        if !is_less(&v[node], &v[child]) { break; }
+        v.swap(node, child);
+        node = child;
+    };
+
+    for i in (0..v.len() / 2).rev() {
+        sift_down(v, i);
+    }
+    for i in (1..v.len()).rev() {
+        v.swap(0, i);
+        sift_down(&mut v[..i], 0);
+    }
+}
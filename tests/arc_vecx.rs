@@ -0,0 +1,156 @@
+use std::thread;
+
+use test_demo::vecx::arc::ArcVecx;
+use test_demo::vecx::Vecx;
+
+mod common;
+
+use common::{numbers, Drops, Tracked};
+
+#[test]
+fn from_vecx_is_zero_copy_when_the_header_fits() {
+    // 空余 54 个 u64，放得下头部
+    let v = numbers::<u64>(10, 64);
+    let ptr = v.as_ptr();
+    let a = ArcVecx::from(v);
+    assert_eq!(a.as_ptr(), ptr);
+    assert!(a.iter().copied().eq(0..10));
+
+    // try_unwrap 原样还回同一块存储，容量不变
+    let v = ArcVecx::try_unwrap(a).unwrap();
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(v.capacity(), 64);
+    assert!(v.iter().copied().eq(0..10));
+}
+
+#[test]
+fn from_vecx_reallocates_when_the_header_does_not_fit() {
+    // 没有空余容量，只能换一块更大的内存
+    let v = numbers::<u64>(8, 8);
+    let ptr = v.as_ptr();
+    let a = ArcVecx::from(v);
+    assert_ne!(a.as_ptr(), ptr);
+    assert!(a.iter().copied().eq(0..8));
+
+    let v = ArcVecx::try_unwrap(a).unwrap();
+    assert!(v.capacity() > 8);
+    assert!(v.iter().copied().eq(0..8));
+
+    // 空的 Vecx 也一样
+    let empty: ArcVecx<u64> = ArcVecx::new();
+    assert!(empty.is_empty());
+    assert_eq!(ArcVecx::strong_count(&empty), 1);
+}
+
+#[test]
+fn make_mut_copies_only_when_shared() {
+    let mut a = ArcVecx::from(numbers::<u64>(4, 16));
+    let ptr = a.as_ptr();
+    // 独占时原地修改
+    a.make_mut()[0] = 100;
+    assert_eq!(a.as_ptr(), ptr);
+    assert!(a.get_mut().is_some());
+
+    let b = a.clone();
+    assert_eq!(ArcVecx::strong_count(&a), 2);
+    assert!(a.get_mut().is_none());
+    // 共享时先复制一份，b 不受影响
+    a.make_mut()[1] = 200;
+    assert_ne!(a.as_ptr(), ptr);
+    assert_eq!(b.as_ptr(), ptr);
+    assert!(!ArcVecx::ptr_eq(&a, &b));
+    assert_eq!(&a[..], [100, 200, 2, 3]);
+    assert_eq!(&b[..], [100, 1, 2, 3]);
+    assert_eq!(ArcVecx::strong_count(&a), 1);
+    assert_eq!(ArcVecx::strong_count(&b), 1);
+
+    // 复制出来的那份之后也是独占的
+    let copied = a.as_ptr();
+    a.make_mut()[2] = 300;
+    assert_eq!(a.as_ptr(), copied);
+}
+
+#[test]
+fn try_unwrap_fails_while_shared() {
+    let a = ArcVecx::from(numbers::<u64>(3, 16));
+    let b = a.clone();
+    let Err(a) = ArcVecx::try_unwrap(a) else { panic!("unwrapped a shared ArcVecx") };
+    assert!(ArcVecx::ptr_eq(&a, &b));
+    drop(b);
+    assert_eq!(&ArcVecx::try_unwrap(a).unwrap()[..], [0, 1, 2]);
+
+    // unwrap_or_clone：共享时复制，独占时直接取回
+    let a = ArcVecx::from(numbers::<u64>(3, 16));
+    let ptr = a.as_ptr();
+    let b = a.clone();
+    let copy = ArcVecx::unwrap_or_clone(a);
+    assert_ne!(copy.as_ptr(), ptr);
+    assert_eq!(ArcVecx::unwrap_or_clone(b).as_ptr(), ptr);
+}
+
+#[test]
+fn drops_each_element_once_across_threads() {
+    const LEN: usize = 1_000;
+    let drops = Drops::new();
+    let a: ArcVecx<Tracked> = (0..LEN).map(|value| drops.track(value)).collect();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let a = a.clone();
+            thread::spawn(move || {
+                let mut local = a.clone();
+                let sum: usize = local.iter().map(|t| t.id).sum();
+                assert_eq!(sum, LEN * (LEN - 1) / 2);
+                // 一些线程复制出自己的一份再修改
+                local.make_mut()[0].id = usize::MAX;
+                assert_eq!(local[0].id, usize::MAX);
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|handle| handle.join().unwrap());
+
+    // 每个线程的副本都已经 drop，原来那份还在
+    assert_eq!(drops.total(), 8 * LEN);
+    assert_eq!(ArcVecx::strong_count(&a), 1);
+    assert_eq!(a[0].id, 0);
+    drop(a);
+    assert_eq!(drops.total(), 9 * LEN);
+}
+
+#[test]
+fn last_handle_dropped_on_another_thread() {
+    let drops = Drops::new();
+    let a: ArcVecx<Tracked> = (0..100).map(|value| drops.track(value)).collect();
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let a = a.clone();
+            thread::spawn(move || drop(a))
+        })
+        .collect();
+    drop(a);
+    handles.into_iter().for_each(|handle| handle.join().unwrap());
+    // 不管哪个线程最后 drop，元素都只 drop 一次
+    assert_eq!(drops.total(), 100);
+}
+
+#[test]
+fn zero_sized_elements() {
+    let mut v = Vecx::new();
+    for _ in 0..1_000 {
+        v.push(());
+    }
+    let a = ArcVecx::from(v);
+    assert_eq!(a.len(), 1_000);
+    let b = a.clone();
+    assert_eq!(ArcVecx::strong_count(&a), 2);
+    drop(b);
+    let v = ArcVecx::try_unwrap(a).unwrap();
+    assert_eq!(v.len(), 1_000);
+    assert_eq!(v.capacity(), usize::MAX);
+
+    let mut a: ArcVecx<()> = std::iter::repeat_n((), 5).collect();
+    let b = a.clone();
+    assert_eq!(a.make_mut().len(), 5);
+    assert!(!ArcVecx::ptr_eq(&a, &b));
+    assert_eq!(ArcVecx::unwrap_or_clone(b).len(), 5);
+}
@@ -0,0 +1,134 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use test_demo::arena::Arena;
+
+mod common;
+
+// 统计还没有释放的堆分配个数，用来检查 arena 有没有漏掉块
+#[global_allocator]
+static GLOBAL: common::Counting = common::Counting;
+
+// drop 时记录自己的编号，编号等于 `panic_on` 的那个会 panic
+struct Noisy {
+    id: usize,
+    panic_on: usize,
+    dropped: Rc<Cell<Vec<usize>>>,
+}
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        let mut dropped = self.dropped.take();
+        dropped.push(self.id);
+        self.dropped.set(dropped);
+        if self.id == self.panic_on {
+            panic!("drop {}", self.id);
+        }
+    }
+}
+
+#[test]
+fn references_survive_chunk_growth() {
+    let arena = Arena::new();
+    let first = arena.alloc(0u64);
+    let first_addr = first as *const u64;
+    // 第一块大约 4 KiB，分配足够多的对象让 arena 开好几块新块
+    let refs: Vec<&mut u64> = (1..5_000).map(|i| arena.alloc(i)).collect();
+    assert_eq!(first_addr, first as *const u64);
+    assert_eq!(*first, 0);
+    *first = 100;
+    for (i, r) in refs.into_iter().enumerate() {
+        assert_eq!(*r, i as u64 + 1);
+        *r += 1;
+    }
+    assert_eq!(*first, 100);
+    assert_eq!(arena.len(), 5_000);
+}
+
+#[test]
+fn alloc_extend_is_contiguous_and_iteration_keeps_order() {
+    let mut arena = Arena::new();
+    arena.alloc(String::from("a"));
+    // 比剩下的空位大，整段放进同一块新块
+    let slice = arena.alloc_extend((0..1_000).map(|i| i.to_string()));
+    assert_eq!(slice.len(), 1_000);
+    assert!(slice.iter().map(|s| s.parse::<usize>().unwrap()).eq(0..1_000));
+    assert!(arena.alloc_extend(std::iter::empty()).is_empty());
+    arena.alloc(String::from("z"));
+
+    let expected: Vec<String> = std::iter::once("a".to_string())
+        .chain((0..1_000).map(|i| i.to_string()))
+        .chain(std::iter::once("z".to_string()))
+        .collect();
+    let iter = arena.iter_mut();
+    assert_eq!(iter.len(), 1_002);
+    assert!(iter.map(|s| s.clone()).eq(expected.iter().cloned()));
+    assert!(arena.into_iter().eq(expected));
+}
+
+#[test]
+fn alloc_extend_may_allocate_from_inside_the_iterator() {
+    let arena = Arena::new();
+    let slice = arena.alloc_extend((0..3).map(|i| *arena.alloc(i * 10) + 1));
+    assert_eq!(slice, [1, 11, 21]);
+    assert_eq!(arena.len(), 6);
+}
+
+#[test]
+fn clear_drops_everything_and_keeps_the_last_chunk() {
+    let dropped = Rc::new(Cell::new(Vec::new()));
+    let mut arena = Arena::with_capacity(4);
+    for id in 0..100 {
+        arena.alloc(Noisy { id, panic_on: usize::MAX, dropped: dropped.clone() });
+    }
+    arena.clear();
+    assert!(arena.is_empty());
+    let mut ids = dropped.take();
+    ids.sort();
+    assert!(ids.into_iter().eq(0..100));
+
+    arena.alloc(Noisy { id: 7, panic_on: usize::MAX, dropped: dropped.clone() });
+    assert_eq!(arena.len(), 1);
+}
+
+#[test]
+fn panicking_drop_neither_leaks_nor_double_drops() {
+    let dropped = Rc::new(Cell::new(Vec::new()));
+    common::warm_up_panics();
+    let before = common::live();
+    {
+        let arena = Arena::with_capacity(8);
+        // 分布在好几块里，panic 的那个在中间一块
+        for id in 0..200 {
+            arena.alloc(Noisy { id, panic_on: 50, dropped: dropped.clone() });
+        }
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| drop(arena))).is_err());
+    }
+    let mut ids = dropped.take();
+    ids.sort();
+    // 每个对象恰好 drop 一次，所有块都已释放
+    assert!(ids.into_iter().eq(0..200));
+    assert_eq!(common::live(), before);
+}
+
+#[test]
+fn panicking_drop_during_clear() {
+    let dropped = Rc::new(Cell::new(Vec::new()));
+    common::warm_up_panics();
+    let before = common::live();
+    let mut arena = Arena::with_capacity(8);
+    for id in 0..50 {
+        arena.alloc(Noisy { id, panic_on: 3, dropped: dropped.clone() });
+    }
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| arena.clear())).is_err());
+    let mut ids = dropped.take();
+    ids.sort();
+    assert!(ids.into_iter().eq(0..50));
+
+    // clear 中途 panic 之后 arena 依然可以继续使用和 drop
+    arena.alloc(Noisy { id: 99, panic_on: usize::MAX, dropped: dropped.clone() });
+    drop(arena);
+    assert_eq!(dropped.take(), [99]);
+    assert_eq!(common::live(), before);
+}
@@ -0,0 +1,134 @@
+use test_demo::bit_vecx::{BitVecx, RankSelect};
+
+// 长度覆盖字（64 位）和超级块（512 位）的边界
+const LENS: &[usize] = &[0, 1, 2, 63, 64, 65, 127, 128, 511, 512, 513, 575, 576, 1023, 1024, 1025, 4096, 4159];
+
+fn xorshift(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+// 和逐位扫描的结果逐个比较所有的 rank 和 select
+fn check(bits: &[bool]) {
+    let rs = RankSelect::new(bits.iter().copied().collect());
+    assert_eq!(rs.len(), bits.len());
+    assert_eq!(rs.is_empty(), bits.is_empty());
+
+    let ones: Vec<usize> = (0..bits.len()).filter(|&i| bits[i]).collect();
+    let zeros: Vec<usize> = (0..bits.len()).filter(|&i| !bits[i]).collect();
+    assert_eq!(rs.count_ones(), ones.len());
+
+    let mut rank = 0;
+    for i in 0..=bits.len() {
+        assert_eq!(rs.rank1(i), rank, "rank1({i}) of {} bits", bits.len());
+        assert_eq!(rs.rank0(i), i - rank, "rank0({i}) of {} bits", bits.len());
+        if i < bits.len() && bits[i] {
+            rank += 1;
+        }
+    }
+
+    for (k, &pos) in ones.iter().enumerate() {
+        assert_eq!(rs.select1(k), Some(pos), "select1({k}) of {} bits", bits.len());
+    }
+    assert_eq!(rs.select1(ones.len()), None);
+    assert_eq!(rs.select1(usize::MAX), None);
+
+    for (k, &pos) in zeros.iter().enumerate() {
+        assert_eq!(rs.select0(k), Some(pos), "select0({k}) of {} bits", bits.len());
+    }
+    assert_eq!(rs.select0(zeros.len()), None);
+
+    // select 和 rank 互为逆运算
+    for (k, &pos) in ones.iter().enumerate() {
+        assert_eq!(rs.rank1(pos), k);
+    }
+}
+
+#[test]
+fn empty() {
+    check(&[]);
+    let rs = RankSelect::new(BitVecx::new());
+    assert_eq!(rs.rank1(0), 0);
+    assert_eq!(rs.rank0(0), 0);
+    assert_eq!(rs.select1(0), None);
+    assert_eq!(rs.select0(0), None);
+}
+
+#[test]
+fn all_ones_and_all_zeros() {
+    for &len in LENS {
+        check(&vec![true; len]);
+        check(&vec![false; len]);
+
+        let rs = RankSelect::new(BitVecx::repeat(true, len));
+        assert_eq!(rs.count_ones(), len);
+        // 最后一个字超出 len 的位不能被当成 0
+        assert_eq!(rs.select0(0), None);
+        if len > 0 {
+            assert_eq!(rs.select1(len - 1), Some(len - 1));
+        }
+    }
+}
+
+#[test]
+fn matches_naive_scan() {
+    let mut seed = 0x9e37_79b9_7f4a_7c15;
+    for &len in LENS {
+        // 不同的密度：稀疏、一半、稠密
+        for &density in &[1u64, 8, 32, 56, 63] {
+            let bits: Vec<bool> = (0..len).map(|_| xorshift(&mut seed) % 64 < density).collect();
+            check(&bits);
+        }
+        check(&(0..len).map(|i| i % 2 == 0).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn bits_on_word_and_block_boundaries() {
+    let len = 4 * 512 + 7;
+    // 只在每个字和超级块的首尾放 1
+    let bits: Vec<bool> = (0..len).map(|i| i % 64 == 0 || i % 64 == 63 || i % 512 == 511).collect();
+    check(&bits);
+
+    // 整个超级块全是 0 或全是 1，select 需要跳过它们
+    let bits: Vec<bool> = (0..len).map(|i| (i / 512) % 2 == 1 || i == len - 1).collect();
+    check(&bits);
+    let bits: Vec<bool> = (0..len).map(|i| (i / 512) % 2 == 0 && i != 0).collect();
+    check(&bits);
+
+    // 单个 1 放在每个可能的位置
+    for pos in [0, 1, 62, 63, 64, 65, 511, 512, 513, 1023, 1024] {
+        let mut bits = vec![false; 1100];
+        bits[pos] = true;
+        check(&bits);
+        let mut bits = vec![true; 1100];
+        bits[pos] = false;
+        check(&bits);
+    }
+}
+
+#[test]
+fn index_reflects_bits_at_construction() {
+    let mut bits: BitVecx = (0..700).map(|i| i % 3 == 0).collect();
+    let rs = RankSelect::new(bits.clone());
+    assert_eq!(rs.bits(), &bits);
+    assert_eq!(rs.rank1(700), 234);
+
+    // 取回位向量修改之后重建索引
+    bits = rs.into_bits();
+    bits.set(1, true);
+    bits.push(true);
+    let rs = RankSelect::new(bits);
+    assert_eq!(rs.rank1(701), 236);
+    assert_eq!(rs.select1(1), Some(1));
+    assert_eq!(rs.select1(235), Some(700));
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn rank_past_the_end_panics() {
+    let rs = RankSelect::new(BitVecx::repeat(true, 10));
+    rs.rank1(11);
+}
@@ -0,0 +1,140 @@
+use test_demo::bloom::{BloomError, BloomFilter};
+
+#[test]
+fn no_false_negatives() {
+    let mut filter = BloomFilter::new(10_000, 0.01);
+    for i in 0..10_000u64 {
+        filter.insert(&i);
+    }
+    assert!((0..10_000u64).all(|i| filter.contains(&i)));
+
+    // 插入超过预计个数之后误判率变高，但依然不会漏
+    for i in 10_000..50_000u64 {
+        filter.insert(&i);
+    }
+    assert!((0..50_000u64).all(|i| filter.contains(&i)));
+
+    // 不定长的类型也可以
+    let mut words: BloomFilter<str> = BloomFilter::new(100, 0.01);
+    for w in ["alpha", "beta", "gamma", ""] {
+        words.insert(w);
+    }
+    assert!(["alpha", "beta", "gamma", ""].iter().all(|w| words.contains(w)));
+}
+
+#[test]
+fn false_positive_rate_stays_within_bounds() {
+    for &(n, p) in &[(1_000usize, 0.05), (10_000, 0.01), (20_000, 0.001)] {
+        let mut filter = BloomFilter::new(n, p);
+        for i in 0..n as u64 {
+            filter.insert(&i);
+        }
+        // 用从没插入过的元素测量误判率
+        let trials = 200_000u64;
+        let hits = (0..trials).filter(|i| filter.contains(&(u64::MAX - i))).count();
+        let rate = hits as f64 / trials as f64;
+        // 参数是按 p 选的，实测值应该在 p 附近，给两倍的余量
+        assert!(rate < 2.0 * p, "n {n}, p {p}: measured {rate}");
+        assert!(rate > p / 4.0, "n {n}, p {p}: measured {rate} is suspiciously low");
+
+        // 估计的元素个数也在合理范围内
+        let estimate = filter.estimated_len();
+        assert!((estimate - n as f64).abs() < n as f64 * 0.05, "estimated {estimate} for {n}");
+    }
+}
+
+#[test]
+fn parameters_follow_the_formula() {
+    // n = 1000，p = 1% 时 m ≈ 9586，k ≈ 7
+    let filter: BloomFilter<u32> = BloomFilter::new(1_000, 0.01);
+    assert_eq!(filter.num_bits(), 9_586);
+    assert_eq!(filter.num_hashes(), 7);
+
+    let empty: BloomFilter<u32> = BloomFilter::new(0, 0.5);
+    assert!(empty.num_bits() >= 1);
+    assert!(!empty.contains(&1));
+}
+
+#[test]
+#[should_panic(expected = "false positive rate must be in (0, 1)")]
+fn rejects_invalid_rate() {
+    let _: BloomFilter<u32> = BloomFilter::new(10, 1.0);
+}
+
+#[test]
+fn union_and_intersection() {
+    let mut a = BloomFilter::with_params(4_096, 4);
+    let mut b = BloomFilter::with_params(4_096, 4);
+    for i in 0..100u32 {
+        a.insert(&i);
+    }
+    for i in 50..150u32 {
+        b.insert(&i);
+    }
+
+    let mut union = BloomFilter::from_bytes(&a.to_bytes()).unwrap();
+    union.union(&b).unwrap();
+    assert!((0..150u32).all(|i| union.contains(&i)));
+
+    let mut both = BloomFilter::from_bytes(&a.to_bytes()).unwrap();
+    both.intersection(&b).unwrap();
+    assert!((50..100u32).all(|i| both.contains(&i)));
+    assert!(both.count_ones() <= a.count_ones().min(b.count_ones()));
+
+    let other: BloomFilter<u32> = BloomFilter::with_params(4_096, 5);
+    assert_eq!(a.union(&other), Err(BloomError::Incompatible));
+    assert_eq!(a.intersection(&BloomFilter::with_params(4_000, 4)), Err(BloomError::Incompatible));
+
+    a.clear();
+    assert_eq!(a.count_ones(), 0);
+    assert_eq!(a.estimated_len(), 0.0);
+}
+
+#[test]
+fn serialization_round_trip() {
+    // 位数不是 64 的倍数，最后一个字只用了一部分
+    let mut filter = BloomFilter::with_params(1_000, 3);
+    for w in ["a", "b", "c"] {
+        filter.insert(w);
+    }
+    let bytes = filter.to_bytes();
+    assert_eq!(bytes.len(), 16 + 16 * 8);
+    assert_eq!(&bytes[..4], b"BLM1");
+
+    let back: BloomFilter<str> = BloomFilter::from_bytes(&bytes).unwrap();
+    assert_eq!((back.num_bits(), back.num_hashes()), (1_000, 3));
+    assert!(["a", "b", "c"].iter().all(|w| back.contains(w)));
+    assert_eq!(back.count_ones(), filter.count_ones());
+
+    let text = filter.to_base64();
+    let back: BloomFilter<str> = BloomFilter::from_base64(&format!("{text}\n")).unwrap();
+    assert_eq!(&back.to_bytes()[..], &bytes[..]);
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    let filter: BloomFilter<u32> = BloomFilter::with_params(100, 2);
+    let bytes = filter.to_bytes();
+    let malformed = |b: &[u8]| BloomFilter::<u32>::from_bytes(b).err();
+
+    assert_eq!(malformed(b"BLM"), Some(BloomError::Malformed("bad header")));
+    let mut bad = bytes.to_vec();
+    bad[0] = b'X';
+    assert_eq!(malformed(&bad), Some(BloomError::Malformed("bad header")));
+
+    let mut bad = bytes.to_vec();
+    bad[12..16].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(malformed(&bad), Some(BloomError::Malformed("empty filter")));
+
+    let mut bad = bytes.to_vec();
+    bad.pop();
+    assert_eq!(malformed(&bad), Some(BloomError::Malformed("bit array length mismatch")));
+
+    // 100 位只用了第二个字的低 36 位
+    let mut bad = bytes.to_vec();
+    let last = bad.len() - 1;
+    bad[last] = 0x80;
+    assert_eq!(malformed(&bad), Some(BloomError::Malformed("bits set past the end")));
+
+    assert_eq!(BloomFilter::<u32>::from_base64("@@").err(), Some(BloomError::Malformed("invalid base64")));
+}
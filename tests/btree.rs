@@ -0,0 +1,174 @@
+use test_demo::btree::Tree;
+
+const DEPTH: usize = 10_000_000;
+
+// 只有左孩子的退化树，等价于一条一千万个节点的链表
+fn left_spine(n: usize) -> Tree<usize> {
+    let mut tree = Tree::new();
+    for i in 0..n {
+        tree = Tree::join(tree, i, Tree::new());
+    }
+    tree
+}
+
+fn right_spine(n: usize) -> Tree<usize> {
+    let mut tree = Tree::new();
+    for i in 0..n {
+        tree = Tree::join(Tree::new(), i, tree);
+    }
+    tree
+}
+
+#[test]
+fn drop_degenerate_left() {
+    let tree = left_spine(DEPTH);
+    assert_eq!(tree.len(), DEPTH);
+    drop(tree);
+}
+
+#[test]
+fn drop_degenerate_right() {
+    drop(right_spine(DEPTH));
+}
+
+#[test]
+fn drop_zigzag() {
+    let mut tree = Tree::new();
+    for i in 0..DEPTH {
+        tree = if i % 2 == 0 { Tree::join(tree, i, Tree::new()) } else { Tree::join(Tree::new(), i, tree) };
+    }
+    drop(tree);
+}
+
+#[test]
+fn clone_and_eq_degenerate() {
+    let tree = left_spine(DEPTH);
+    let copy = tree.clone();
+    assert_eq!(copy.len(), DEPTH);
+    assert!(tree == copy);
+    assert!(tree != right_spine(DEPTH));
+}
+
+#[test]
+fn drop_partially_consumed_into_iter() {
+    let mut iter = left_spine(DEPTH).into_iter();
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next_back(), Some(DEPTH - 1));
+    drop(iter);
+
+    let mut iter = right_spine(DEPTH).into_level_order();
+    assert_eq!(iter.next(), Some(DEPTH - 1));
+    drop(iter);
+}
+
+#[test]
+fn clone_and_eq_shape() {
+    let tree = Tree::join(Tree::leaf(1), 2, Tree::join(Tree::leaf(3), 4, Tree::new()));
+    let copy = tree.clone();
+    assert!(tree == copy);
+    assert_eq!(copy.preorder().copied().collect::<Vec<_>>(), [2, 1, 4, 3]);
+
+    // 中序相同但形状不同的树不相等
+    let other = Tree::join(Tree::join(Tree::leaf(1), 2, Tree::leaf(3)), 4, Tree::new());
+    assert!(tree.iter().eq(other.iter()));
+    assert!(tree != other);
+}
+
+//         1
+//       /   \
+//      2     3
+//     / \     \
+//    4   5     6
+//             /
+//            7
+fn sample() -> Tree<u32> {
+    let left = Tree::join(Tree::leaf(4), 2, Tree::leaf(5));
+    let right = Tree::join(Tree::new(), 3, Tree::join(Tree::leaf(7), 6, Tree::new()));
+    Tree::join(left, 1, right)
+}
+
+const IN: [u32; 7] = [4, 2, 5, 1, 3, 7, 6];
+const PRE: [u32; 7] = [1, 2, 4, 5, 3, 6, 7];
+const POST: [u32; 7] = [4, 5, 2, 7, 6, 3, 1];
+const LEVEL: [u32; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+fn rev(order: [u32; 7]) -> Vec<u32> {
+    order.iter().rev().copied().collect()
+}
+
+#[test]
+fn traversal_shared() {
+    let tree = sample();
+    assert_eq!(tree.iter().copied().collect::<Vec<_>>(), IN);
+    assert_eq!(tree.preorder().copied().collect::<Vec<_>>(), PRE);
+    assert_eq!(tree.postorder().copied().collect::<Vec<_>>(), POST);
+    assert_eq!(tree.level_order().copied().collect::<Vec<_>>(), LEVEL);
+    assert_eq!((&tree).into_iter().copied().collect::<Vec<_>>(), IN);
+
+    assert_eq!(tree.iter().rev().copied().collect::<Vec<_>>(), rev(IN));
+    assert_eq!(tree.preorder().rev().copied().collect::<Vec<_>>(), rev(PRE));
+    assert_eq!(tree.postorder().rev().copied().collect::<Vec<_>>(), rev(POST));
+}
+
+#[test]
+fn traversal_mut() {
+    // 按访问顺序给每个元素加上序号，再用中序读出来核对访问顺序
+    fn visit(order: [u32; 7], f: impl FnOnce(&mut Tree<u32>) -> Vec<&mut u32>) {
+        let mut tree = sample();
+        let elems = f(&mut tree);
+        assert_eq!(elems.iter().map(|x| **x).collect::<Vec<_>>(), order);
+        for (i, x) in elems.into_iter().enumerate() {
+            *x += 100 * i as u32;
+        }
+        let expected: Vec<u32> = IN.iter().map(|x| x + 100 * order.iter().position(|y| y == x).unwrap() as u32).collect();
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), expected);
+    }
+
+    visit(IN, |t| t.iter_mut().collect());
+    visit(IN, |t| t.into_iter().collect());
+    visit(PRE, |t| t.preorder_mut().collect());
+    visit(POST, |t| t.postorder_mut().collect());
+    visit(LEVEL, |t| t.level_order_mut().collect());
+
+    let mut tree = sample();
+    assert_eq!(tree.preorder_mut().rev().map(|x| *x).collect::<Vec<_>>(), rev(PRE));
+    assert_eq!(tree.postorder_mut().rev().map(|x| *x).collect::<Vec<_>>(), rev(POST));
+}
+
+#[test]
+fn traversal_owned() {
+    assert_eq!(sample().into_iter().collect::<Vec<_>>(), IN);
+    assert_eq!(sample().into_preorder().collect::<Vec<_>>(), PRE);
+    assert_eq!(sample().into_postorder().collect::<Vec<_>>(), POST);
+    assert_eq!(sample().into_level_order().collect::<Vec<_>>(), LEVEL);
+
+    assert_eq!(sample().into_iter().rev().collect::<Vec<_>>(), rev(IN));
+    assert_eq!(sample().into_preorder().rev().collect::<Vec<_>>(), rev(PRE));
+    assert_eq!(sample().into_postorder().rev().collect::<Vec<_>>(), rev(POST));
+}
+
+#[test]
+fn traversal_both_ends_and_len() {
+    let tree = sample();
+    let mut iter = tree.iter();
+    assert_eq!(iter.len(), 7);
+    assert_eq!(iter.next(), Some(&4));
+    assert_eq!(iter.next_back(), Some(&6));
+    assert_eq!(iter.next_back(), Some(&7));
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.copied().collect::<Vec<_>>(), [2, 5, 1, 3]);
+
+    let mut iter = sample().into_preorder();
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next_back(), Some(7));
+    assert_eq!(iter.collect::<Vec<_>>(), [2, 4, 5, 3, 6]);
+
+    let mut level = tree.level_order();
+    level.next();
+    assert_eq!(level.len(), 6);
+
+    let empty: Tree<u32> = Tree::new();
+    assert_eq!(empty.iter().next(), None);
+    assert_eq!(empty.postorder().next_back(), None);
+    assert_eq!(empty.into_level_order().next(), None);
+}
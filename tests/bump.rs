@@ -0,0 +1,153 @@
+use std::alloc::Layout;
+
+use test_demo::allocator::Allocator;
+use test_demo::bump::Bump;
+use test_demo::vecx::Vecx;
+
+mod common;
+
+// 统计全局分配器的调用次数，用来确认 `Vecx<T, &Bump>` 不碰全局堆
+#[global_allocator]
+static GLOBAL: common::Counting = common::Counting;
+
+#[test]
+fn push_grows_in_place() {
+    let bump = Bump::new();
+    let mut v = Vecx::new_in(&bump);
+    v.push(0u32);
+    let ptr = v.as_ptr();
+    for i in 1..512u32 {
+        v.push(i);
+    }
+    // 一直是最近一次分配，每次翻倍都原地扩大
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(v.capacity(), 512);
+    assert!(v.iter().copied().eq(0..512));
+
+    let stats = bump.stats();
+    assert_eq!(stats.chunks, 1);
+    assert_eq!(stats.allocated, 512 * 4);
+    assert_eq!(stats.wasted, 0);
+}
+
+#[test]
+fn grow_across_chunk_boundary() {
+    let bump = Bump::new();
+    let mut v = Vecx::new_in(&bump);
+    for i in 0..1024u32 {
+        v.push(i);
+    }
+    let first = bump.stats();
+    assert_eq!(first.chunks, 1);
+    assert_eq!(first.allocated, 4096);
+
+    // 第一块正好用完，再 push 只能搬到新块里
+    let old = v.as_ptr();
+    v.push(1024);
+    assert_ne!(v.as_ptr(), old);
+    assert!(v.iter().copied().eq(0..1025));
+
+    let stats = bump.stats();
+    assert_eq!(stats.chunks, 2);
+    assert_eq!(stats.allocated, 2048 * 4);
+    // 旧的 4096 字节不是新块里的最近一次分配，只能记为浪费
+    assert_eq!(stats.wasted, 4096);
+    assert!(stats.capacity >= stats.allocated + stats.wasted);
+
+    // 之后又可以在新块里原地扩大
+    let ptr = v.as_ptr();
+    for i in 1025..2048u32 {
+        v.push(i);
+    }
+    assert_eq!(v.as_ptr(), ptr);
+}
+
+#[test]
+fn old_chunk_block_is_not_treated_as_last() {
+    let bump = Bump::with_capacity(4096);
+    // 占满第一块，再分配一次开出第二块
+    let layout = Layout::from_size_align(4096, 16).unwrap();
+    let a = bump.allocate(layout).unwrap();
+    let b = bump.allocate(Layout::from_size_align(16, 16).unwrap()).unwrap();
+    assert_eq!(bump.stats().chunks, 2);
+
+    unsafe {
+        // a 属于旧块，不管新块的地址在哪里，释放都不能把 bump 指针退回旧块
+        bump.deallocate(a, layout);
+        let c = bump.allocate(Layout::from_size_align(16, 16).unwrap()).unwrap();
+        assert_eq!(c.as_ptr(), b.as_ptr().add(16));
+        let stats = bump.stats();
+        assert_eq!(stats.allocated, 32);
+        assert_eq!(stats.wasted, 4096);
+    }
+}
+
+#[test]
+fn dealloc_last_rewinds_and_reset_keeps_largest_chunk() {
+    let mut bump = Bump::with_capacity(1 << 16);
+    {
+        let mut a: Vecx<u64, &Bump> = Vecx::with_capacity_in(8, &bump);
+        a.push(1);
+        let b: Vecx<u64, &Bump> = Vecx::with_capacity_in(8, &bump);
+        assert_eq!(bump.stats().allocated, 128);
+        // b 是最近一次分配，释放时指针退回
+        drop(b);
+        assert_eq!(bump.stats().allocated, 64);
+        assert_eq!(bump.stats().wasted, 0);
+
+        let c: Vecx<u64, &Bump> = Vecx::with_capacity_in(8, &bump);
+        // a 已经不是最近一次分配，只能算作浪费
+        drop(a);
+        assert_eq!(bump.stats().wasted, 64);
+        drop(c);
+    }
+
+    for _ in 0..5 {
+        let mut v = Vecx::new_in(&bump);
+        for i in 0..20_000u64 {
+            v.push(i);
+        }
+    }
+    assert!(bump.stats().chunks > 1);
+
+    bump.reset();
+    let stats = bump.stats();
+    assert_eq!(stats.chunks, 1);
+    assert_eq!(stats.allocated, 0);
+    assert_eq!(stats.wasted, 0);
+
+    // reset 之后从保留的那块开头重新分配
+    let mut v = Vecx::new_in(&bump);
+    v.push(7u8);
+    assert_eq!(bump.stats().chunks, 1);
+    assert_eq!(bump.stats().allocated, 1);
+}
+
+#[test]
+fn sort_uses_the_vecx_allocator() {
+    let bump = Bump::with_capacity(1 << 20);
+    let mut v: Vecx<(u32, u32), &Bump> = Vecx::with_capacity_in(10_000, &bump);
+    let mut x = 12345u32;
+    for i in 0..10_000 {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        v.push((x % 100, i));
+    }
+
+    let before = common::allocs();
+    v.sort();
+    v.sort_by(|a, b| b.cmp(a));
+    v.sort_by_key(|p| p.0);
+    v.sort_by_cached_key(|p| p.1);
+    v.sort_unstable();
+    assert_eq!(common::allocs(), before);
+
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    // scratch 是 bump 里最近的分配，用完后指针直接退回
+    assert_eq!(bump.stats().allocated, 10_000 * 8);
+
+    v.sort_by_key(|p| std::cmp::Reverse(p.1));
+    v.par_sort();
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+}
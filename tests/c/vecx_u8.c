@@ -0,0 +1,80 @@
+// 通过 include/vecx.h 调用 Rust 的 Vecx<u8>，由 tests/ffi.rs 编译并运行
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "vecx.h"
+
+static void push_and_extend(void) {
+    VecxU8 *v = vecx_u8_new();
+    assert(vecx_u8_len(v) == 0);
+    assert(vecx_u8_capacity(v) == 0);
+    assert(vecx_u8_data(v) != NULL);
+
+    for (int i = 0; i < 300; i++) {
+        vecx_u8_push(v, (uint8_t)i);
+    }
+    const char *tail = "hello, vecx";
+    vecx_u8_extend(v, (const uint8_t *)tail, strlen(tail));
+    vecx_u8_extend(v, NULL, 0);
+    assert(vecx_u8_len(v) == 300 + strlen(tail));
+
+    const uint8_t *data = vecx_u8_data(v);
+    for (int i = 0; i < 300; i++) {
+        assert(data[i] == (uint8_t)i);
+    }
+    assert(memcmp(data + 300, tail, strlen(tail)) == 0);
+    vecx_u8_free(v);
+}
+
+static void reserve_then_fill_in_place(void) {
+    VecxU8 *v = vecx_u8_with_capacity(4);
+    assert(vecx_u8_capacity(v) >= 4);
+    vecx_u8_reserve(v, 1000);
+    size_t cap = vecx_u8_capacity(v);
+    assert(cap >= 1000);
+
+    // reserve 之后追加不会重新分配，数据指针保持不变
+    uint8_t *before = vecx_u8_data(v);
+    uint8_t chunk[100];
+    memset(chunk, 0xab, sizeof chunk);
+    for (int i = 0; i < 10; i++) {
+        vecx_u8_extend(v, chunk, sizeof chunk);
+    }
+    assert(vecx_u8_data(v) == before);
+    assert(vecx_u8_capacity(v) == cap);
+
+    // 已有的字节可以直接改写
+    vecx_u8_data(v)[0] = 1;
+    assert(vecx_u8_data(v)[0] == 1 && vecx_u8_data(v)[999] == 0xab);
+    vecx_u8_free(v);
+}
+
+static void raw_round_trip(void) {
+    VecxU8 *v = vecx_u8_new();
+    vecx_u8_extend(v, (const uint8_t *)"abcdef", 6);
+
+    size_t len = 0, cap = 0;
+    uint8_t *raw = vecx_u8_into_raw(v, &len, &cap);
+    assert(len == 6 && cap >= 6);
+    assert(memcmp(raw, "abcdef", 6) == 0);
+    raw[0] = 'A';
+
+    // 只保留前 3 个字节交回去
+    VecxU8 *back = vecx_u8_from_raw(raw, 3, cap);
+    assert(vecx_u8_len(back) == 3);
+    assert(memcmp(vecx_u8_data(back), "Abc", 3) == 0);
+    vecx_u8_push(back, 'z');
+    assert(memcmp(vecx_u8_data(back), "Abcz", 4) == 0);
+    vecx_u8_free(back);
+
+    vecx_u8_free(NULL);
+}
+
+int main(void) {
+    push_and_extend();
+    reserve_then_fill_in_place();
+    raw_round_trip();
+    puts("vecx_u8: ok");
+    return 0;
+}
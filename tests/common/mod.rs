@@ -0,0 +1,130 @@
+// 几个测试二进制共用的工具，每个二进制只用到其中一部分
+#![allow(dead_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::panic;
+use std::sync::{Arc, Mutex, Once};
+
+use test_demo::vecx::Vecx;
+
+// 统计当前线程对全局分配器的使用：`allocs` 是 alloc 和 realloc 的调用次数，
+// `live` 是还没有释放的块数。全局分配器只能在每个二进制里各自声明：
+//
+//     #[global_allocator]
+//     static GLOBAL: common::Counting = common::Counting;
+pub struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        let _ = LIVE.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = LIVE.try_with(|n| n.set(n.get() - 1));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+pub fn allocs() -> usize {
+    ALLOCS.with(Cell::get)
+}
+
+pub fn live() -> isize {
+    LIVE.with(Cell::get)
+}
+
+// 测试里的 panic 是故意的，不打印到输出里
+pub fn quiet_panics() {
+    static QUIET: Once = Once::new();
+    QUIET.call_once(|| panic::set_hook(Box::new(|_| {})));
+}
+
+// 第一次 panic 会初始化 panic 机制内部的状态，这些分配不归被测的代码管。
+// 先安静地 panic 一次，之后再开始计数
+pub fn warm_up_panics() {
+    quiet_panics();
+    let _ = panic::catch_unwind(|| panic!("warm up"));
+}
+
+// 按编号记录每个 `Tracked` 被 drop 的次数，用来检查元素既没有丢也没有被 drop 两次。
+// 可以跨线程共享
+#[derive(Clone, Default)]
+pub struct Drops(Arc<Mutex<HashMap<usize, usize>>>);
+
+impl Drops {
+    pub fn new() -> Self {
+        Drops::default()
+    }
+
+    pub fn track(&self, id: usize) -> Tracked {
+        self.keyed(id, 0)
+    }
+
+    // 带一个排序用的 key
+    pub fn keyed(&self, id: usize, key: u32) -> Tracked {
+        Tracked { id, key, drops: self.clone() }
+    }
+
+    // 所有元素加起来 drop 的次数
+    pub fn total(&self) -> usize {
+        self.0.lock().unwrap().values().sum()
+    }
+
+    // 编号为 `id` 的元素 drop 的次数
+    pub fn of(&self, id: usize) -> usize {
+        self.0.lock().unwrap().get(&id).copied().unwrap_or(0)
+    }
+
+    // 编号 `0..len` 的元素都恰好 drop 了一次，没有别的 drop
+    pub fn each_once(&self, len: usize) -> bool {
+        let counts = self.0.lock().unwrap();
+        counts.len() == len && (0..len).all(|id| counts.get(&id) == Some(&1))
+    }
+
+    // 除了自己以外还有多少个句柄，也就是还活着的 `Tracked` 和别的 `Drops` 克隆
+    pub fn others(&self) -> usize {
+        Arc::strong_count(&self.0) - 1
+    }
+}
+
+// drop 时给自己编号对应的计数加一；克隆出来的元素编号相同
+pub struct Tracked {
+    pub id: usize,
+    pub key: u32,
+    drops: Drops,
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        self.drops.keyed(self.id, self.key)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        *self.drops.0.lock().unwrap().entry(self.id).or_insert(0) += 1;
+    }
+}
+
+// 装着 `0..len` 的向量，容量正好是 `cap`
+pub fn numbers<T: From<u32>>(len: u32, cap: usize) -> Vecx<T> {
+    let mut v = Vecx::with_capacity(cap);
+    for i in 0..len {
+        v.push(T::from(i));
+    }
+    v
+}
@@ -0,0 +1,223 @@
+// 这些用例都很小，可以直接用 `cargo +nightly miri test --test doubly_linked_list` 检查未定义行为
+use std::cell::Cell;
+
+use test_demo::doubly_linked_list::DoublyLinkedList;
+
+fn list_from(elems: &[i32]) -> DoublyLinkedList<i32> {
+    elems.iter().copied().collect()
+}
+
+fn to_vec(list: &DoublyLinkedList<i32>) -> Vec<i32> {
+    list.iter().copied().collect()
+}
+
+#[test]
+fn push_pop_both_ends() {
+    let mut list = DoublyLinkedList::new();
+    assert_eq!(list.pop_front(), None);
+    assert_eq!(list.pop_back(), None);
+
+    list.push_front(2);
+    list.push_front(1);
+    list.push_back(3);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.front(), Some(&1));
+    assert_eq!(list.back(), Some(&3));
+
+    *list.front_mut().unwrap() = 10;
+    *list.back_mut().unwrap() = 30;
+    assert_eq!(list.pop_front(), Some(10));
+    assert_eq!(list.pop_back(), Some(30));
+    assert_eq!(list.pop_back(), Some(2));
+    assert!(list.is_empty());
+    assert_eq!(list.front(), None);
+}
+
+#[test]
+fn double_ended_iterators() {
+    let mut list = list_from(&[1, 2, 3, 4, 5]);
+
+    let mut iter = list.iter();
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.next(), Some(&1));
+    assert_eq!(iter.next_back(), Some(&5));
+    assert_eq!(iter.next(), Some(&2));
+    assert_eq!(iter.next_back(), Some(&4));
+    assert_eq!(iter.next(), Some(&3));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    for elem in list.iter_mut().rev() {
+        *elem *= 10;
+    }
+    assert_eq!(to_vec(&list), [10, 20, 30, 40, 50]);
+
+    let mut into = list.into_iter();
+    assert_eq!(into.next_back(), Some(50));
+    assert_eq!(into.next(), Some(10));
+    assert_eq!(into.collect::<Vec<_>>(), [20, 30, 40]);
+}
+
+#[test]
+fn cursor_moves_through_ghost() {
+    let mut list = list_from(&[1, 2, 3]);
+    let mut cursor = list.cursor_mut();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.peek_next(), Some(&mut 1));
+    assert_eq!(cursor.peek_prev(), Some(&mut 3));
+
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&mut 1));
+    assert_eq!(cursor.index(), Some(0));
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&mut 3));
+    assert_eq!(cursor.index(), Some(2));
+    cursor.move_next();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.index(), None);
+    cursor.move_prev();
+    assert_eq!(cursor.current(), Some(&mut 3));
+    cursor.move_prev();
+    assert_eq!(cursor.peek_prev(), Some(&mut 1));
+    assert_eq!(cursor.peek_next(), Some(&mut 3));
+
+    let mut empty: DoublyLinkedList<i32> = DoublyLinkedList::new();
+    let mut cursor = empty.cursor_mut();
+    cursor.move_next();
+    cursor.move_prev();
+    assert_eq!(cursor.current(), None);
+}
+
+#[test]
+fn cursor_insert_and_remove() {
+    let mut list = list_from(&[1, 3, 5]);
+    let mut cursor = list.cursor_front_mut();
+    cursor.insert_after(2);
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&mut 3));
+    cursor.insert_before(25);
+    assert_eq!(cursor.index(), Some(3));
+    cursor.insert_after(4);
+
+    assert_eq!(cursor.remove_current(), Some(3));
+    assert_eq!(cursor.current(), Some(&mut 4));
+    cursor.move_prev();
+    assert_eq!(cursor.remove_current(), Some(25));
+    assert_eq!(to_vec(&list), [1, 2, 4, 5]);
+    assert_eq!(list.len(), 4);
+
+    // 删除尾部后游标落在幽灵位置
+    let mut cursor = list.cursor_back_mut();
+    assert_eq!(cursor.remove_current(), Some(5));
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.remove_current(), None);
+    cursor.insert_after(0);
+    cursor.insert_before(9);
+    assert_eq!(to_vec(&list), [0, 1, 2, 4, 9]);
+    assert_eq!(list.back(), Some(&9));
+    assert_eq!(list.front(), Some(&0));
+
+    let mut cursor = list.cursor_front_mut();
+    while cursor.remove_current().is_some() {}
+    assert!(list.is_empty());
+    assert_eq!(list.back(), None);
+}
+
+#[test]
+fn cursor_splice() {
+    let mut list = list_from(&[1, 2, 6]);
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    cursor.splice_after(list_from(&[3, 4, 5]));
+    assert_eq!(cursor.current(), Some(&mut 2));
+    cursor.splice_before(list_from(&[]));
+    cursor.splice_after(DoublyLinkedList::new());
+    cursor.move_prev();
+    cursor.splice_before(list_from(&[-1, 0]));
+    assert_eq!(cursor.index(), Some(2));
+    assert_eq!(to_vec(&list), [-1, 0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(list.len(), 8);
+
+    let mut cursor = list.cursor_mut();
+    cursor.splice_after(list_from(&[-2]));
+    cursor.splice_before(list_from(&[7]));
+    assert_eq!(to_vec(&list), [-2, -1, 0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(list.iter().next_back(), Some(&7));
+}
+
+#[test]
+fn cursor_split() {
+    let mut list = list_from(&[1, 2, 3, 4, 5]);
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    let tail = cursor.split_after();
+    assert_eq!(to_vec(&tail), [3, 4, 5]);
+    assert_eq!(tail.back(), Some(&5));
+    assert_eq!(to_vec(&list), [1, 2]);
+    assert_eq!(list.back(), Some(&2));
+
+    let mut list = list_from(&[1, 2, 3, 4, 5]);
+    let mut cursor = list.cursor_back_mut();
+    cursor.move_prev();
+    let head = cursor.split_before();
+    assert_eq!(cursor.index(), Some(0));
+    assert_eq!(to_vec(&head), [1, 2, 3]);
+    assert_eq!(to_vec(&list), [4, 5]);
+    assert_eq!(list.len(), 2);
+
+    let mut cursor = list.cursor_back_mut();
+    assert!(cursor.split_after().is_empty());
+    let mut cursor = list.cursor_mut();
+    assert_eq!(to_vec(&cursor.split_after()), [4, 5]);
+    assert!(list.is_empty());
+}
+
+#[test]
+fn drops_every_element_once() {
+    struct Counted<'a>(&'a Cell<usize>);
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let drops = Cell::new(0);
+    let mut list: DoublyLinkedList<_> = (0..6).map(|_| Counted(&drops)).collect();
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    drop(cursor.remove_current());
+    let tail = cursor.split_after();
+    assert_eq!(drops.get(), 1);
+    drop(tail);
+    assert_eq!(drops.get(), 4);
+
+    let mut iter = list.into_iter();
+    drop(iter.next());
+    drop(iter);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn clone_eq_debug() {
+    let list = list_from(&[1, 2, 3]);
+    let copy = list.clone();
+    assert_eq!(list, copy);
+    assert_ne!(list, list_from(&[1, 2]));
+    assert_eq!(format!("{:?}", copy), "[1, 2, 3]");
+}
+
+// 只要能编译通过就说明 DoublyLinkedList 及其迭代器在 T 上是协变的
+#[allow(dead_code)]
+fn covariance<'a>(
+    list: DoublyLinkedList<&'static str>,
+    iter: test_demo::doubly_linked_list::Iter<'a, &'static str>,
+    into: test_demo::doubly_linked_list::IntoIter<&'static str>,
+) -> (
+    DoublyLinkedList<&'a str>,
+    test_demo::doubly_linked_list::Iter<'a, &'a str>,
+    test_demo::doubly_linked_list::IntoIter<&'a str>,
+) {
+    (list, iter, into)
+}
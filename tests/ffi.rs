@@ -0,0 +1,48 @@
+// 用系统的 C 编译器编译 tests/c/vecx_u8.c，链接 cdylib 后运行；只在 Linux 上跑
+#![cfg(target_os = "linux")]
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+// cargo test 编出来的 cdylib 和测试可执行文件一样在 target/<profile>/deps 下，
+// cargo build 之后还会复制一份到上一级目录
+fn find_cdylib() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    [deps, deps.parent().unwrap()]
+        .iter()
+        .map(|dir| dir.join("libtest_demo.so"))
+        .find(|lib| lib.exists())
+        .expect("cdylib libtest_demo.so not found")
+}
+
+#[test]
+fn c_program_uses_vecx_u8() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let lib = find_cdylib();
+    let lib_dir = lib.parent().unwrap();
+
+    let out = lib_dir.join("vecx_u8_c_test");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests/c/vecx_u8.c"))
+        .arg(&lib)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling the C test failed");
+
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "vecx_u8: ok\n");
+}
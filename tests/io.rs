@@ -0,0 +1,318 @@
+#![cfg_attr(read_buf, feature(read_buf, core_io_borrowed_buf))]
+
+use std::io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use test_demo::vecx::io::{read_to_end_into, VecxBufWriter, VecxReader};
+use test_demo::vecx::Vecx;
+
+fn vecx_of(bytes: &[u8]) -> Vecx<u8> {
+    let mut v = Vecx::new();
+    v.extend_from_slice(bytes);
+    v
+}
+
+// 按脚本依次返回：Some(n) 最多读 n 个字节，None 返回一个错误
+struct Scripted<'a> {
+    data: &'a [u8],
+    script: Vec<Option<usize>>,
+    error: ErrorKind,
+}
+
+impl Read for Scripted<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let step = if self.script.is_empty() { Some(usize::MAX) } else { self.script.remove(0) };
+        match step {
+            None => Err(io::Error::new(self.error, "scripted error")),
+            Some(max) => {
+                let n = max.min(buf.len()).min(self.data.len());
+                buf[..n].copy_from_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                Ok(n)
+            }
+        }
+    }
+}
+
+#[test]
+fn read_to_end_appends_after_existing_data() {
+    let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    let mut buf = vecx_of(b"head");
+    let n = read_to_end_into(&mut &data[..], &mut buf).unwrap();
+    assert_eq!(n, data.len());
+    assert_eq!(&buf[..4], b"head");
+    assert_eq!(&buf[4..], &data[..]);
+
+    // 容量正好用完并且已经到 EOF：探测之后直接返回，不扩容
+    let mut exact = Vecx::with_capacity(4);
+    exact.extend_from_slice(b"abcd");
+    assert_eq!(read_to_end_into(&mut io::empty(), &mut exact).unwrap(), 0);
+    assert_eq!(exact.capacity(), 4);
+}
+
+#[test]
+fn read_to_end_retries_interrupted() {
+    let data = b"interrupted reads are retried";
+    let mut reader = Scripted {
+        data,
+        script: vec![None, Some(3), None, None, Some(5), Some(0), None],
+        error: ErrorKind::Interrupted,
+    };
+    // 第一次 Some(0) 就是 EOF
+    let mut buf = Vecx::new();
+    assert_eq!(read_to_end_into(&mut reader, &mut buf).unwrap(), 8);
+    assert_eq!(&buf[..], &data[..8]);
+
+    // 容量用完时的探测读也会重试
+    let mut reader = Scripted { data, script: vec![None, None, Some(1)], error: ErrorKind::Interrupted };
+    let mut buf = Vecx::new();
+    assert_eq!(read_to_end_into(&mut reader, &mut buf).unwrap(), data.len());
+    assert_eq!(&buf[..], &data[..]);
+}
+
+// 每次把整个缓冲区涂成 0xAA，但只报告读到了 `step` 个字节，记下每次看到的缓冲区
+#[cfg(not(read_buf))]
+struct Scribbler {
+    step: usize,
+    left: usize,
+    seen: Vec<Vec<u8>>,
+}
+
+#[cfg(not(read_buf))]
+impl Read for Scribbler {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.seen.push(buf.to_vec());
+        let n = self.step.min(self.left).min(buf.len());
+        buf.fill(0xAA);
+        self.left -= n;
+        Ok(n)
+    }
+}
+
+// stable 上每个字节只在第一次交给 reader 之前清零，之后直接复用
+#[cfg(not(read_buf))]
+#[test]
+fn read_to_end_zeroes_each_byte_once() {
+    let mut reader = Scribbler { step: 4, left: 8, seen: Vec::new() };
+    let mut buf = Vecx::with_capacity(16);
+    assert_eq!(read_to_end_into(&mut reader, &mut buf).unwrap(), 8);
+    assert_eq!(&buf[..], [0xAA; 8]);
+
+    assert_eq!(reader.seen[0], [0; 16]);
+    // 第二次看到的是上一轮涂过、没有报告为读到的 12 个字节，没有再清零
+    assert_eq!(reader.seen[1], [0xAA; 12]);
+    assert_eq!(reader.seen[2], [0xAA; 8]);
+}
+
+// 只实现了 `read_buf` 的 reader：`read` 一旦被调用就说明空余容量被清零后才交出去
+#[cfg(read_buf)]
+struct BufOnly<'a> {
+    data: &'a [u8],
+    step: usize,
+}
+
+#[cfg(read_buf)]
+impl Read for BufOnly<'_> {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        panic!("read_to_end_into should read through read_buf");
+    }
+
+    fn read_buf(&mut self, mut cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        let n = self.step.min(self.data.len()).min(cursor.capacity());
+        cursor.append(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(())
+    }
+}
+
+#[cfg(read_buf)]
+#[test]
+fn read_to_end_reads_into_uninitialized_capacity() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut buf = Vecx::with_capacity(1024);
+    buf.extend_from_slice(b"x");
+    let n = read_to_end_into(&mut BufOnly { data: &data, step: 10 }, &mut buf).unwrap();
+    assert_eq!(n, 256);
+    assert_eq!(&buf[1..], &data[..]);
+    assert_eq!(buf.capacity(), 1024);
+}
+
+#[test]
+fn read_to_end_keeps_data_read_before_an_error() {
+    let data = b"partial data then failure";
+    let mut reader = Scripted { data, script: vec![Some(7), Some(5), None], error: ErrorKind::ConnectionReset };
+    let mut buf = vecx_of(b">");
+    let err = read_to_end_into(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+    assert_eq!(&buf[..], b">partial data");
+
+    // 接着读完剩下的
+    read_to_end_into(&mut reader, &mut buf).unwrap();
+    assert_eq!(&buf[1..], &data[..]);
+}
+
+#[test]
+fn reader_reads_and_seeks_like_cursor() {
+    let mut r = VecxReader::new(vecx_of(b"hello world"));
+    let mut word = [0u8; 5];
+    r.read_exact(&mut word).unwrap();
+    assert_eq!(&word, b"hello");
+    assert_eq!(r.position(), 5);
+
+    let mut line = String::new();
+    r.read_line(&mut line).unwrap();
+    assert_eq!(line, " world");
+
+    assert_eq!(r.seek(SeekFrom::End(-5)).unwrap(), 6);
+    assert_eq!(r.fill_buf().unwrap(), b"world");
+    r.consume(2);
+    assert_eq!(r.seek(SeekFrom::Current(-1)).unwrap(), 7);
+    assert_eq!(r.remaining_slice(), b"orld");
+
+    // 数据不够时 read_exact 失败，并把位置移到末尾
+    let mut big = [0u8; 10];
+    assert_eq!(r.read_exact(&mut big).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(r.position(), 11);
+}
+
+#[test]
+fn reader_seek_past_the_end() {
+    let mut r = VecxReader::new(vecx_of(b"abc"));
+    assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
+    assert_eq!(r.seek(SeekFrom::End(5)).unwrap(), 8);
+    assert_eq!(r.remaining_slice(), b"");
+    let mut buf = [0u8; 4];
+    assert_eq!(r.read(&mut buf).unwrap(), 0);
+    assert_eq!(r.fill_buf().unwrap(), b"");
+    assert_eq!(r.stream_position().unwrap(), 8);
+
+    // 之后数据变长，从原来的位置继续读
+    r.get_mut().extend_from_slice(b"defghijk");
+    assert_eq!(r.remaining_slice(), b"ijk");
+}
+
+#[test]
+fn reader_seek_to_negative_position_fails() {
+    let mut r = VecxReader::new(vecx_of(b"abc"));
+    r.set_position(2);
+    for style in [SeekFrom::Current(-3), SeekFrom::End(-4), SeekFrom::Current(i64::MIN)] {
+        let err = r.seek(style).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        // 失败的 seek 不改变位置
+        assert_eq!(r.position(), 2);
+    }
+    r.set_position(u64::MAX);
+    assert_eq!(r.seek(SeekFrom::Current(1)).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(r.seek(SeekFrom::Current(-1)).unwrap(), u64::MAX - 1);
+    assert_eq!(r.into_inner().len(), 3);
+}
+
+// 每次最多接受 `max` 个字节，总共最多接受 `budget` 个（None 表示不限），用完之后返回 Ok(0)；
+// `fail` 为 Some 时返回那个错误
+#[derive(Debug, Default)]
+struct Sink {
+    data: Vec<u8>,
+    max: usize,
+    budget: Option<usize>,
+    fail: Option<ErrorKind>,
+    writes: usize,
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        if let Some(kind) = self.fail {
+            return Err(io::Error::new(kind, "sink failed"));
+        }
+        let n = self.max.min(buf.len()).min(self.budget.unwrap_or(usize::MAX));
+        if let Some(budget) = &mut self.budget {
+            *budget -= n;
+        }
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn buf_writer_batches_small_writes() {
+    let mut w = VecxBufWriter::with_capacity(16, Sink { max: usize::MAX, ..Sink::default() });
+    for _ in 0..3 {
+        w.write_all(b"abcd").unwrap();
+    }
+    assert_eq!(w.get_ref().writes, 0);
+    assert_eq!(w.buffer(), b"abcdabcdabcd");
+    // 放不下了，先写出缓冲区
+    w.write_all(b"efghij").unwrap();
+    assert_eq!(w.get_ref().writes, 1);
+    assert_eq!(w.buffer(), b"efghij");
+    // 比缓冲区大的写入直接交给底层
+    w.write_all(&[b'x'; 32]).unwrap();
+    assert_eq!(w.buffer(), b"");
+    let sink = w.into_inner().unwrap();
+    assert_eq!(sink.data.len(), 12 + 6 + 32);
+}
+
+#[test]
+fn buf_writer_handles_partial_writes() {
+    let mut w = VecxBufWriter::with_capacity(8, Sink { max: 3, ..Sink::default() });
+    w.write_all(b"0123456").unwrap();
+    w.flush().unwrap();
+    // 每次只写出 3 个字节，flush 一直写到缓冲区空为止
+    assert_eq!(w.get_ref().writes, 3);
+    assert_eq!(w.get_ref().data, b"0123456");
+    assert!(w.buffer().is_empty());
+}
+
+#[test]
+fn buf_writer_write_zero_keeps_unwritten_data() {
+    let mut w = VecxBufWriter::with_capacity(8, Sink { max: 3, budget: Some(0), ..Sink::default() });
+    w.write_all(b"abcdef").unwrap();
+    // 底层返回 Ok(0)，flush 报 WriteZero 而不是一直重试
+    assert_eq!(w.flush().unwrap_err().kind(), ErrorKind::WriteZero);
+    assert_eq!(w.buffer(), b"abcdef");
+
+    // 写出 4 个字节之后又不再接受：没写出去的数据挪到缓冲区开头
+    w.get_mut().budget = Some(4);
+    w.write_all(b"gh").unwrap();
+    assert_eq!(w.write_all(b"i").unwrap_err().kind(), ErrorKind::WriteZero);
+    assert_eq!(w.get_ref().data, b"abcd");
+    assert_eq!(w.buffer(), b"efgh");
+
+    w.get_mut().budget = None;
+    w.write_all(b"i").unwrap();
+    w.flush().unwrap();
+    assert_eq!(w.get_ref().data, b"abcdefghi");
+    assert!(w.buffer().is_empty());
+}
+
+#[test]
+fn buf_writer_into_inner_error_returns_the_writer() {
+    let sink = Sink { max: 3, fail: Some(ErrorKind::BrokenPipe), ..Sink::default() };
+    let mut w = VecxBufWriter::with_capacity(16, sink);
+    w.write_all(b"abcdef").unwrap();
+    let err = w.into_inner().unwrap_err();
+    assert_eq!(err.error().kind(), ErrorKind::BrokenPipe);
+    assert!(err.to_string().contains("sink failed"));
+
+    let (error, mut w) = err.into_parts();
+    assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    // 缓冲的数据没有丢，底层恢复之后可以继续写出
+    assert_eq!(w.buffer(), b"abcdef");
+    w.get_mut().fail = None;
+    let sink = w.into_inner().unwrap();
+    assert_eq!(sink.data, b"abcdef");
+    assert_eq!(sink.writes, 3);
+}
+
+#[test]
+fn buf_writer_drop_flushes() {
+    let mut out = Vec::new();
+    {
+        let mut w = VecxBufWriter::new(&mut out);
+        write!(w, "{}-{}", 1, 2).unwrap();
+    }
+    assert_eq!(out, b"1-2");
+}
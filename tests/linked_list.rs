@@ -0,0 +1,109 @@
+use test_demo::LinkedList::LinkedList;
+
+// 一千万个节点，递归实现的 drop、clone、eq 会栈溢出
+const LEN: usize = 10_000_000;
+
+fn long_list(n: usize) -> LinkedList<usize> {
+    let mut list = LinkedList::new();
+    for i in 0..n {
+        list.push(i);
+    }
+    list
+}
+
+#[test]
+fn drop_long_list() {
+    drop(long_list(LEN));
+}
+
+#[test]
+fn clone_and_eq_long_list() {
+    let list = long_list(LEN);
+    let mut copy = list.clone();
+    assert!(list == copy);
+
+    *copy.iter_mut().last().unwrap() += 1;
+    assert!(list != copy);
+}
+
+#[test]
+fn eq_different_lengths() {
+    let mut a = long_list(3);
+    let b = long_list(2);
+    assert!(a != b);
+    assert!(b != a);
+    assert!(a == long_list(3));
+
+    // 长度相同、元素不同
+    a.iter_mut().for_each(|x| *x *= 10);
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), [20, 10, 0]);
+    assert!(a != long_list(3));
+}
+
+#[test]
+fn stack_operations() {
+    let mut list = LinkedList::new();
+    assert!(list.is_empty());
+    assert_eq!(list.pop(), None);
+
+    list.push(1);
+    list.push(2);
+    list.push(3);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.peek(), Some(&3));
+    *list.peek_mut().unwrap() = 30;
+    assert_eq!(list.pop(), Some(30));
+    assert_eq!(list.pop(), Some(2));
+    assert_eq!(list.len(), 1);
+    assert!(list.contains(&1));
+    assert!(!list.contains(&2));
+}
+
+#[test]
+fn collect_extend_and_append() {
+    let mut list: LinkedList<i32> = (1..=3).collect();
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+    list.extend([4, 5]);
+    let mut other: LinkedList<i32> = (6..=7).collect();
+    list.append(&mut other);
+    assert!(other.is_empty());
+    assert_eq!(list.len(), 7);
+    assert_eq!(format!("{:?}", list), "[1, 2, 3, 4, 5, 6, 7]");
+
+    let tail = list.split_off(5);
+    assert_eq!(tail.into_iter().collect::<Vec<_>>(), [6, 7]);
+    assert_eq!(list.len(), 5);
+    assert!(list.split_off(0).len() == 5 && list.is_empty());
+}
+
+#[test]
+fn reverse_and_retain() {
+    let mut list: LinkedList<i32> = (1..=6).collect();
+    list.reverse();
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [6, 5, 4, 3, 2, 1]);
+
+    list.retain(|x| x % 2 == 0);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.clone().into_iter().collect::<Vec<_>>(), [6, 4, 2]);
+}
+
+#[test]
+fn iterators_know_their_length() {
+    let mut list: LinkedList<i32> = (1..=4).collect();
+    let mut iter = list.iter();
+    assert_eq!(iter.len(), 4);
+    iter.next();
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.by_ref().count(), 3);
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+
+    let mut iter = list.iter_mut();
+    iter.next();
+    assert_eq!(iter.len(), 3);
+
+    let mut iter = list.into_iter();
+    iter.next();
+    assert_eq!(iter.len(), 3);
+}
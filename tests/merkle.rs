@@ -0,0 +1,177 @@
+use test_demo::merkle::{leaf_hash, node_hash, Hash, MerkleTree};
+
+fn hex(s: &str) -> Hash {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+// RFC 6962（Certificate Transparency）的测试向量：叶子前缀 0x00、内部节点前缀 0x01，
+// 奇数个节点时把最后一个提升上去和按 2 的幂切分得到的树相同
+const LEAVES: [&[u8]; 8] = [
+    b"",
+    b"\x00",
+    b"\x10",
+    b"\x20\x21",
+    b"\x30\x31",
+    b"\x40\x41\x42\x43",
+    b"\x50\x51\x52\x53\x54\x55\x56\x57",
+    b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+];
+
+const ROOTS: [&str; 9] = [
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+];
+
+fn items(n: usize) -> Vec<Vec<u8>> {
+    (0..n).map(|i| format!("leaf {i}").into_bytes()).collect()
+}
+
+#[test]
+fn root_matches_known_vectors() {
+    for (n, root) in ROOTS.iter().enumerate() {
+        let tree = MerkleTree::from_leaves(&LEAVES[..n]);
+        assert_eq!(tree.len(), n);
+        assert_eq!(tree.is_empty(), n == 0);
+        assert_eq!(tree.root(), hex(root), "{n} leaves");
+    }
+}
+
+#[test]
+fn odd_leaf_counts_promote_the_last_node() {
+    let [a, b, c, d, e] = [b"a", b"b", b"c", b"d", b"e"].map(|x| leaf_hash(x));
+
+    let tree = MerkleTree::from_leaves(&[b"a", b"b", b"c"]);
+    assert_eq!(tree.root(), node_hash(&node_hash(&a, &b), &c));
+
+    // 最后一个叶子连续两层没有兄弟
+    let tree = MerkleTree::from_leaves(&[b"a", b"b", b"c", b"d", b"e"]);
+    let abcd = node_hash(&node_hash(&a, &b), &node_hash(&c, &d));
+    assert_eq!(tree.root(), node_hash(&abcd, &e));
+    let proof = tree.proof(4).unwrap();
+    assert_eq!(proof.siblings(), [abcd]);
+    assert!(proof.verify(&tree.root(), b"e"));
+
+    let tree = MerkleTree::from_leaves(&[b"x"]);
+    assert_eq!(tree.root(), leaf_hash(b"x"));
+    assert!(tree.proof(0).unwrap().siblings().is_empty());
+    assert!(tree.proof(0).unwrap().verify(&tree.root(), b"x"));
+}
+
+#[test]
+fn every_proof_verifies() {
+    for n in 1..=33 {
+        let data = items(n);
+        let tree = MerkleTree::from_leaves(&data);
+        let root = tree.root();
+        for (i, item) in data.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            assert_eq!(proof.index(), i);
+            assert!(proof.siblings().len() <= (n as f64).log2().ceil() as usize);
+            assert!(proof.verify(&root, item), "{n} leaves, index {i}");
+            assert!(proof.verify_hash(&root, *tree.leaf(i).unwrap()));
+        }
+        assert!(tree.proof(n).is_none());
+        assert!(tree.leaf(n).is_none());
+    }
+    assert!(MerkleTree::from_leaves::<&[u8]>(&[]).proof(0).is_none());
+}
+
+#[test]
+fn tampered_leaf_is_rejected() {
+    for n in [2, 3, 7, 8, 9] {
+        let data = items(n);
+        let tree = MerkleTree::from_leaves(&data);
+        let root = tree.root();
+        for (i, item) in data.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            // 改掉一位
+            let mut tampered = item.clone();
+            tampered[0] ^= 1;
+            assert!(!proof.verify(&root, &tampered));
+            assert!(!proof.verify(&root, &item[1..]));
+            // 别的叶子的数据也不能用这个证明通过
+            let other = &data[(i + 1) % n];
+            assert!(!proof.verify(&root, other));
+            // 根不对
+            let mut bad_root = root;
+            bad_root[31] ^= 0x80;
+            assert!(!proof.verify(&bad_root, item));
+            // 不能把内部节点当成叶子数据
+            assert!(!proof.verify_hash(&root, root));
+        }
+    }
+}
+
+#[test]
+fn proof_from_a_different_tree_is_rejected() {
+    let small = MerkleTree::from_leaves(&items(5));
+    let large = MerkleTree::from_leaves(&items(6));
+    let proof = small.proof(2).unwrap();
+    assert!(proof.verify(&small.root(), b"leaf 2"));
+    assert!(!proof.verify(&large.root(), b"leaf 2"));
+}
+
+#[test]
+fn domain_separation_between_leaves_and_nodes() {
+    // 两个叶子哈希拼起来作为一个叶子的数据，得到的根不能和两个叶子的树相同
+    let pair = MerkleTree::from_leaves(&[b"a", b"b"]);
+    let mut joined = leaf_hash(b"a").to_vec();
+    joined.extend_from_slice(&leaf_hash(b"b"));
+    let single = MerkleTree::from_leaves(&[joined]);
+    assert_ne!(pair.root(), single.root());
+}
+
+#[test]
+fn update_recomputes_the_path() {
+    for n in [1, 2, 5, 8, 13] {
+        let mut data = items(n);
+        let mut tree = MerkleTree::from_leaves(&data);
+        for i in 0..n {
+            let old_root = tree.root();
+            let j = (i + 1) % n;
+            let neighbour = tree.proof(j).unwrap();
+            data[i] = format!("changed {i}").into_bytes();
+            tree.update(i, &data[i]);
+
+            // 和重新建树的结果一致
+            assert_eq!(tree.root(), MerkleTree::from_leaves(&data).root(), "{n} leaves, index {i}");
+            assert!(tree.proof(i).unwrap().verify(&tree.root(), &data[i]));
+            // 其他叶子旧的证明里包含了被改的叶子，在新根下失效
+            if n > 1 {
+                assert_ne!(tree.root(), old_root);
+                assert!(neighbour.verify(&old_root, &data[j]));
+                assert!(!neighbour.verify(&tree.root(), &data[j]));
+                assert!(tree.proof(j).unwrap().verify(&tree.root(), &data[j]));
+            }
+        }
+    }
+}
+
+#[test]
+fn chunks_are_leaves() {
+    let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let chunks: Vec<&[u8]> = data.chunks(64).collect();
+    let tree = MerkleTree::from_chunks(&data, 64);
+    assert_eq!(tree.len(), 16);
+    assert_eq!(tree.root(), MerkleTree::from_leaves(&chunks).root());
+    // 最后一块只有 40 字节
+    assert!(tree.proof(15).unwrap().verify(&tree.root(), &data[960..]));
+    assert!(MerkleTree::from_chunks(&[], 64).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be non-zero")]
+fn zero_chunk_size_panics() {
+    MerkleTree::from_chunks(b"data", 0);
+}
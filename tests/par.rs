@@ -0,0 +1,292 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use test_demo::vecx::par::Par;
+use test_demo::vecx::Vecx;
+
+mod common;
+
+use common::{quiet_panics, Drops, Tracked};
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(p) => p.downcast::<&str>().map(|s| s.to_string()).unwrap(),
+    }
+}
+
+#[test]
+fn map_keeps_order() {
+    for len in [0, 1, 2, 7, 64, 1000, 1001] {
+        let v: Vec<usize> = (0..len).collect();
+        for threads in [1, 2, 3, 4, 8, 2000] {
+            let out = Par::with_threads(threads).map(&v, |&x| format!("{x}"));
+            assert_eq!(out.len(), len);
+            assert!(out.iter().map(|s| s.parse::<usize>().unwrap()).eq(0..len), "len {len}, threads {threads}");
+        }
+    }
+}
+
+#[test]
+fn map_panic_drops_each_result_once() {
+    quiet_panics();
+    let len = 100;
+    let threads = 4;
+    let chunk = 25;
+    let v: Vec<usize> = (0..len).collect();
+    for panic_at in [0, 10, 24, 25, 60, 99] {
+        let drops = Drops::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Par::with_threads(threads).map(&v, |&i| {
+                if i == panic_at {
+                    panic!("map panicked at {i}");
+                }
+                drops.track(i)
+            })
+        }));
+        let Err(payload) = result else { panic!("map should propagate the worker panic") };
+        assert_eq!(panic_message(payload), format!("map panicked at {panic_at}"));
+
+        // panic 那块里已经写好的前缀和其他完整的块各 drop 一次，panic 之后的元素从没有创建过
+        let chunk_end = (panic_at / chunk + 1) * chunk;
+        for i in 0..len {
+            let expected = usize::from(!(panic_at..chunk_end).contains(&i));
+            assert_eq!(drops.of(i), expected, "panic at {panic_at}, element {i}");
+        }
+        assert_eq!(drops.others(), 0);
+    }
+}
+
+#[test]
+fn map_with_several_panics_reports_the_first_chunk() {
+    quiet_panics();
+    let v: Vec<usize> = (0..40).collect();
+    let drops = Drops::new();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        Par::with_threads(4).map(&v, |&i| {
+            // 第 2 块和第 1 块都会 panic，重新抛出的是块顺序上的第一个
+            if i == 25 || i == 15 {
+                panic!("panic at {i}");
+            }
+            drops.track(i)
+        })
+    }));
+    let Err(payload) = result else { panic!("map should panic") };
+    assert_eq!(panic_message(payload), "panic at 15");
+
+    let dropped = drops.total();
+    // 0..10、10..15、20..25、30..40
+    assert_eq!(dropped, 10 + 5 + 5 + 10);
+    assert!((0..40).all(|i| drops.of(i) <= 1));
+}
+
+#[test]
+fn map_results_are_dropped_with_the_vecx() {
+    let v: Vec<usize> = (0..50).collect();
+    let drops = Drops::new();
+    let out = Par::with_threads(3).map(&v, |&i| drops.track(i));
+    assert!(out.iter().map(|t| t.id).eq(0..50));
+    assert_eq!(drops.total(), 0);
+    drop(out);
+    assert!(drops.each_once(50));
+}
+
+#[test]
+fn for_each_mut_visits_every_element_once() {
+    for len in [0, 1, 5, 1000] {
+        for threads in [1, 3, 8] {
+            let mut v: Vec<usize> = (0..len).collect();
+            Par::with_threads(threads).for_each_mut(&mut v, |x| *x = *x * 2 + 1);
+            assert!(v.iter().copied().eq((0..len).map(|x| x * 2 + 1)));
+        }
+    }
+}
+
+#[test]
+fn for_each_mut_panic_waits_for_other_threads() {
+    quiet_panics();
+    let mut v: Vec<usize> = (0..400).collect();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        Par::with_threads(4).for_each_mut(&mut v, |x| {
+            if *x == 150 {
+                panic!("element {x}");
+            }
+            *x += 1000;
+        });
+    }));
+    let Err(payload) = result else { panic!("for_each_mut should propagate the panic") };
+    assert_eq!(panic_message(payload), "element 150");
+
+    // 出 panic 的那块停在 panic 的位置，其余的块在重新 panic 之前都处理完了
+    for (i, &x) in v.iter().enumerate() {
+        let done = !(150..200).contains(&i);
+        assert_eq!(x, if done { i + 1000 } else { i }, "element {i}");
+    }
+}
+
+#[test]
+fn reduce_combines_chunks_in_order() {
+    let words: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let expected: String = words.concat();
+    for threads in [1, 2, 3, 7, 200] {
+        // 字符串拼接满足结合律但不满足交换律，块的顺序错了结果就不对
+        let joined = Par::with_threads(threads).reduce(&words, String::new(), |a, b| a + &b);
+        assert_eq!(joined, expected);
+    }
+    let empty: [u64; 0] = [];
+    assert_eq!(Par::with_threads(4).reduce(&empty, 0, |a, b| a + b), 0);
+}
+
+#[test]
+fn reduce_propagates_panic() {
+    quiet_panics();
+    let v: Vec<u32> = (0..100).collect();
+    let result = panic::catch_unwind(|| {
+        Par::with_threads(4).reduce(&v, 0, |a, b| {
+            assert!(b != 77, "saw 77");
+            a + b
+        })
+    });
+    let Err(payload) = result else { panic!("reduce should propagate the panic") };
+    assert_eq!(panic_message(payload), "saw 77");
+}
+
+#[test]
+fn vecx_convenience_methods() {
+    let mut v: Vecx<u64> = Vecx::new();
+    v.extend_from_slice(&(1..=1000).collect::<Vec<_>>());
+    v.par_for_each_mut(|x| *x *= 2);
+    assert_eq!(v.par_reduce(0, |a, b| a + b), 1000 * 1001);
+    let squares = v.par_map(|&x| x * x);
+    assert!(squares.iter().copied().eq((1..=1000u64).map(|x| 4 * x * x)));
+}
+
+fn xorshift(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+#[test]
+fn sort_matches_std() {
+    let mut seed = 0x9e37_79b9_7f4a_7c15;
+    // 包括 len < threads，以及块数不是 2 的幂（最后一轮有落单的段）
+    for len in [0, 1, 2, 3, 5, 17, 100, 1000, 1001, 4099] {
+        let data: Vec<u32> = (0..len).map(|_| xorshift(&mut seed) as u32).collect();
+        let mut expected = data.clone();
+        expected.sort();
+        for threads in [1, 2, 3, 4, 5, 7, 8, 16] {
+            let par = Par::with_threads(threads);
+            let mut v = data.clone();
+            par.sort(&mut v);
+            assert_eq!(v, expected, "len {len}, threads {threads}");
+
+            let mut v = data.clone();
+            par.sort_by(&mut v, |a, b| b.cmp(a));
+            assert!(v.iter().rev().eq(expected.iter()), "len {len}, threads {threads}");
+        }
+    }
+
+    // 已经有序和逆序的输入
+    let mut v: Vec<u32> = (0..3000).collect();
+    Par::with_threads(6).sort(&mut v);
+    assert!(v.iter().copied().eq(0..3000));
+    v.reverse();
+    Par::with_threads(6).sort(&mut v);
+    assert!(v.iter().copied().eq(0..3000));
+}
+
+#[test]
+fn sort_is_stable() {
+    let mut seed = 42;
+    for threads in [2, 3, 4, 8] {
+        // 只有 8 种 key，相等的元素很多，并且分散在各个块里
+        let data: Vec<(u8, usize)> = (0..2000).map(|i| ((xorshift(&mut seed) % 8) as u8, i)).collect();
+        let mut expected = data.clone();
+        expected.sort_by_key(|p| p.0);
+
+        let mut v = data.clone();
+        Par::with_threads(threads).sort_by(&mut v, |a, b| a.0.cmp(&b.0));
+        assert_eq!(v, expected, "threads {threads}");
+
+        let mut v: Vecx<(u8, usize)> = Vecx::new();
+        v.extend_from_slice(&data);
+        v.par_sort_by(|a, b| a.0.cmp(&b.0));
+        assert!(v.iter().eq(expected.iter()));
+    }
+}
+
+#[test]
+fn sort_zero_sized() {
+    for threads in [1, 2, 4, 7] {
+        let mut units = [(); 100];
+        Par::with_threads(threads).sort(&mut units);
+        Par::with_threads(threads).sort_by(&mut units, |_, _| std::cmp::Ordering::Less);
+    }
+    let mut v: Vecx<()> = Vecx::new();
+    for _ in 0..1000 {
+        v.push(());
+    }
+    v.par_sort();
+    v.par_sort_by(|_, _| std::cmp::Ordering::Greater);
+    assert_eq!(v.len(), 1000);
+}
+
+#[test]
+fn vecx_par_sort_matches_std() {
+    let mut seed = 7;
+    let data: Vec<u64> = (0..5000).map(|_| xorshift(&mut seed) % 1000).collect();
+    let mut expected = data.clone();
+    expected.sort();
+    let mut v: Vecx<u64> = Vecx::new();
+    v.extend_from_slice(&data);
+    v.par_sort();
+    assert!(v.iter().eq(expected.iter()));
+}
+
+#[test]
+fn panicking_merge_drops_each_element_once() {
+    quiet_panics();
+    let len = 1000;
+    let threads = 4;
+    let chunk = len / threads;
+    let mut seed = 3;
+    let keys: Vec<u32> = (0..len).map(|_| xorshift(&mut seed) as u32 % 64).collect();
+
+    for panic_at in [1, 10, 300, 900] {
+        let drops = Drops::new();
+        let mut v: Vecx<Tracked> = Vecx::new();
+        for i in 0..len {
+            v.push(drops.track(i));
+        }
+
+        // 第一阶段只在块内比较，跨块的比较只会发生在两两归并的时候
+        let merges = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Par::with_threads(threads).sort_by(&mut v, |a, b| {
+                if a.id / chunk != b.id / chunk && merges.fetch_add(1, Ordering::SeqCst) + 1 == panic_at {
+                    panic!("merge panicked");
+                }
+                keys[a.id].cmp(&keys[b.id])
+            })
+        }));
+        let Err(payload) = result else { panic!("sort should propagate the comparator panic") };
+        assert_eq!(panic_message(payload), "merge panicked");
+
+        // 每个元素都还在切片里，没有被提前 drop
+        assert_eq!(drops.total(), 0);
+        let mut ids: Vec<usize> = v.iter().map(|t| t.id).collect();
+        ids.sort();
+        assert!(ids.into_iter().eq(0..len), "panic_at {panic_at}");
+
+        drop(v);
+        assert!(drops.each_once(len), "panic_at {panic_at}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "thread count must be non-zero")]
+fn zero_threads_is_rejected() {
+    Par::with_threads(0);
+}
@@ -0,0 +1,180 @@
+use std::thread;
+
+use test_demo::persistent::{PersistentList, PersistentTree};
+
+// 递归 drop 在这么小的栈上几千层就会溢出
+const SMALL_STACK: usize = 64 * 1024;
+
+fn on_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+    thread::Builder::new().stack_size(SMALL_STACK).spawn(f).unwrap().join().unwrap();
+}
+
+fn list_of(n: usize) -> PersistentList<usize> {
+    let mut list = PersistentList::new();
+    for i in 0..n {
+        list = list.push(i);
+    }
+    list
+}
+
+#[test]
+fn list_push_and_tail_keep_old_version() {
+    let empty = PersistentList::new();
+    let a = empty.push(1);
+    let b = a.push(2);
+    let c = a.push(3);
+
+    assert!(empty.is_empty());
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), [1]);
+    assert_eq!(b.iter().copied().collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(c.iter().copied().collect::<Vec<_>>(), [3, 1]);
+    assert_eq!(b.tail(), a);
+    assert_eq!(b.head(), Some(&2));
+    assert_eq!(b.len(), 2);
+    assert_eq!(a.tail().tail(), empty);
+    assert_eq!(format!("{:?}", b), "[2, 1]");
+}
+
+#[test]
+fn list_shares_suffix() {
+    let a = list_of(3);
+    let b = a.push(10);
+    let c = a.tail();
+    // 新旧版本指向同一批节点
+    assert!(a.iter().zip(b.iter().skip(1)).all(|(x, y)| std::ptr::eq(x, y)));
+    assert!(a.iter().skip(1).zip(c.iter()).all(|(x, y)| std::ptr::eq(x, y)));
+    assert!(std::ptr::eq(a.head().unwrap(), a.clone().head().unwrap()));
+}
+
+#[test]
+fn list_iter_order_and_len() {
+    let list = list_of(5);
+    let mut iter = list.iter();
+    assert_eq!(iter.len(), 5);
+    iter.next();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.copied().collect::<Vec<_>>(), [3, 2, 1, 0]);
+}
+
+#[test]
+fn list_long_drop_does_not_recurse() {
+    on_small_stack(|| {
+        drop(list_of(1_000_000));
+
+        // 两个版本共享一条很长的后缀，先后 drop
+        let base = list_of(1_000_000);
+        let a = base.push(1);
+        let b = base.push(2);
+        drop(base);
+        drop(a);
+        drop(b);
+    });
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn list_concurrent_drop_of_shared_suffix() {
+    use std::sync::{Arc, Barrier};
+
+    for _ in 0..20 {
+        let base = list_of(200_000);
+        let a = base.push(1);
+        let b = base.push(2);
+        drop(base);
+
+        let barrier = Arc::new(Barrier::new(2));
+        let handles: Vec<_> = [a, b]
+            .into_iter()
+            .map(|list| {
+                let barrier = barrier.clone();
+                thread::Builder::new()
+                    .stack_size(SMALL_STACK)
+                    .spawn(move || {
+                        barrier.wait();
+                        drop(list);
+                    })
+                    .unwrap()
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
+
+fn tree_of(keys: &[i32]) -> PersistentTree<i32, String> {
+    let mut tree = PersistentTree::new();
+    for &k in keys {
+        tree = tree.insert(k, k.to_string());
+    }
+    tree
+}
+
+fn keys(tree: &PersistentTree<i32, String>) -> Vec<i32> {
+    tree.iter().map(|(k, _)| *k).collect()
+}
+
+#[test]
+fn tree_insert_and_remove_keep_old_version() {
+    let v1 = tree_of(&[5, 3, 8, 1, 4, 7, 9]);
+    let v2 = v1.insert(6, "six".into());
+    let v3 = v2.insert(5, "five".into());
+    let v4 = v3.remove(&3);
+    let v5 = v4.remove(&5);
+
+    assert_eq!(keys(&v1), [1, 3, 4, 5, 7, 8, 9]);
+    assert_eq!(keys(&v2), [1, 3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(v2.get(&5).map(String::as_str), Some("5"));
+    assert_eq!(v3.get(&5).map(String::as_str), Some("five"));
+    assert_eq!(v3.len(), 8);
+    assert_eq!(keys(&v4), [1, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(keys(&v5), [1, 4, 6, 7, 8, 9]);
+    assert!(v3.contains_key(&3) && !v4.contains_key(&3));
+    assert!(!v5.contains_key(&5));
+    assert_eq!(v5.len(), 6);
+
+    // 删除不存在的 key 得到一个相同的版本
+    assert_eq!(keys(&v5.remove(&100)), keys(&v5));
+    assert_eq!(v5.remove(&100).len(), v5.len());
+}
+
+#[test]
+fn tree_shares_untouched_subtrees() {
+    let v1 = tree_of(&[5, 3, 8, 1, 4, 7, 9]);
+    // 插入到左子树，右子树（7、8、9）原样共享，根和左侧路径被复制
+    let v2 = v1.insert(2, "2".into());
+    assert!(std::ptr::eq(v1.get(&8).unwrap(), v2.get(&8).unwrap()));
+    assert!(std::ptr::eq(v1.get(&9).unwrap(), v2.get(&9).unwrap()));
+    assert!(std::ptr::eq(v1.get(&4).unwrap(), v2.get(&4).unwrap()));
+    assert!(!std::ptr::eq(v1.get(&5).unwrap(), v2.get(&5).unwrap()));
+    assert!(!std::ptr::eq(v1.get(&3).unwrap(), v2.get(&3).unwrap()));
+    assert!(!std::ptr::eq(v1.get(&1).unwrap(), v2.get(&1).unwrap()));
+
+    let v3 = v2.remove(&8);
+    assert!(std::ptr::eq(v2.get(&3).unwrap(), v3.get(&3).unwrap()));
+}
+
+#[test]
+fn tree_iter_in_key_order() {
+    let tree = tree_of(&[50, 20, 80, 10, 30, 70, 90, 60, 65, 25]);
+    let mut iter = tree.iter();
+    assert_eq!(iter.len(), 10);
+    assert_eq!(iter.next(), Some((&10, &"10".to_string())));
+    assert_eq!(iter.len(), 9);
+    assert_eq!(keys(&tree), [10, 20, 25, 30, 50, 60, 65, 70, 80, 90]);
+    assert_eq!(format!("{:?}", tree_of(&[2, 1])), r#"{1: "1", 2: "2"}"#);
+}
+
+#[test]
+fn tree_degenerate_drop_does_not_recurse() {
+    on_small_stack(|| {
+        // 升序插入得到一条只有右孩子的链；路径复制是 O(n²)，数量不能太大
+        let mut tree = PersistentTree::new();
+        for i in 0..5_000 {
+            tree = tree.insert(i, ());
+        }
+        let other = tree.insert(-1, ());
+        drop(tree);
+        drop(other);
+    });
+}
@@ -0,0 +1,179 @@
+use std::mem::{self, MaybeUninit};
+use std::rc::Rc;
+
+use test_demo::vecx::Vecx;
+
+mod common;
+
+use common::numbers;
+
+#[test]
+fn raw_parts_round_trip() {
+    let v = numbers::<u32>(5, 16);
+    let ptr = v.as_ptr();
+    let (raw, len, cap) = v.into_raw_parts();
+    assert_eq!((raw as *const u32, len, cap), (ptr, 5, 16));
+
+    // 像 C 代码那样直接写进空余容量
+    unsafe { raw.add(len).write(5) };
+    let v = unsafe { Vecx::from_raw_parts(raw, len + 1, cap) };
+    assert!(v.iter().copied().eq(0..6));
+    assert_eq!(v.capacity(), 16);
+
+    // 没有分配的空向量也能来回转换
+    let (raw, len, cap) = Vecx::<String>::new().into_raw_parts();
+    assert_eq!((len, cap), (0, 0));
+    let empty = unsafe { Vecx::from_raw_parts(raw, len, cap) };
+    assert!(empty.is_empty());
+
+    // 和 std 的 Vec 共用全局分配器，缓冲区可以直接互换
+    let mut std_vec = vec![String::from("a"), String::from("b")];
+    std_vec.reserve(3);
+    let (raw, len, cap) = (std_vec.as_mut_ptr(), std_vec.len(), std_vec.capacity());
+    mem::forget(std_vec);
+    let v = unsafe { Vecx::from_raw_parts(raw, len, cap) };
+    assert_eq!(&v[..], ["a", "b"]);
+}
+
+#[test]
+fn zst_raw_parts() {
+    let mut v = Vecx::new();
+    v.push(());
+    v.push(());
+    let (raw, len, cap) = v.into_raw_parts();
+    assert_eq!((len, cap), (2, usize::MAX));
+    // ZST 的容量参数会被忽略
+    let v = unsafe { Vecx::from_raw_parts(raw, len, 0) };
+    assert_eq!(v.len(), 2);
+    assert_eq!(v.capacity(), usize::MAX);
+}
+
+#[test]
+fn spare_capacity_and_set_len() {
+    let mut v: Vecx<u32> = Vecx::with_capacity(8);
+    v.push(0);
+    let spare = v.spare_capacity_mut();
+    assert_eq!(spare.len(), 7);
+    for (i, slot) in spare.iter_mut().take(4).enumerate() {
+        slot.write(i as u32 + 1);
+    }
+    unsafe { v.set_len(5) };
+    assert!(v.iter().copied().eq(0..5));
+    assert_eq!(v.spare_capacity_mut().len(), 3);
+
+    // 通过 as_mut_ptr 写入，效果相同
+    unsafe {
+        v.as_mut_ptr().add(5).write(5);
+        v.set_len(6);
+    }
+    assert!(v.iter().copied().eq(0..6));
+
+    // 缩短长度时被截掉的元素由调用者负责，这里是不需要 drop 的整数
+    unsafe { v.set_len(2) };
+    assert_eq!(&v[..], [0, 1]);
+
+    let mut strings: Vecx<String> = Vecx::with_capacity(2);
+    let spare: &mut [MaybeUninit<String>] = strings.spare_capacity_mut();
+    spare[0].write(String::from("x"));
+    unsafe { strings.set_len(1) };
+    assert_eq!(&strings[..], ["x"]);
+}
+
+#[test]
+fn push_within_capacity_when_full() {
+    let mut v = Vecx::with_capacity(2);
+    assert_eq!(v.push_within_capacity(1), Ok(()));
+    assert_eq!(v.push_within_capacity(2), Ok(()));
+    let ptr = v.as_ptr();
+    // 容量已满，原样返回，不扩容
+    assert_eq!(v.push_within_capacity(3), Err(3));
+    assert_eq!(v.capacity(), 2);
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(&v[..], [1, 2]);
+
+    let mut empty = Vecx::new();
+    let rc = Rc::new(());
+    let back = empty.push_within_capacity(rc.clone()).unwrap_err();
+    // 返回的就是传进去的那个值，没有被 drop 也没有被复制
+    assert!(Rc::ptr_eq(&back, &rc));
+    assert_eq!(Rc::strong_count(&rc), 2);
+
+    let mut units = Vecx::new();
+    assert_eq!(units.push_within_capacity(()), Ok(()));
+}
+
+#[test]
+fn leak_gives_a_static_slice() {
+    let leaked: &'static mut [u32] = numbers::<u32>(4, 4).leak();
+    leaked[0] = 10;
+    assert_eq!(leaked, [10, 1, 2, 3]);
+    // 交还给 Vecx，让测试不留下泄漏。切片的指针只覆盖 len 个元素，所以这里选了 len == cap
+    let v = unsafe { Vecx::from_raw_parts(leaked.as_mut_ptr(), 4, 4) };
+    assert_eq!(v.len(), 4);
+
+    let empty: &'static mut [String] = Vecx::new().leak();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn into_boxed_slice_drops_spare_capacity() {
+    let boxed = numbers::<u32>(3, 100).into_boxed_slice();
+    assert_eq!(&*boxed, [0, 1, 2]);
+    // Box 用精确的布局释放，容量已经缩到正好 3 个
+    let v: Vec<u32> = boxed.into_vec();
+    assert_eq!(v.capacity(), 3);
+
+    let empty: Box<[u32]> = numbers::<u32>(0, 16).into_boxed_slice();
+    assert!(empty.is_empty());
+    let empty: Box<[String]> = Vecx::new().into_boxed_slice();
+    assert!(empty.is_empty());
+
+    let mut units = Vecx::new();
+    for _ in 0..7 {
+        units.push(());
+    }
+    let boxed = units.into_boxed_slice();
+    assert_eq!(boxed.len(), 7);
+}
+
+// 缓冲区经 raw parts 进出 Vecx 时记为 adopted / released，
+// 这样 `allocations + adopted - deallocations - released` 始终是活着的缓冲区个数
+#[cfg(feature = "telemetry")]
+#[test]
+fn telemetry_records_raw_parts_transfers() {
+    use test_demo::telemetry;
+
+    // 注册表按类型名统计，用一个只在这里出现的类型，不受其他测试影响
+    #[derive(Clone, Copy)]
+    struct Transfer(#[allow(dead_code)] u64);
+
+    let stats = || telemetry::snapshot().of::<Transfer>().cloned().unwrap_or_default();
+    let live = |s: &telemetry::TypeStats| s.allocations + s.adopted - s.deallocations - s.released;
+
+    let mut v = Vecx::with_capacity(4);
+    for i in 0..4 {
+        v.push(Transfer(i));
+    }
+    let (raw, len, cap) = v.into_raw_parts();
+    let s = stats();
+    assert_eq!((s.allocations, s.released, live(&s)), (1, 1, 0));
+
+    let v = unsafe { Vecx::from_raw_parts(raw, len, cap) };
+    let s = stats();
+    assert_eq!((s.adopted, live(&s)), (1, 1));
+
+    let leaked = v.leak();
+    assert_eq!(live(&stats()), 0);
+    let v = unsafe { Vecx::from_raw_parts(leaked.as_mut_ptr(), len, cap) };
+
+    let boxed = v.into_boxed_slice();
+    let s = stats();
+    assert_eq!((s.adopted, s.released, live(&s)), (2, 3, 0));
+    drop(boxed);
+
+    // 没有分配的缓冲区不算转移
+    let (raw, len, cap) = Vecx::<Transfer>::new().into_raw_parts();
+    drop(unsafe { Vecx::from_raw_parts(raw, len, cap) });
+    let s = stats();
+    assert_eq!((s.adopted, s.released, s.deallocations), (2, 3, 0));
+}
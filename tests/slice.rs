@@ -0,0 +1,129 @@
+use test_demo::slice::{
+    chunks_exact_mut, chunks_mut, get_many_mut, group_by_mut, iter_mut, rchunks_mut, split_mut, windows_mut,
+    GetManyMutError,
+};
+
+fn shapes<'a>(iter: impl Iterator<Item = &'a mut [i32]>) -> Vec<Vec<i32>> {
+    iter.map(|s| s.to_vec()).collect()
+}
+
+#[test]
+fn chunks_match_std_with_remainders() {
+    for len in 0..12 {
+        for size in 1..6 {
+            let mut a: Vec<i32> = (0..len).collect();
+            let mut b = a.clone();
+            assert_eq!(shapes(chunks_mut(&mut a, size)), shapes(b.chunks_mut(size)));
+            assert_eq!(shapes(chunks_mut(&mut a, size).rev()), shapes(b.chunks_mut(size).rev()));
+            assert_eq!(chunks_mut(&mut a, size).len(), b.chunks_mut(size).len());
+            assert_eq!(shapes(chunks_exact_mut(&mut a, size)), shapes(b.chunks_exact_mut(size)));
+            assert_eq!(shapes(chunks_exact_mut(&mut a, size).rev()), shapes(b.chunks_exact_mut(size).rev()));
+            assert_eq!(shapes(rchunks_mut(&mut a, size)), shapes(b.rchunks_mut(size)));
+            assert_eq!(shapes(rchunks_mut(&mut a, size).rev()), shapes(b.rchunks_mut(size).rev()));
+            assert_eq!(rchunks_mut(&mut a, size).len(), b.rchunks_mut(size).len());
+        }
+    }
+}
+
+#[test]
+fn chunks_exact_remainder_and_mutation() {
+    let mut v: Vec<i32> = (0..7).collect();
+    let mut iter = chunks_exact_mut(&mut v, 3);
+    assert_eq!(iter.len(), 2);
+    for chunk in iter.by_ref() {
+        chunk[0] = -1;
+    }
+    let rem = iter.into_remainder();
+    assert_eq!(rem, [6]);
+    rem[0] = 60;
+    assert_eq!(v, [-1, 1, 2, -1, 4, 5, 60]);
+
+    let mut v: Vec<i32> = (0..5).collect();
+    // 最短的一块在末尾（chunks）或开头（rchunks）
+    chunks_mut(&mut v, 2).last().unwrap()[0] = 40;
+    rchunks_mut(&mut v, 2).last().unwrap()[0] = 100;
+    assert_eq!(v, [100, 1, 2, 3, 40]);
+
+    for x in iter_mut(&mut v).rev().take(2) {
+        *x = 0;
+    }
+    assert_eq!(v, [100, 1, 2, 0, 0]);
+}
+
+#[test]
+fn windows_mut_lends_overlapping_windows() {
+    let mut v: Vec<i32> = (1..=5).collect();
+    let mut windows = windows_mut(&mut v, 2);
+    assert_eq!(windows.len(), 4);
+    // 前缀和：每个窗口把前一个元素加到后一个上
+    while let Some(w) = windows.next() {
+        w[1] += w[0];
+    }
+    assert!(windows.is_empty());
+    assert_eq!(v, [1, 3, 6, 10, 15]);
+
+    let mut windows = windows_mut(&mut v, 3);
+    assert_eq!(windows.next_back().unwrap(), [6, 10, 15]);
+    assert_eq!(windows.next().unwrap(), [1, 3, 6]);
+    assert_eq!(windows.next().unwrap(), [3, 6, 10]);
+    assert!(windows.next().is_none() && windows.next_back().is_none());
+
+    // 窗口比切片长时一个都没有
+    assert!(windows_mut(&mut v, 6).next().is_none());
+    assert!(windows_mut(&mut [0i32; 0], 1).is_empty());
+}
+
+#[test]
+fn split_mut_matches_std() {
+    let cases: [&[i32]; 6] = [&[], &[0], &[1, 0, 2, 0, 0, 3], &[0, 0], &[1, 2, 3], &[0, 1, 0]];
+    for case in cases {
+        let mut a = case.to_vec();
+        let mut b = case.to_vec();
+        assert_eq!(shapes(split_mut(&mut a, |x| *x == 0)), shapes(b.split_mut(|x| *x == 0)));
+        assert_eq!(shapes(split_mut(&mut a, |x| *x == 0).rev()), shapes(b.split_mut(|x| *x == 0).rev()));
+    }
+
+    let mut v = vec![1, 0, 2, 3, 0, 4];
+    let mut iter = split_mut(&mut v, |x| *x == 0);
+    iter.next().unwrap()[0] = 10;
+    iter.next_back().unwrap()[0] = 40;
+    iter.next().unwrap().reverse();
+    assert!(iter.next().is_none());
+    assert_eq!(v, [10, 0, 3, 2, 0, 40]);
+}
+
+#[test]
+fn group_by_mut_matches_std() {
+    let cases: [&[i32]; 5] = [&[], &[1], &[1, 1, 2, 3, 3, 3, 1], &[1, 2, 3], &[5, 5, 5]];
+    for case in cases {
+        let mut a = case.to_vec();
+        let mut b = case.to_vec();
+        assert_eq!(shapes(group_by_mut(&mut a, |x, y| x == y)), shapes(b.chunk_by_mut(|x, y| x == y)));
+        assert_eq!(shapes(group_by_mut(&mut a, |x, y| x == y).rev()), shapes(b.chunk_by_mut(|x, y| x == y).rev()));
+    }
+
+    // 按升序段分组，每段内部排成降序
+    let mut v = vec![1, 2, 3, 2, 5, 0];
+    for run in group_by_mut(&mut v, |a, b| a < b) {
+        run.reverse();
+    }
+    assert_eq!(v, [3, 2, 1, 5, 2, 0]);
+}
+
+#[test]
+fn get_many_mut_checks_indices() {
+    let mut v = vec![1, 2, 3, 4];
+    let [a, b] = get_many_mut(&mut v, [3, 0]).unwrap();
+    std::mem::swap(a, b);
+    assert_eq!(v, [4, 2, 3, 1]);
+
+    assert_eq!(get_many_mut(&mut v, [0, 4]).unwrap_err(), GetManyMutError::IndexOutOfBounds { index: 4, len: 4 });
+    assert_eq!(get_many_mut(&mut v, [1, 2, 1]).unwrap_err(), GetManyMutError::OverlappingIndices { index: 1 });
+    assert!(get_many_mut(&mut v, [2, 2]).is_err());
+    assert!(get_many_mut(&mut [0i32; 0], [0]).is_err());
+    assert_eq!(get_many_mut(&mut v, []).unwrap().len(), 0);
+    assert_eq!(
+        GetManyMutError::IndexOutOfBounds { index: 9, len: 4 }.to_string(),
+        "index 9 out of bounds for slice of length 4"
+    );
+}
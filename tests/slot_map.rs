@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use test_demo::slot_map::{DenseSlotMap, Key, SlotMap};
+
+fn xorshift(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+#[test]
+fn stale_key_is_rejected_after_reinsert() {
+    let mut map = SlotMap::new();
+    let a = map.insert("a");
+    assert_eq!(map.remove(a), Some("a"));
+
+    // 新元素复用同一个槽位，代数加一
+    let b = map.insert("b");
+    assert_eq!(b.index(), a.index());
+    assert_eq!(b.generation(), a.generation() + 1);
+
+    assert_eq!(map.get(a), None);
+    assert_eq!(map.get_mut(a), None);
+    assert!(!map.contains_key(a));
+    assert_eq!(map.remove(a), None);
+    assert_eq!(map[b], "b");
+    assert_eq!(map.len(), 1);
+
+    let mut dense = DenseSlotMap::new();
+    let a = dense.insert("a");
+    dense.remove(a);
+    let b = dense.insert("b");
+    assert_eq!((b.index(), b.generation()), (a.index(), a.generation() + 1));
+    assert_eq!(dense.get(a), None);
+    assert_eq!(dense.remove(a), None);
+    assert_eq!(dense[b], "b");
+}
+
+#[test]
+#[should_panic(expected = "invalid slot map key")]
+fn indexing_with_stale_key_panics() {
+    let mut map = SlotMap::new();
+    let a = map.insert(1);
+    map.remove(a);
+    map.insert(2);
+    let _ = map[a];
+}
+
+#[test]
+fn free_list_is_reused() {
+    let mut map = SlotMap::new();
+    let keys: Vec<Key> = (0..10).map(|i| map.insert(i)).collect();
+    for &i in &[2, 5, 7] {
+        map.remove(keys[i]);
+    }
+    assert_eq!(map.len(), 7);
+
+    // 空闲链表后进先出，不开新的槽位
+    let reused: Vec<usize> = (0..3).map(|i| map.insert(100 + i).index()).collect();
+    assert_eq!(reused, [7, 5, 2]);
+    assert_eq!(map.insert(200).index(), 10);
+    assert_eq!(map.len(), 11);
+
+    let mut dense = DenseSlotMap::new();
+    let keys: Vec<Key> = (0..10).map(|i| dense.insert(i)).collect();
+    for &i in &[2, 5, 7] {
+        dense.remove(keys[i]);
+    }
+    let reused: Vec<usize> = (0..3).map(|i| dense.insert(100 + i).index()).collect();
+    assert_eq!(reused, [7, 5, 2]);
+    assert_eq!(dense.insert(200).index(), 10);
+}
+
+#[test]
+fn slot_map_iterates_in_index_order() {
+    let mut map = SlotMap::new();
+    let keys: Vec<Key> = (0..6).map(|i| map.insert(i * 10)).collect();
+    map.remove(keys[1]);
+    map.remove(keys[4]);
+    let items: Vec<(Key, i32)> = map.iter().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(items, [(keys[0], 0), (keys[2], 20), (keys[3], 30), (keys[5], 50)]);
+    assert_eq!(map.iter().len(), 4);
+
+    for (_, v) in &mut map {
+        *v += 1;
+    }
+    assert!(map.values().copied().eq([1, 21, 31, 51]));
+    assert!(map.keys().eq([keys[0], keys[2], keys[3], keys[5]]));
+}
+
+#[test]
+fn dense_iteration_is_consistent_after_swap_remove() {
+    let mut map = DenseSlotMap::new();
+    let keys: Vec<Key> = (0..8).map(|i| map.insert(i)).collect();
+
+    // 删除第 2 个，最后一个元素被换过去
+    assert_eq!(map.remove(keys[2]), Some(2));
+    assert_eq!(map.values(), [0, 1, 7, 3, 4, 5, 6]);
+    assert_eq!(map[keys[7]], 7);
+
+    // 删除最后一个不需要移动其他元素
+    assert_eq!(map.remove(keys[6]), Some(6));
+    assert_eq!(map.values(), [0, 1, 7, 3, 4, 5]);
+
+    // 删除第一个，之前被换过一次的元素不受影响
+    assert_eq!(map.remove(keys[0]), Some(0));
+    assert_eq!(map.values(), [5, 1, 7, 3, 4]);
+
+    // iter、keys、values 的顺序一致，每个 key 都能找回对应的元素
+    let from_iter: Vec<(Key, i32)> = map.iter().map(|(k, &v)| (k, v)).collect();
+    let from_keys: Vec<Key> = map.keys().collect();
+    assert_eq!(from_iter.len(), map.len());
+    for (pos, &(key, value)) in from_iter.iter().enumerate() {
+        assert_eq!(from_keys[pos], key);
+        assert_eq!(map.values()[pos], value);
+        assert_eq!(map[key], value);
+        assert_eq!(key, keys[value as usize]);
+    }
+
+    for (key, v) in &mut map {
+        *v += key.index() as i32 * 100;
+    }
+    for &i in &[1, 3, 4, 5, 7] {
+        assert_eq!(map[keys[i]], i as i32 * 101);
+    }
+}
+
+#[test]
+fn retain_and_drain_invalidate_removed_keys() {
+    let mut map = SlotMap::new();
+    let mut dense = DenseSlotMap::new();
+    let keys: Vec<(Key, Key)> = (0..20).map(|i| (map.insert(i), dense.insert(i))).collect();
+
+    map.retain(|_, v| *v % 3 == 0);
+    dense.retain(|_, v| *v % 3 == 0);
+    assert_eq!(map.len(), 7);
+    assert_eq!(dense.len(), 7);
+    for (i, &(k, d)) in keys.iter().enumerate() {
+        assert_eq!(map.contains_key(k), i % 3 == 0);
+        assert_eq!(dense.contains_key(d), i % 3 == 0);
+    }
+
+    // 提前 drop 的 drain 也会移除剩下的元素
+    let first = map.drain().next();
+    assert_eq!(first, Some((keys[0].0, 0)));
+    assert!(map.is_empty());
+    let mut drained: Vec<i32> = dense.drain().map(|(_, v)| v).collect();
+    drained.sort();
+    assert_eq!(drained, [0, 3, 6, 9, 12, 15, 18]);
+    assert!(dense.is_empty());
+    assert!(keys.iter().all(|&(k, d)| !map.contains_key(k) && !dense.contains_key(d)));
+
+    // 所有槽位都回到了空闲链表
+    let k = map.insert(1);
+    assert!(k.index() < 20 && k.generation() == 1);
+    map.clear();
+    assert_eq!(map.get(k), None);
+}
+
+#[test]
+fn insert_with_key_sees_its_own_key() {
+    let mut map = SlotMap::new();
+    let a = map.insert_with_key(|k| (k, "a"));
+    map.remove(a);
+    let b = map.insert_with_key(|k| (k, "b"));
+    assert_eq!(map[b], (b, "b"));
+
+    let mut dense = DenseSlotMap::new();
+    let keys: Vec<Key> = (0..5).map(|_| dense.insert_with_key(|k| k)).collect();
+    dense.remove(keys[1]);
+    assert!(dense.iter().all(|(k, &v)| k == v));
+}
+
+// 和 HashMap 做对照的随机操作
+#[test]
+fn random_operations_match_a_hash_map() {
+    let mut seed = 0x2545_f491_4f6c_dd1d;
+    let mut map = SlotMap::new();
+    let mut dense = DenseSlotMap::new();
+    let mut model: HashMap<u64, (Key, Key)> = HashMap::new();
+    let mut removed: Vec<(Key, Key)> = Vec::new();
+
+    for step in 0..5_000u64 {
+        let r = xorshift(&mut seed);
+        if !r.is_multiple_of(3) || model.is_empty() {
+            model.insert(step, (map.insert(step), dense.insert(step)));
+        } else {
+            let victim = *model.keys().nth((r / 3) as usize % model.len()).unwrap();
+            let (k, d) = model.remove(&victim).unwrap();
+            assert_eq!(map.remove(k), Some(victim));
+            assert_eq!(dense.remove(d), Some(victim));
+            removed.push((k, d));
+        }
+    }
+
+    assert_eq!(map.len(), model.len());
+    assert_eq!(dense.len(), model.len());
+    for (&value, &(k, d)) in &model {
+        assert_eq!(map.get(k), Some(&value));
+        assert_eq!(dense.get(d), Some(&value));
+    }
+    for &(k, d) in &removed {
+        assert_eq!(map.get(k), None);
+        assert_eq!(dense.get(d), None);
+    }
+    let mut values: Vec<u64> = dense.values().to_vec();
+    values.sort();
+    let mut expected: Vec<u64> = model.keys().copied().collect();
+    expected.sort();
+    assert_eq!(values, expected);
+    assert!(map.values().copied().eq(map.iter().map(|(_, &v)| v)));
+}
@@ -0,0 +1,146 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use test_demo::doubly_linked_list::DoublyLinkedList;
+use test_demo::snapshot::{encode, Scheme, Signer, SnapshotError, Verifier};
+use test_demo::vecx::Vecx;
+use test_demo::LinkedList::LinkedList;
+
+// 测试专用的 1024 位密钥，只为了让测试跑得快
+const SIGNER_KEY: &str = include_str!("keys/signer.pem");
+const SIGNER_PUB: &str = include_str!("keys/signer.pub.pem");
+const OTHER_KEY: &str = include_str!("keys/other.pem");
+
+fn signer(scheme: Scheme) -> Signer {
+    Signer::from_pkcs8_pem(SIGNER_KEY, scheme).unwrap()
+}
+
+fn verifier() -> Verifier {
+    Verifier::from_public_key_pem(SIGNER_PUB).unwrap()
+}
+
+fn sample() -> Vecx<(u32, String)> {
+    let mut v = Vecx::new();
+    v.push((1, "one".to_string()));
+    v.push((2, "two".to_string()));
+    v
+}
+
+// Snapshot 没有实现 Debug，不能直接 unwrap_err
+fn rejection(verifier: &Verifier, envelope: &str) -> SnapshotError {
+    verifier.verify(envelope).err().expect("envelope was accepted")
+}
+
+// 解开信封，改一改，再装回去
+fn rewrap(envelope: &str, f: impl FnOnce(&mut Vec<u8>)) -> String {
+    let mut bytes = STANDARD.decode(envelope).unwrap();
+    f(&mut bytes);
+    STANDARD.encode(bytes)
+}
+
+#[test]
+fn sign_and_verify_round_trip() {
+    let verifier = verifier();
+    for scheme in [Scheme::Pkcs1v15, Scheme::Pss] {
+        let envelope = signer(scheme).sign(&sample());
+        let snapshot = verifier.verify(&envelope).unwrap();
+        assert_eq!(snapshot.scheme, scheme);
+        assert_eq!(&snapshot.payload[..], &encode(&sample())[..]);
+        // 信封前后的空白会被忽略
+        assert!(verifier.verify(&format!("  {envelope}\n")).is_ok());
+    }
+}
+
+#[test]
+fn canonical_encoding_is_container_independent() {
+    let mut vecx = Vecx::new();
+    let mut doubly = DoublyLinkedList::new();
+    let mut single = LinkedList::new();
+    for i in [3u16, 1, 2] {
+        vecx.push(i);
+        doubly.push_back(i);
+    }
+    // 单链表从头部 push，倒着放进去才是同样的顺序
+    for i in [2u16, 1, 3] {
+        single.push(i);
+    }
+    let expected = [3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 1, 0, 2, 0];
+    assert_eq!(&encode(&vecx)[..], expected);
+    assert_eq!(&encode(&doubly)[..], expected);
+    assert_eq!(&encode(&single)[..], expected);
+
+    assert_eq!(&encode(&(Some(true), None::<u8>))[..], [1, 1, 0]);
+    assert_eq!(&encode("hi")[..], [2, 0, 0, 0, 0, 0, 0, 0, b'h', b'i']);
+}
+
+#[test]
+fn tampered_envelopes_are_rejected() {
+    let verifier = verifier();
+    let envelope = signer(Scheme::Pkcs1v15).sign(&sample());
+    let len = STANDARD.decode(&envelope).unwrap().len();
+
+    // 改 payload 的一个字节
+    let tampered = rewrap(&envelope, |b| b[50] ^= 1);
+    assert_eq!(rejection(&verifier, &tampered), SnapshotError::Tampered);
+    // 改签名的一个字节
+    let tampered = rewrap(&envelope, |b| b[len - 1] ^= 1);
+    assert_eq!(rejection(&verifier, &tampered), SnapshotError::Tampered);
+    // 头部也在签名范围内：换一种方案
+    let tampered = rewrap(&envelope, |b| b[5] = 2);
+    assert_eq!(rejection(&verifier, &tampered), SnapshotError::Tampered);
+}
+
+#[test]
+fn wrong_key_is_reported_separately() {
+    let other = Signer::from_pkcs8_pem(OTHER_KEY, Scheme::Pss).unwrap();
+    let envelope = other.sign(&sample());
+    assert_eq!(rejection(&verifier(), &envelope), SnapshotError::WrongKey);
+    assert!(other.verifier().verify(&envelope).is_ok());
+
+    // 只把指纹改成自己的也没用：签名仍然对不上
+    let forged = rewrap(&envelope, |b| b[6..38].copy_from_slice(verifier().fingerprint()));
+    assert_eq!(rejection(&verifier(), &forged), SnapshotError::Tampered);
+
+    // 自己签的信封把指纹改掉：先验签名，失败之后按指纹归类
+    let envelope = signer(Scheme::Pkcs1v15).sign(&sample());
+    let relabeled = rewrap(&envelope, |b| b[6] ^= 1);
+    assert_eq!(rejection(&verifier(), &relabeled), SnapshotError::WrongKey);
+}
+
+#[test]
+fn malformed_envelopes_are_rejected() {
+    let verifier = verifier();
+    let envelope = signer(Scheme::Pkcs1v15).sign_bytes(b"payload");
+    let malformed = |reason| Err(SnapshotError::Malformed(reason));
+    let check = |envelope: &str, reason| assert_eq!(verifier.verify(envelope).map(|_| ()), malformed(reason));
+
+    check("not base64!", "invalid base64");
+    check(&STANDARD.encode(b"VXSN"), "truncated header");
+    check(&rewrap(&envelope, |b| b[0] = b'X'), "bad magic");
+    check(&rewrap(&envelope, |b| b[4] = 9), "unsupported version");
+    check(&rewrap(&envelope, |b| b[5] = 0), "unknown scheme");
+    // payload 长度超出信封，包括会溢出的长度
+    check(&rewrap(&envelope, |b| b[38..46].copy_from_slice(&1000u64.to_le_bytes())), "truncated payload");
+    check(&rewrap(&envelope, |b| b[38..46].copy_from_slice(&u64::MAX.to_le_bytes())), "truncated payload");
+    check(&rewrap(&envelope, |b| b.truncate(46 + 7 + 1)), "truncated payload");
+    // 签名长度字段和实际长度不一致
+    check(&rewrap(&envelope, |b| b.pop().map(drop).unwrap()), "bad signature length");
+    check(&rewrap(&envelope, |b| b.push(0)), "bad signature length");
+}
+
+#[test]
+fn pem_loading() {
+    let signer = signer(Scheme::Pss);
+    let verifier = verifier();
+    // 从私钥推出的公钥和单独加载的公钥是同一把
+    assert_eq!(signer.verifier().fingerprint(), verifier.fingerprint());
+    assert!(verifier.verify(&signer.sign_bytes(b"")).is_ok());
+
+    // 公钥不能当私钥用，反之亦然
+    assert!(matches!(Signer::from_pkcs8_pem(SIGNER_PUB, Scheme::Pss), Err(SnapshotError::InvalidKey(_))));
+    assert!(matches!(Verifier::from_public_key_pem(SIGNER_KEY), Err(SnapshotError::InvalidKey(_))));
+    assert!(matches!(Verifier::from_public_key_pem("garbage"), Err(SnapshotError::InvalidKey(_))));
+    let truncated = &SIGNER_PUB[..SIGNER_PUB.len() / 2];
+    let err = Verifier::from_public_key_pem(truncated).err().unwrap();
+    assert!(err.to_string().starts_with("invalid key: "));
+}
@@ -0,0 +1,231 @@
+use std::cmp::{Ordering, Reverse};
+use std::panic::{self, AssertUnwindSafe};
+
+use test_demo::vecx::sort::{merge_sort, quicksort};
+use test_demo::vecx::Vecx;
+
+mod common;
+
+use common::{Drops, Tracked};
+
+// xorshift，测试里只需要可复现的伪随机数
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn vecx_of<T: Clone>(items: &[T]) -> Vecx<T> {
+    let mut v = Vecx::new();
+    v.extend_from_slice(items);
+    v
+}
+
+// 各种形状的输入：随机、有序、逆序、基本有序（少量随机交换）、大量重复
+fn patterns(len: usize, seed: u64) -> Vec<Vec<u32>> {
+    let mut rng = Rng(seed);
+    let random: Vec<u32> = (0..len).map(|_| rng.next() as u32).collect();
+    let sorted: Vec<u32> = (0..len as u32).collect();
+    let reversed: Vec<u32> = sorted.iter().rev().copied().collect();
+    let mut mostly_sorted = sorted.clone();
+    for _ in 0..len / 20 + 1 {
+        if len > 1 {
+            let (a, b) = (rng.next() as usize % len, rng.next() as usize % len);
+            mostly_sorted.swap(a, b);
+        }
+    }
+    let few_values: Vec<u32> = (0..len).map(|_| rng.next() as u32 % 4).collect();
+    let saw: Vec<u32> = (0..len as u32).map(|i| i % 37).collect();
+    vec![random, sorted, reversed, mostly_sorted, few_values, saw]
+}
+
+const LENS: &[usize] = &[0, 1, 2, 3, 19, 20, 21, 50, 100, 257, 1_000, 10_000];
+
+#[test]
+fn matches_std_on_all_patterns() {
+    for &len in LENS {
+        for (i, input) in patterns(len, len as u64 * 31 + 7).into_iter().enumerate() {
+            let mut expected = input.clone();
+            expected.sort();
+
+            let mut v = vecx_of(&input);
+            v.sort();
+            assert_eq!(&v[..], &expected[..], "sort, len {len}, pattern {i}");
+
+            let mut v = vecx_of(&input);
+            v.sort_unstable();
+            assert_eq!(&v[..], &expected[..], "sort_unstable, len {len}, pattern {i}");
+
+            let mut v = vecx_of(&input);
+            v.sort_by(|a, b| b.cmp(a));
+            assert!(v.iter().eq(expected.iter().rev()), "sort_by, len {len}, pattern {i}");
+
+            let mut v = vecx_of(&input);
+            v.sort_unstable_by(|a, b| b.cmp(a));
+            assert!(v.iter().eq(expected.iter().rev()), "sort_unstable_by, len {len}, pattern {i}");
+
+            let mut v = input.clone();
+            merge_sort(&mut v, |a, b| a < b);
+            assert_eq!(v, expected, "merge_sort, len {len}, pattern {i}");
+
+            let mut v = input.clone();
+            quicksort(&mut v, |a, b| a < b);
+            assert_eq!(v, expected, "quicksort, len {len}, pattern {i}");
+        }
+    }
+}
+
+#[test]
+fn stable_sorts_keep_equal_keys_in_order() {
+    for &len in LENS {
+        let mut rng = Rng(len as u64 + 1);
+        // (key, 原位置)，key 只有几种取值，大量相等
+        let input: Vec<(u32, usize)> = (0..len).map(|i| (rng.next() as u32 % 8, i)).collect();
+        let mut expected = input.clone();
+        expected.sort_by_key(|p| p.0);
+
+        let mut v = vecx_of(&input);
+        v.sort_by_key(|p| p.0);
+        assert_eq!(&v[..], &expected[..], "sort_by_key, len {len}");
+
+        let mut v = vecx_of(&input);
+        v.sort_by_cached_key(|p| p.0);
+        assert_eq!(&v[..], &expected[..], "sort_by_cached_key, len {len}");
+
+        let mut v = vecx_of(&input);
+        v.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(&v[..], &expected[..], "sort_by, len {len}");
+
+        // 逆序比较也要稳定：相等的元素仍然按原来的先后
+        let mut expected_rev = input.clone();
+        expected_rev.sort_by_key(|p| Reverse(p.0));
+        let mut v = vecx_of(&input);
+        v.sort_by(|a, b| b.0.cmp(&a.0));
+        assert_eq!(&v[..], &expected_rev[..], "reverse sort_by, len {len}");
+    }
+}
+
+#[test]
+fn unstable_by_key_and_cached_key() {
+    let words = ["delta", "a", "charlie", "bb", "echo", "ccc", "b"];
+    let mut v = vecx_of(&words);
+    v.sort_unstable_by_key(|w| w.len());
+    assert!(v.windows(2).all(|w| w[0].len() <= w[1].len()));
+
+    // cached key 的 key 函数每个元素只调用一次
+    let mut calls = 0;
+    let mut v = vecx_of(&words);
+    v.sort_by_cached_key(|w| {
+        calls += 1;
+        w.to_string()
+    });
+    assert_eq!(calls, words.len());
+    let mut expected = words.to_vec();
+    expected.sort();
+    assert_eq!(&v[..], &expected[..]);
+}
+
+#[test]
+fn zero_sized_elements() {
+    let mut v = Vecx::new();
+    for _ in 0..1_000 {
+        v.push(());
+    }
+    v.sort();
+    v.sort_unstable();
+    v.sort_by(|_, _| Ordering::Greater);
+    v.sort_by_cached_key(|_| 0);
+    assert_eq!(v.len(), 1_000);
+
+    let mut units = [(); 100];
+    merge_sort(&mut units, |_, _| true);
+    quicksort(&mut units, |_, _| true);
+}
+
+type SortFn = fn(&mut Vecx<Tracked>, &mut dyn FnMut(&Tracked, &Tracked) -> Ordering);
+
+// 比较到第 `panic_at` 次时 panic，之后检查每个元素都还在，并且 drop 时恰好一次
+fn check_panicking_compare(len: usize, panic_at: usize, sort: SortFn) {
+    let drops = Drops::new();
+    let mut rng = Rng(len as u64 * 13 + panic_at as u64);
+    let mut v = Vecx::new();
+    for id in 0..len {
+        v.push(drops.keyed(id, rng.next() as u32 % 16));
+    }
+
+    let mut count = 0;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        sort(&mut v, &mut |a, b| {
+            count += 1;
+            if count == panic_at {
+                panic!("comparator panicked");
+            }
+            a.key.cmp(&b.key)
+        })
+    }));
+    // 比较次数不够时不会 panic，那就必须排好序
+    if result.is_ok() {
+        assert!(v.windows(2).all(|w| w[0].key <= w[1].key), "len {len}, panic_at {panic_at}");
+    }
+
+    // 没有元素在排序中途被 drop
+    assert_eq!(drops.total(), 0);
+    let mut ids: Vec<usize> = v.iter().map(|t| t.id).collect();
+    ids.sort();
+    assert!(ids.into_iter().eq(0..len));
+
+    drop(v);
+    assert!(drops.each_once(len));
+}
+
+#[test]
+fn panicking_comparator_drops_each_element_once() {
+    let sorts: [SortFn; 3] = [
+        |v, f| v.sort_by(f),
+        |v, f| v.sort_unstable_by(f),
+        // key 函数里 panic
+        |v, f| {
+            v.sort_by_key(|t| {
+                f(t, t);
+                t.key
+            })
+        },
+    ];
+    for sort in sorts {
+        for len in [5, 21, 200, 1_500] {
+            for panic_at in [1, 7, len, len * 3] {
+                check_panicking_compare(len, panic_at, sort);
+            }
+        }
+    }
+}
+
+#[test]
+fn panicking_cached_key_drops_each_element_once() {
+    let drops = Drops::new();
+    let mut v = Vecx::new();
+    for id in 0..100 {
+        v.push(drops.keyed(id, 100 - id as u32));
+    }
+    let mut calls = 0;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        v.sort_by_cached_key(|t| {
+            calls += 1;
+            if calls == 50 {
+                panic!("key panicked");
+            }
+            t.key
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.total(), 0);
+    // key 在排序前全部算完，panic 时元素还没有动过
+    assert!(v.iter().map(|t| t.id).eq(0..100));
+    drop(v);
+    assert!(drops.each_once(100));
+}
@@ -0,0 +1,211 @@
+use std::mem;
+
+use test_demo::stringx::Stringx;
+use test_demo::vecx::Vecx;
+
+fn bytes(b: &[u8]) -> Vecx<u8> {
+    let mut v = Vecx::new();
+    v.extend_from_slice(b);
+    v
+}
+
+// "é" 占两个字节，"€" 占三个，下标 2 和 5 都落在字符中间
+const MIXED: &str = "aé€z";
+
+#[test]
+#[should_panic(expected = "insertion index is not a char boundary")]
+fn insert_inside_a_char_panics() {
+    Stringx::from(MIXED).insert(2, 'x');
+}
+
+#[test]
+#[should_panic(expected = "insertion index is not a char boundary")]
+fn insert_str_past_the_end_panics() {
+    Stringx::from(MIXED).insert_str(MIXED.len() + 1, "x");
+}
+
+#[test]
+#[should_panic(expected = "is not a char boundary")]
+fn remove_inside_a_char_panics() {
+    Stringx::from(MIXED).remove(5);
+}
+
+#[test]
+#[should_panic(expected = "cannot remove a char from the end of a string")]
+fn remove_at_the_end_panics() {
+    Stringx::from(MIXED).remove(MIXED.len());
+}
+
+#[test]
+#[should_panic(expected = "new length is not a char boundary")]
+fn truncate_inside_a_char_panics() {
+    Stringx::from(MIXED).truncate(4);
+}
+
+#[test]
+#[should_panic(expected = "split index is not a char boundary")]
+fn split_off_inside_a_char_panics() {
+    Stringx::from(MIXED).split_off(2);
+}
+
+#[test]
+#[should_panic(expected = "drain start is not a char boundary")]
+fn drain_start_inside_a_char_panics() {
+    Stringx::from(MIXED).drain(2..);
+}
+
+#[test]
+#[should_panic(expected = "drain end is not a char boundary")]
+fn drain_end_inside_a_char_panics() {
+    Stringx::from(MIXED).drain(..=3);
+}
+
+#[test]
+#[should_panic(expected = "drain end is out of bounds")]
+fn drain_past_the_end_panics() {
+    Stringx::from(MIXED).drain(..MIXED.len() + 1);
+}
+
+#[test]
+fn editing_on_char_boundaries() {
+    let mut s = Stringx::from(MIXED);
+    s.insert(3, '∑');
+    assert_eq!(s, "aé∑€z");
+    assert_eq!(s.remove(1), 'é');
+    assert_eq!(s.pop(), Some('z'));
+    assert_eq!(s, "a∑€");
+
+    // 比当前长度长的截断什么都不做
+    s.truncate(100);
+    s.truncate(4);
+    assert_eq!(s, "a∑");
+
+    let mut s = Stringx::from(MIXED);
+    let cap = s.capacity();
+    let tail = s.split_off(3);
+    assert_eq!((s.as_str(), tail.as_str()), ("aé", "€z"));
+    assert_eq!(s.capacity(), cap);
+    assert_eq!(s.split_off(s.len()), "");
+    assert_eq!(s.split_off(0), "aé");
+    assert_eq!(s, "");
+}
+
+#[test]
+fn drain_fully_consumed() {
+    let mut s = Stringx::from(MIXED);
+    let drained: String = s.drain(1..6).collect();
+    assert_eq!(drained, "é€");
+    assert_eq!(s, "az");
+
+    let mut s = Stringx::from(MIXED);
+    assert_eq!(s.drain(..).rev().collect::<String>(), "z€éa");
+    assert!(s.is_empty());
+}
+
+#[test]
+fn drain_dropped_early_still_removes_the_range() {
+    let mut s = Stringx::from("hello, wörld!");
+    {
+        let mut drain = s.drain(5..13);
+        assert_eq!(drain.next(), Some(','));
+        assert_eq!(drain.next_back(), Some('d'));
+        assert_eq!(drain.as_str(), " wörl");
+    }
+    assert_eq!(s, "hello!");
+
+    // 一个字符都没取就 drop
+    drop(s.drain(0..1));
+    assert_eq!(s, "ello!");
+    drop(s.drain(2..2));
+    assert_eq!(s, "ello!");
+}
+
+#[test]
+fn forgotten_drain_leaves_the_string_unchanged() {
+    let mut s = Stringx::from(MIXED);
+    let mut drain = s.drain(1..6);
+    assert_eq!(drain.next(), Some('é'));
+    mem::forget(drain);
+    // 字符串保持原样，依然是合法的 UTF-8
+    assert_eq!(s, MIXED);
+    s.push('!');
+    assert_eq!(s, "aé€z!");
+}
+
+#[test]
+fn from_utf8_reports_where_it_failed() {
+    // 合法前缀之后是一个不完整的三字节序列
+    let err = Stringx::from_utf8(bytes(b"ok\xE2\x82")).unwrap_err();
+    assert_eq!(err.valid_up_to(), 2);
+    assert_eq!(err.error_len(), None);
+    assert_eq!(err.as_bytes(), b"ok\xE2\x82");
+
+    // 一个不可能出现的字节
+    let err = Stringx::from_utf8(bytes(b"ab\xFFcd")).unwrap_err();
+    assert_eq!(err.valid_up_to(), 2);
+    assert_eq!(err.error_len(), Some(1));
+    assert_eq!(err.utf8_error().valid_up_to(), 2);
+
+    // "é" 之后是代理项编码（ED A0 80），在它的第二个字节处失败
+    let err = Stringx::from_utf8(bytes(b"\xC3\xA9\xED\xA0\x80")).unwrap_err();
+    assert_eq!(err.valid_up_to(), 2);
+    assert_eq!(err.error_len(), Some(1));
+
+    // 原来的字节原样还回来，不复制
+    let v = bytes(b"\xC0\x80");
+    let ptr = v.as_ptr();
+    let err = Stringx::from_utf8(v).unwrap_err();
+    assert_eq!(err.to_string(), err.utf8_error().to_string());
+    let back = err.into_bytes();
+    assert_eq!(back.as_ptr(), ptr);
+    assert_eq!(&back[..], b"\xC0\x80");
+
+    let ok = Stringx::from_utf8(bytes(MIXED.as_bytes())).unwrap();
+    assert_eq!(ok, MIXED);
+}
+
+#[test]
+fn from_utf8_lossy_replaces_each_invalid_sequence() {
+    let cases: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (b"plain", "plain"),
+        (b"a\xFFb", "a\u{FFFD}b"),
+        // 不完整的多字节序列整体替换成一个 U+FFFD
+        (b"a\xE2\x82b", "a\u{FFFD}b"),
+        // 连续的非法字节各自替换
+        (b"\xFF\xFE", "\u{FFFD}\u{FFFD}"),
+        // 过长编码和代理项
+        (b"\xC0\x80x\xED\xA0\x80", "\u{FFFD}\u{FFFD}x\u{FFFD}\u{FFFD}\u{FFFD}"),
+        // 合法的多字节字符混在中间
+        ("é".as_bytes(), "é"),
+        (b"\xF0\x9F\x98\x80\xF0\x9F\x98", "😀\u{FFFD}"),
+        (b"\x80\xC3\xA9\xE2\x82\xAC\xBF", "\u{FFFD}é€\u{FFFD}"),
+    ];
+    for &(input, expected) in cases {
+        assert_eq!(Stringx::from_utf8_lossy(input), expected, "input {input:?}");
+        // 和 std 的结果一致
+        assert_eq!(String::from_utf8_lossy(input), expected);
+    }
+}
+
+#[test]
+fn string_round_trip_is_zero_copy() {
+    let mut s = String::with_capacity(32);
+    s.push_str(MIXED);
+    let ptr = s.as_ptr();
+
+    let x = Stringx::from(s);
+    assert_eq!(x.as_ptr(), ptr);
+    assert_eq!(x.capacity(), 32);
+    assert_eq!(x, MIXED);
+
+    let mut back = String::from(x);
+    assert_eq!(back.as_ptr(), ptr);
+    assert_eq!(back.capacity(), 32);
+    back.push('!');
+    assert_eq!(back, "aé€z!");
+
+    // 没有分配的空字符串也能来回转换
+    let empty = String::from(Stringx::from(String::new()));
+    assert!(empty.is_empty());
+}
@@ -0,0 +1,745 @@
+// 多线程压力测试，结构和 main.rs 的计数器一样：起一批线程同时操作，join 之后检查结果
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use test_demo::sync::{epoch, mpmc, spsc, AtomicStack, Backoff, OnceSlot, SeqLock, ShardedCounter, TryPopError, TryPushError};
+
+mod common;
+
+use common::Drops;
+
+const THREADS: usize = 16;
+
+fn run<F>(threads: usize, f: F)
+where
+    F: Fn(usize) + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    let handles: Vec<_> = (0..threads)
+        .map(|i| {
+            let f = f.clone();
+            thread::spawn(move || f(i))
+        })
+        .collect();
+    handles.into_iter().for_each(|handle| handle.join().unwrap());
+}
+
+#[test]
+fn sharded_counter_counts_every_increment() {
+    let counter = Arc::new(ShardedCounter::new());
+    let c = counter.clone();
+    run(THREADS, move |i| {
+        for _ in 0..10_000 {
+            c.increment();
+        }
+        c.add(i);
+    });
+    let extra: usize = (0..THREADS).sum();
+    assert_eq!(counter.get(), THREADS * 10_000 + extra);
+}
+
+#[test]
+fn sharded_counter_with_fewer_shards_than_threads() {
+    let mut counter = ShardedCounter::with_shards(2);
+    let shared = Arc::new(ShardedCounter::with_shards(2));
+    let c = shared.clone();
+    run(THREADS, move |_| {
+        for _ in 0..5_000 {
+            c.increment();
+        }
+    });
+    assert_eq!(shared.get(), THREADS * 5_000);
+
+    counter.add(7);
+    assert_eq!(counter.get(), 7);
+    counter.reset();
+    assert_eq!(counter.get(), 0);
+}
+
+#[test]
+#[should_panic(expected = "shard count must be non-zero")]
+fn sharded_counter_rejects_zero_shards() {
+    ShardedCounter::with_shards(0);
+}
+
+#[test]
+fn seqlock_readers_never_see_torn_values() {
+    // 写者总是写入 (n, !n, n)，读者检查三个字段是否一致
+    let lock = Arc::new(SeqLock::new((0u64, !0u64, 0u64)));
+    let done = Arc::new(AtomicBool::new(false));
+    let torn = Arc::new(AtomicUsize::new(0));
+
+    let writers: Vec<_> = (0..4)
+        .map(|w| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for n in 0..20_000u64 {
+                    let v = n * 4 + w;
+                    lock.write((v, !v, v));
+                }
+            })
+        })
+        .collect();
+    let readers: Vec<_> = (0..THREADS - 4)
+        .map(|_| {
+            let (lock, done, torn) = (lock.clone(), done.clone(), torn.clone());
+            thread::spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    let (a, b, c) = lock.read();
+                    if a != c || b != !a {
+                        torn.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    writers.into_iter().for_each(|handle| handle.join().unwrap());
+    done.store(true, Ordering::Relaxed);
+    readers.into_iter().for_each(|handle| handle.join().unwrap());
+
+    assert_eq!(torn.load(Ordering::Relaxed), 0);
+    assert_eq!(lock.version(), 4 * 20_000 * 2);
+    let (a, b, c) = lock.read();
+    assert!(a == c && b == !a);
+}
+
+// 撕裂的字节可能不是合法的 char 或枚举判别值，读者只能在序号检查通过后才把它当成 T
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Payload {
+    Empty,
+    Text(char, char, u64),
+    Flag(bool, u32),
+}
+
+impl Payload {
+    fn make(n: u32) -> Payload {
+        match n % 3 {
+            0 => Payload::Empty,
+            1 => {
+                let c = char::from_u32(0x1F600 + n % 64).unwrap();
+                Payload::Text(c, c, n as u64)
+            }
+            _ => Payload::Flag(n.is_multiple_of(2), n),
+        }
+    }
+
+    fn consistent(self) -> bool {
+        match self {
+            Payload::Empty => true,
+            Payload::Text(a, b, n) => a == b && char::from_u32(0x1F600 + n as u32 % 64) == Some(a),
+            Payload::Flag(f, n) => f == n.is_multiple_of(2) && n % 3 == 2,
+        }
+    }
+}
+
+#[test]
+fn seqlock_enum_and_char_payload() {
+    let lock = Arc::new(SeqLock::new(Payload::Empty));
+    let done = Arc::new(AtomicBool::new(false));
+    let bad = Arc::new(AtomicUsize::new(0));
+
+    let writers: Vec<_> = (0..4u32)
+        .map(|w| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for n in 0..20_000u32 {
+                    lock.write(Payload::make(n * 4 + w));
+                }
+            })
+        })
+        .collect();
+    let readers: Vec<_> = (0..THREADS - 4)
+        .map(|_| {
+            let (lock, done, bad) = (lock.clone(), done.clone(), bad.clone());
+            thread::spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    if !lock.read().consistent() {
+                        bad.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    writers.into_iter().for_each(|handle| handle.join().unwrap());
+    done.store(true, Ordering::Relaxed);
+    readers.into_iter().for_each(|handle| handle.join().unwrap());
+
+    assert_eq!(bad.load(Ordering::Relaxed), 0);
+    assert!(lock.read().consistent());
+}
+
+#[test]
+fn seqlock_single_thread() {
+    let mut lock = SeqLock::new(1);
+    assert_eq!(lock.read(), 1);
+    lock.write(2);
+    assert_eq!(lock.version(), 2);
+    *lock.get_mut() += 1;
+    assert_eq!(lock.into_inner(), 3);
+}
+
+#[test]
+fn once_slot_initializes_exactly_once() {
+    let slot = Arc::new(OnceSlot::new());
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (s, c) = (slot.clone(), calls.clone());
+    run(THREADS, move |i| {
+        let v = s.get_or_init(|| {
+            c.fetch_add(1, Ordering::Relaxed);
+            thread::yield_now();
+            format!("thread {}", i)
+        });
+        assert!(v.starts_with("thread "));
+        assert_eq!(s.get(), Some(v));
+    });
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert!(slot.get().is_some());
+}
+
+#[test]
+fn once_slot_set_races() {
+    let slot = Arc::new(OnceSlot::new());
+    let wins = Arc::new(AtomicUsize::new(0));
+    let (s, w) = (slot.clone(), wins.clone());
+    run(THREADS, move |i| {
+        match s.set(i) {
+            Ok(()) => {
+                w.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(v) => assert_eq!(v, i),
+        }
+    });
+    assert_eq!(wins.load(Ordering::Relaxed), 1);
+    let winner = *slot.get().unwrap();
+    assert!(winner < THREADS);
+}
+
+#[test]
+fn once_slot_retries_after_panicking_init() {
+    let slot = OnceSlot::new();
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        slot.get_or_init(|| -> i32 { panic!("boom") });
+    }));
+    assert!(r.is_err());
+    assert_eq!(slot.get(), None);
+    assert_eq!(*slot.get_or_init(|| 5), 5);
+}
+
+#[test]
+fn once_slot_take_and_drop() {
+    let counter = Arc::new(());
+    let mut slot = OnceSlot::new();
+    assert!(slot.take().is_none());
+    slot.set(counter.clone()).unwrap();
+    assert_eq!(Arc::strong_count(&counter), 2);
+    assert!(slot.take().is_some());
+    assert_eq!(Arc::strong_count(&counter), 1);
+
+    let slot = OnceSlot::new();
+    slot.set(counter.clone()).unwrap();
+    drop(slot);
+    assert_eq!(Arc::strong_count(&counter), 1);
+
+    let slot = OnceSlot::new();
+    slot.set(counter.clone()).unwrap();
+    assert!(slot.into_inner().is_some());
+    assert_eq!(Arc::strong_count(&counter), 1);
+}
+
+#[test]
+fn backoff_spin_lock_protects_counter() {
+    // 用 Backoff 做一个最简单的自旋锁，检查互斥
+    let locked = Arc::new(AtomicBool::new(false));
+    let value = Arc::new(AtomicUsize::new(0));
+    let (l, v) = (locked.clone(), value.clone());
+    run(THREADS, move |_| {
+        for _ in 0..2_000 {
+            let backoff = Backoff::new();
+            while l
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                backoff.spin();
+            }
+            // 非原子的读-改-写，只有在互斥成立时结果才正确
+            let n = v.load(Ordering::Relaxed);
+            v.store(n + 1, Ordering::Relaxed);
+            l.store(false, Ordering::Release);
+        }
+    });
+    assert_eq!(value.load(Ordering::Relaxed), THREADS * 2_000);
+}
+
+#[test]
+fn backoff_completes_after_snoozing() {
+    let backoff = Backoff::new();
+    let mut steps = 0;
+    while !backoff.is_completed() {
+        backoff.snooze();
+        steps += 1;
+    }
+    assert!(steps > 0);
+    backoff.reset();
+    assert!(!backoff.is_completed());
+    for _ in 0..100 {
+        backoff.spin();
+    }
+    // spin 不会让退避进入“完成”状态
+    assert!(!backoff.is_completed());
+}
+
+#[test]
+fn atomic_stack_single_thread() {
+    let stack = AtomicStack::new();
+    assert!(stack.is_empty());
+    assert_eq!(stack.pop(), None);
+    for i in 0..5 {
+        stack.push(i);
+    }
+    assert_eq!(stack.pop(), Some(4));
+    let list = stack.take_all();
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2, 1, 0]);
+    assert!(stack.is_empty());
+    assert!(stack.take_all().is_empty());
+}
+
+#[test]
+fn atomic_stack_push_pop_stress() {
+    const PER_THREAD: usize = 20_000;
+    let stack = Arc::new(AtomicStack::new());
+    let drops = Drops::new();
+    let seen: Arc<Vec<AtomicBool>> = Arc::new((0..THREADS * PER_THREAD).map(|_| AtomicBool::new(false)).collect());
+
+    let (s, d, seen2) = (stack.clone(), drops.clone(), seen.clone());
+    run(THREADS, move |t| {
+        for i in 0..PER_THREAD {
+            s.push(d.track(t * PER_THREAD + i));
+            // 每 push 两次 pop 一次，让栈里一直有元素，也让 push 和 pop 互相竞争
+            if i & 1 == 1 {
+                let item = s.pop().unwrap();
+                assert!(!seen2[item.id].swap(true, Ordering::Relaxed), "popped twice");
+            }
+        }
+    });
+
+    for item in stack.take_all() {
+        assert!(!seen[item.id].swap(true, Ordering::Relaxed), "popped twice");
+    }
+    assert!(seen.iter().all(|s| s.load(Ordering::Relaxed)), "lost an element");
+    assert_eq!(drops.total(), THREADS * PER_THREAD);
+}
+
+#[test]
+fn atomic_stack_take_all_races_with_pop() {
+    const PER_THREAD: usize = 10_000;
+    let stack = Arc::new(AtomicStack::new());
+    let total = Arc::new(AtomicUsize::new(0));
+    let (s, n) = (stack.clone(), total.clone());
+    run(THREADS, move |t| {
+        for i in 0..PER_THREAD {
+            s.push(i);
+            match t % 3 {
+                0 => n.fetch_add(s.take_all().len(), Ordering::Relaxed),
+                1 => n.fetch_add(s.pop().is_some() as usize, Ordering::Relaxed),
+                _ => 0,
+            };
+        }
+    });
+    total.fetch_add(stack.take_all().len(), Ordering::Relaxed);
+    assert_eq!(total.load(Ordering::Relaxed), THREADS * PER_THREAD);
+}
+
+#[test]
+fn atomic_stack_drops_remaining_elements() {
+    let drops = Drops::new();
+    let stack = AtomicStack::new();
+    for value in 0..100 {
+        stack.push(drops.track(value));
+    }
+    drop(stack.pop());
+    drop(stack);
+    assert_eq!(drops.total(), 100);
+}
+
+// drop 时把自己的孩子也交给 epoch 延迟释放，无锁树、链表的节点通常这样写
+struct Node {
+    children: Vec<*mut Node>,
+    drops: Arc<AtomicUsize>,
+}
+
+unsafe impl Send for Node {}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let guard = epoch::pin();
+        for &child in &self.children {
+            unsafe { guard.defer_destroy(child) };
+        }
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn node(children: Vec<*mut Node>, drops: &Arc<AtomicUsize>) -> *mut Node {
+    Box::into_raw(Box::new(Node { children, drops: drops.clone() }))
+}
+
+#[test]
+fn epoch_destructor_can_defer_more_garbage() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    // 一条 50 个节点的链，再加上一个有 200 个孩子的节点：
+    // 后者在析构函数里攒满垃圾袋，会在析构函数里再触发一次 flush
+    let mut head = node(Vec::new(), &drops);
+    for _ in 1..50 {
+        head = node(vec![head], &drops);
+    }
+    let leaves = (0..200).map(|_| node(Vec::new(), &drops)).collect();
+    let wide = node(leaves, &drops);
+    let total = 50 + 1 + 200;
+
+    {
+        let guard = epoch::pin();
+        unsafe {
+            guard.defer_destroy(head);
+            guard.defer_destroy(wide);
+        }
+    }
+
+    // 其他测试的线程可能暂时 pin 在旧的 epoch 上，多试几次
+    for _ in 0..100_000 {
+        if drops.load(Ordering::Relaxed) == total {
+            break;
+        }
+        epoch::pin().flush();
+        thread::yield_now();
+    }
+    assert_eq!(drops.load(Ordering::Relaxed), total);
+}
+
+// 每个值恰好出现一次：没有丢失，也没有重复
+fn assert_each_once(seen: &[AtomicBool]) {
+    for (i, flag) in seen.iter().enumerate() {
+        assert!(flag.load(Ordering::Relaxed), "value {i} was lost");
+    }
+}
+
+#[test]
+fn spsc_delivers_every_value_in_order() {
+    const N: usize = 200_000;
+    let (mut tx, mut rx) = spsc::channel(8);
+    let producer = thread::spawn(move || {
+        for i in 0..N {
+            // 一半用阻塞的 push，一半自己轮询 try_push
+            if i % 2 == 0 {
+                tx.push(i).unwrap();
+            } else {
+                let mut value = i;
+                while let Err(err) = tx.try_push(value) {
+                    value = err.into_inner();
+                    thread::yield_now();
+                }
+            }
+        }
+    });
+    let mut expected = 0;
+    while let Some(value) = rx.pop() {
+        assert_eq!(value, expected);
+        expected += 1;
+    }
+    producer.join().unwrap();
+    assert_eq!(expected, N);
+    assert_eq!(rx.try_pop(), Err(TryPopError::Disconnected));
+}
+
+#[test]
+fn spsc_blocking_push_and_pop_wake_up() {
+    let (mut tx, mut rx) = spsc::channel(2);
+    assert_eq!(tx.capacity(), 2);
+
+    // 消费者先阻塞在空队列上，生产者稍后写入
+    let consumer = thread::spawn(move || {
+        let first = rx.pop();
+        (rx, first)
+    });
+    thread::sleep(Duration::from_millis(50));
+    tx.push(1).unwrap();
+    let (mut rx, first) = consumer.join().unwrap();
+    assert_eq!(first, Some(1));
+
+    // 生产者阻塞在满队列上，消费者取走一个后继续
+    tx.push(2).unwrap();
+    tx.push(3).unwrap();
+    assert!(matches!(tx.try_push(4), Err(TryPushError::Full(4))));
+    let producer = thread::spawn(move || {
+        tx.push(4).unwrap();
+        tx
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.pop(), Some(2));
+    let tx = producer.join().unwrap();
+    assert_eq!(tx.len(), 2);
+    drop(tx);
+    assert_eq!(rx.by_ref().collect::<Vec<_>>(), [3, 4]);
+}
+
+#[test]
+fn spsc_disconnects_when_the_other_side_drops() {
+    let (mut tx, rx) = spsc::channel::<usize>(4);
+    tx.push(1).unwrap();
+    drop(rx);
+    assert!(tx.is_disconnected());
+    assert!(matches!(tx.try_push(2), Err(TryPushError::Disconnected(2))));
+    assert_eq!(tx.push(3).unwrap_err().0, 3);
+
+    // 生产者关闭后，消费者先取完剩下的元素，然后才看到 Disconnected
+    let (mut tx, mut rx) = spsc::channel(4);
+    tx.push(1).unwrap();
+    drop(tx);
+    assert!(rx.is_disconnected());
+    assert_eq!(rx.try_pop(), Ok(1));
+    assert_eq!(rx.try_pop(), Err(TryPopError::Disconnected));
+    assert_eq!(rx.pop(), None);
+
+    // 阻塞在 pop 上的消费者被生产者的关闭唤醒
+    let (tx, mut rx) = spsc::channel::<usize>(4);
+    let consumer = thread::spawn(move || rx.pop());
+    thread::sleep(Duration::from_millis(50));
+    drop(tx);
+    assert_eq!(consumer.join().unwrap(), None);
+
+    // 阻塞在 push 上的生产者被消费者的关闭唤醒，拿回原值
+    let (mut tx, rx) = spsc::channel(1);
+    tx.push(0).unwrap();
+    let producer = thread::spawn(move || tx.push(7));
+    thread::sleep(Duration::from_millis(50));
+    drop(rx);
+    assert_eq!(producer.join().unwrap().unwrap_err().0, 7);
+}
+
+#[test]
+fn spsc_drops_elements_left_in_the_ring() {
+    let drops = Drops::new();
+    let (mut tx, mut rx) = spsc::channel(8);
+    // 让下标绕过一圈，剩下的元素跨越缓冲区末尾
+    for value in 0..13 {
+        tx.push(drops.track(value)).unwrap();
+        if value < 7 {
+            drop(rx.try_pop().unwrap());
+        }
+    }
+    assert_eq!(drops.total(), 7);
+    assert_eq!(rx.len(), 6);
+    drop(tx);
+    assert_eq!(drops.total(), 7);
+    drop(rx);
+    assert_eq!(drops.total(), 13);
+
+    // try_push 失败时原值随错误返回，不会被队列 drop
+    let (mut tx, rx) = spsc::channel(1);
+    tx.push(drops.track(0)).unwrap();
+    let back = tx.try_push(drops.track(1)).unwrap_err().into_inner();
+    assert_eq!(back.id, 1);
+    assert_eq!(drops.total(), 13);
+    drop((tx, rx));
+    assert_eq!(drops.total(), 14);
+    drop(back);
+    assert_eq!(drops.total(), 15);
+}
+
+#[test]
+fn mpmc_no_value_lost_or_duplicated() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const PER_PRODUCER: usize = 50_000;
+    let (tx, rx) = mpmc::channel(16);
+    let seen: Arc<Vec<AtomicBool>> = Arc::new((0..PRODUCERS * PER_PRODUCER).map(|_| AtomicBool::new(false)).collect());
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    tx.push(p * PER_PRODUCER + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let (rx, seen) = (rx.clone(), seen.clone());
+            thread::spawn(move || {
+                // 同一个生产者的值按顺序到达
+                let mut last = [None; PRODUCERS];
+                let mut count = 0;
+                for value in rx {
+                    assert!(!seen[value].swap(true, Ordering::Relaxed), "value {value} received twice");
+                    let p = value / PER_PRODUCER;
+                    assert!(last[p] < Some(value));
+                    last[p] = Some(value);
+                    count += 1;
+                }
+                count
+            })
+        })
+        .collect();
+    drop(rx);
+
+    producers.into_iter().for_each(|handle| handle.join().unwrap());
+    let total: usize = consumers.into_iter().map(|handle| handle.join().unwrap()).sum();
+    assert_eq!(total, PRODUCERS * PER_PRODUCER);
+    assert_each_once(&seen);
+}
+
+#[test]
+fn mpmc_capacity_one_rounds_up_to_two() {
+    let (tx, rx) = mpmc::channel(1);
+    assert_eq!(tx.capacity(), 2);
+    assert_eq!(rx.capacity(), 2);
+    tx.try_push(1).unwrap();
+    tx.try_push(2).unwrap();
+    assert!(matches!(tx.try_push(3), Err(TryPushError::Full(3))));
+    // 来回多转几圈，槽位的序号不会混淆“空”和“满”
+    for i in 3..100 {
+        assert_eq!(rx.try_pop(), Ok(i - 2));
+        tx.try_push(i).unwrap();
+        assert_eq!(tx.len(), 2);
+    }
+    assert_eq!(mpmc::channel::<u8>(3).0.capacity(), 4);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be non-zero")]
+fn mpmc_rejects_zero_capacity() {
+    mpmc::channel::<u8>(0);
+}
+
+#[test]
+fn mpmc_blocking_push_and_pop_wake_up() {
+    // 多个消费者阻塞在空队列上，每写入一个值唤醒其中一个
+    let (tx, rx) = mpmc::channel(2);
+    let consumers: Vec<_> = (0..4)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || rx.pop())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(50));
+    for i in 0..4 {
+        tx.push(i).unwrap();
+    }
+    let mut got: Vec<_> = consumers.into_iter().map(|handle| handle.join().unwrap().unwrap()).collect();
+    got.sort();
+    assert_eq!(got, [0, 1, 2, 3]);
+
+    // 多个生产者阻塞在满队列上
+    tx.push(10).unwrap();
+    tx.push(11).unwrap();
+    let producers: Vec<_> = (0..4)
+        .map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || tx.push(20 + i).unwrap())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(50));
+    let mut got: Vec<_> = (0..6).map(|_| rx.pop().unwrap()).collect();
+    producers.into_iter().for_each(|handle| handle.join().unwrap());
+    got.sort();
+    assert_eq!(got, [10, 11, 20, 21, 22, 23]);
+    assert!(rx.is_empty());
+}
+
+#[test]
+fn mpmc_disconnects_when_the_last_handle_drops() {
+    let (tx, rx) = mpmc::channel(4);
+    let rx2 = rx.clone();
+    tx.push(1).unwrap();
+    drop(rx);
+    // 还有一个消费者，没有断开
+    assert!(!tx.is_disconnected());
+    tx.push(2).unwrap();
+    drop(rx2);
+    assert!(tx.is_disconnected());
+    assert!(matches!(tx.try_push(3), Err(TryPushError::Disconnected(3))));
+    assert_eq!(tx.push(4).unwrap_err().0, 4);
+
+    let (tx, rx) = mpmc::channel(4);
+    let tx2 = tx.clone();
+    tx.push(1).unwrap();
+    drop(tx);
+    assert!(!rx.is_disconnected());
+    drop(tx2);
+    assert!(rx.is_disconnected());
+    assert_eq!(rx.try_pop(), Ok(1));
+    assert_eq!(rx.try_pop(), Err(TryPopError::Disconnected));
+
+    // 阻塞的两端都会被对端的关闭唤醒
+    let (tx, rx) = mpmc::channel::<usize>(2);
+    let consumers: Vec<_> = (0..3)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || rx.pop())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(50));
+    drop(tx);
+    assert!(consumers.into_iter().all(|handle| handle.join().unwrap().is_none()));
+
+    let (tx, rx) = mpmc::channel(2);
+    tx.push(0).unwrap();
+    tx.push(1).unwrap();
+    let producers: Vec<_> = (0..3)
+        .map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || tx.push(i + 5))
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(50));
+    drop(rx);
+    let mut back: Vec<_> = producers.into_iter().map(|handle| handle.join().unwrap().unwrap_err().0).collect();
+    back.sort();
+    assert_eq!(back, [5, 6, 7]);
+}
+
+#[test]
+fn mpmc_drops_elements_left_in_the_ring() {
+    let drops = Drops::new();
+    let (tx, rx) = mpmc::channel(8);
+    for value in 0..13 {
+        tx.push(drops.track(value)).unwrap();
+        if value < 7 {
+            drop(rx.try_pop().unwrap());
+        }
+    }
+    assert_eq!(drops.total(), 7);
+    let rx2 = rx.clone();
+    drop((tx, rx));
+    assert_eq!(drops.total(), 7);
+    drop(rx2);
+    assert_eq!(drops.total(), 13);
+
+    // 并发下也一样：生产者和消费者同时退出，剩下的每个元素只 drop 一次
+    let drops = Drops::new();
+    let (tx, rx) = mpmc::channel(64);
+    let popped = Arc::new(AtomicUsize::new(0));
+    run(4, {
+        let (drops, popped) = (drops.clone(), popped.clone());
+        move |t| {
+            for value in 0..1_000 {
+                if t % 2 == 0 {
+                    let _ = tx.try_push(drops.track(value));
+                } else if rx.try_pop().is_ok() {
+                    popped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    });
+    // run 持有的两端已经随闭包一起 drop，队列里剩下的元素也已释放
+    let created = 2 * 1_000;
+    assert_eq!(drops.total(), created);
+    assert!(popped.load(Ordering::Relaxed) <= created);
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "telemetry")]
+
+use test_demo::telemetry::{self, TypeStats};
+use test_demo::vecx::Vecx;
+
+// 注册表是全局的、按类型名统计，每个测试用只在自己里面出现的类型，互不影响
+fn stats<T>() -> TypeStats {
+    telemetry::snapshot().of::<T>().cloned().unwrap_or_default()
+}
+
+#[test]
+fn push_sequence_from_empty() {
+    #[derive(Clone, Copy)]
+    struct Pushed(#[allow(dead_code)] u64);
+
+    let mut v = Vecx::new();
+    assert_eq!(stats::<Pushed>(), TypeStats::default());
+
+    // 容量 1 开始每次翻倍：1 次分配，扩容到 2、4、…、128 共 7 次
+    for i in 0..100 {
+        v.push(Pushed(i));
+    }
+    let s = stats::<Pushed>();
+    assert_eq!((s.allocations, s.reallocations, s.deallocations), (1, 7, 0));
+    assert_eq!(s.bytes_requested, 8 * (1 + 2 + 4 + 8 + 16 + 32 + 64 + 128));
+    assert_eq!(s.peak_capacity, 128);
+    assert!(s.growth_histogram().eq((0..8).map(|i| (1 << i, 1))));
+
+    // pop 不会缩小容量，再 push 回去也不扩容
+    for _ in 0..50 {
+        v.pop();
+    }
+    for i in 0..78 {
+        v.push(Pushed(i));
+    }
+    assert_eq!(stats::<Pushed>().reallocations, 7);
+
+    // 第 129 个元素触发第 8 次扩容
+    v.push(Pushed(0));
+    assert_eq!(stats::<Pushed>().reallocations, 8);
+    assert_eq!(stats::<Pushed>().peak_capacity, 256);
+
+    drop(v);
+    let s = stats::<Pushed>();
+    assert_eq!((s.allocations, s.reallocations, s.deallocations), (1, 8, 1));
+}
+
+#[test]
+fn with_capacity_avoids_reallocation() {
+    #[derive(Clone, Copy)]
+    struct Reserved(#[allow(dead_code)] u32);
+
+    let mut v = Vecx::with_capacity(100);
+    for i in 0..100 {
+        v.push(Reserved(i));
+    }
+    let s = stats::<Reserved>();
+    assert_eq!((s.allocations, s.reallocations, s.deallocations), (1, 0, 0));
+    assert_eq!(s.bytes_requested, 400);
+
+    // reserve 按“翻倍”和“刚好够用”中较大的扩容，extend_from_slice 最多扩容一次
+    v.reserve(300);
+    assert_eq!(v.capacity(), 400);
+    v.extend_from_slice(&[Reserved(0); 300]);
+    let s = stats::<Reserved>();
+    assert_eq!((s.allocations, s.reallocations), (1, 1));
+    assert_eq!(s.bytes_requested, 400 + 1600);
+
+    // 一直用不到内存的 Vecx 和 ZST 都不会被记录
+    drop(v);
+    drop(Vecx::<Reserved>::new());
+    drop(Vecx::<Reserved>::with_capacity(0));
+    let s = stats::<Reserved>();
+    assert_eq!((s.allocations, s.reallocations, s.deallocations), (1, 1, 1));
+
+    struct Zst;
+    let mut zsts = Vecx::new();
+    for _ in 0..10 {
+        zsts.push(Zst);
+    }
+    drop(zsts);
+    assert!(telemetry::snapshot().of::<Zst>().is_none());
+}
+
+#[test]
+fn into_boxed_slice_shrinks_then_releases() {
+    #[derive(Clone, Copy)]
+    struct Boxed(#[allow(dead_code)] u16);
+
+    let mut v = Vecx::with_capacity(10);
+    for i in 0..3 {
+        v.push(Boxed(i));
+    }
+    let boxed = v.into_boxed_slice();
+    let s = stats::<Boxed>();
+    // 缩小到 3 个元素记为 shrink，不算扩容，之后缓冲区交给了 Box
+    assert_eq!((s.allocations, s.reallocations, s.shrinks, s.deallocations, s.released), (1, 0, 1, 0, 1));
+    assert_eq!(s.bytes_requested, 20);
+    assert_eq!(s.peak_capacity, 10);
+    assert!(s.growth_histogram().eq([(8, 1)]));
+    drop(boxed);
+
+    // 容量正好等于长度时不需要缩小
+    let mut v = Vecx::with_capacity(2);
+    v.push(Boxed(0));
+    v.push(Boxed(1));
+    drop(v.into_boxed_slice());
+    let s = stats::<Boxed>();
+    assert_eq!((s.allocations, s.shrinks, s.released), (2, 1, 2));
+
+    // 空的 Vecx 缩小到 0 就是释放，Box 里没有分配可交
+    drop(Vecx::<Boxed>::with_capacity(4).into_boxed_slice());
+    let s = stats::<Boxed>();
+    assert_eq!((s.allocations, s.shrinks, s.deallocations, s.released), (3, 1, 1, 2));
+    assert_eq!(s.allocations + s.adopted - s.deallocations - s.released, 0);
+}
+
+#[test]
+fn report_lists_the_type() {
+    struct Reported(#[allow(dead_code)] u8);
+
+    let mut v = Vecx::new();
+    v.push(Reported(1));
+    v.push(Reported(2));
+    let report = telemetry::report();
+    let name = std::any::type_name::<Reported>();
+    let line = report.lines().find(|l| l.starts_with(name)).expect("type missing from report");
+    let columns: Vec<&str> = line[name.len()..].split_whitespace().collect();
+    // allocs reallocs shrinks deallocs adopted released bytes peak
+    assert_eq!(columns, ["1", "1", "0", "0", "0", "0", "3", "2"]);
+}
@@ -0,0 +1,170 @@
+use std::mem;
+
+use test_demo::thin_vecx::ThinVecx;
+
+mod common;
+
+use common::Drops;
+
+#[global_allocator]
+static GLOBAL: common::Counting = common::Counting;
+
+#[test]
+fn one_pointer_wide() {
+    assert_eq!(mem::size_of::<ThinVecx<u8>>(), mem::size_of::<usize>());
+    assert_eq!(mem::size_of::<ThinVecx<String>>(), mem::size_of::<usize>());
+    assert_eq!(mem::size_of::<ThinVecx<()>>(), mem::size_of::<usize>());
+    // 指针非空，Option 不额外占空间
+    assert_eq!(mem::size_of::<Option<ThinVecx<u64>>>(), mem::size_of::<usize>());
+}
+
+#[test]
+fn empty_vectors_do_not_allocate() {
+    let before = common::allocs();
+    let a: ThinVecx<u64> = ThinVecx::new();
+    let b: ThinVecx<String> = ThinVecx::default();
+    let c: ThinVecx<u8> = ThinVecx::with_capacity(0);
+    let d: ThinVecx<u32> = std::iter::empty().collect();
+    let mut e = a.clone();
+    e.reserve(0);
+    e.extend_from_slice(&[]);
+    assert!(e.drain().next().is_none());
+    assert_eq!(e.pop(), None);
+    for v in [&a, &e] {
+        assert_eq!(v.len(), 0);
+        assert_eq!(v.capacity(), 0);
+    }
+    assert_eq!(b.capacity() + c.capacity() + d.capacity(), 0);
+    assert!(a.into_iter().next().is_none());
+    drop((b, c, d, e));
+    // 全都共用同一个只读的空头部
+    assert_eq!(common::allocs(), before);
+
+    // 第一次写入才分配
+    let mut v = ThinVecx::new();
+    v.push(1u8);
+    assert_eq!(common::allocs(), before + 1);
+}
+
+#[test]
+fn reserve_and_extend_from_slice() {
+    let mut v = ThinVecx::new();
+    v.reserve(100);
+    assert!(v.capacity() >= 100);
+    let cap = v.capacity();
+    let ptr = v.as_ptr();
+    for i in 0..100 {
+        v.push(i);
+    }
+    // 预留的空间内 push 不会重新分配
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(v.capacity(), cap);
+
+    v.reserve(0);
+    assert_eq!(v.capacity(), cap);
+
+    let tail: Vec<i32> = (100..1_000).collect();
+    let before = common::allocs();
+    v.extend_from_slice(&tail);
+    // 只扩容一次
+    assert_eq!(common::allocs(), before + 1);
+    assert!(v.iter().copied().eq(0..1_000));
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn reserve_overflow_panics() {
+    let mut v = ThinVecx::new();
+    v.push(1u8);
+    v.reserve(usize::MAX);
+}
+
+#[test]
+fn zero_sized_elements() {
+    let mut v = ThinVecx::new();
+    v.reserve(10);
+    assert_eq!(v.capacity(), usize::MAX);
+    for _ in 0..1_000 {
+        v.push(());
+    }
+    assert_eq!(v.len(), 1_000);
+    assert_eq!(v.capacity(), usize::MAX);
+    v.extend_from_slice(&[(); 24]);
+    assert_eq!(v.len(), 1_024);
+    assert_eq!(v.drain().count(), 1_024);
+    assert!(v.is_empty());
+
+    let mut w: ThinVecx<()> = ThinVecx::new();
+    w.push(());
+    assert_eq!(w.capacity(), usize::MAX);
+    assert_eq!(w.clone().len(), 1);
+    assert_eq!(w.into_iter().count(), 1);
+}
+
+#[test]
+fn drain_partially_consumed() {
+    let drops = Drops::new();
+    let mut v: ThinVecx<_> = (0..10).map(|i| drops.track(i)).collect();
+    {
+        let mut drain = v.drain();
+        assert_eq!(drain.next().map(|t| t.id), Some(0));
+        assert_eq!(drain.next_back().map(|t| t.id), Some(9));
+        assert_eq!(drops.total(), 2);
+    }
+    // 没取出的元素在 Drain drop 时 drop，向量变空但保留容量
+    assert_eq!(drops.total(), 10);
+    assert!(v.is_empty());
+    assert!(v.capacity() >= 10);
+
+    v.push(drops.track(10));
+    drop(v);
+    assert_eq!(drops.total(), 11);
+
+    // forget Drain 只会泄漏元素（这里是不需要 drop 的整数），向量本身依然可用
+    let mut v: ThinVecx<u32> = (0..4).collect();
+    mem::forget(v.drain());
+    assert!(v.is_empty());
+    v.push(7);
+    assert_eq!(&v[..], [7]);
+}
+
+#[test]
+fn into_iter_partially_consumed() {
+    let drops = Drops::new();
+    let v: ThinVecx<_> = (0..10).map(|i| drops.track(i)).collect();
+    let mut iter = v.into_iter();
+    assert_eq!(iter.size_hint(), (10, Some(10)));
+    assert_eq!(iter.next().map(|t| t.id), Some(0));
+    assert_eq!(iter.next_back().map(|t| t.id), Some(9));
+    assert_eq!(iter.size_hint(), (8, Some(8)));
+    drop(iter);
+    assert_eq!(drops.total(), 10);
+
+    let v: ThinVecx<_> = (0..5).map(|i| i.to_string()).collect();
+    assert_eq!(v.into_iter().rev().collect::<Vec<_>>(), ["4", "3", "2", "1", "0"]);
+}
+
+#[test]
+fn clone_is_deep_and_independent() {
+    let drops = Drops::new();
+    let mut a = ThinVecx::new();
+    a.extend_from_slice(&[drops.track(1), drops.track(2)]);
+    // extend_from_slice 的实参是临时数组，clone 之后原件已经 drop
+    assert_eq!(drops.total(), 2);
+
+    let mut b = a.clone();
+    assert_eq!(b.len(), 2);
+    assert_eq!(b.capacity(), 2);
+    b.push(drops.track(3));
+    b[0].id = 100;
+    assert!(a.iter().map(|t| t.id).eq([1, 2]));
+    assert!(b.iter().map(|t| t.id).eq([100, 2, 3]));
+    drop(a);
+    assert_eq!(drops.total(), 4);
+    drop(b);
+    assert_eq!(drops.total(), 7);
+
+    let s: ThinVecx<String> = ["x", "y"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(s.clone(), s);
+    assert_eq!(format!("{:?}", s), r#"["x", "y"]"#);
+}